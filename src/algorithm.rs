@@ -1,12 +1,24 @@
 use crate::{
+    audit::{AuditEvent, AuditOperation, AuditSink},
+    clock,
+    key::aesgcmsiv::{Aes256GcmSivSymmetricKeyAlgorithm, AES256GCMSIV_TAGBYTES},
+    key::passphrase::PassphraseSymmetricAlgorithm,
     key::sodiumoxide::{
         SodiumOxidePublicAsymmetricKeyAlgorithm, SodiumOxideSecretAsymmetricKeyAlgorithm,
         SodiumOxideSymmetricKeyAlgorithm,
     },
-    ByteSource, CryptoError,
+    nonce::{
+        aesgcmsiv::Aes256GcmSivSymmetricNonce,
+        sodiumoxide::{SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce},
+    },
+    ByteSource, CryptoError, Entry, EntryPath, HasFingerprint, HasPublicKey, StateKind,
+    StorableType, Storer, TypeStorer,
 };
+use async_recursion::async_recursion;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::{box_, secretbox};
+use std::{fmt, sync::Arc};
 
 #[async_trait]
 pub trait Algorithm {
@@ -17,12 +29,232 @@ pub trait Algorithm {
     async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError>;
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "t", content = "c")]
 pub enum ByteAlgorithm {
     SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm),
     SodiumOxideSecretAsymmetricKey(SodiumOxideSecretAsymmetricKeyAlgorithm),
     SodiumOxidePublicAsymmetricKey(SodiumOxidePublicAsymmetricKeyAlgorithm),
+    PassphraseSymmetric(PassphraseSymmetricAlgorithm),
+    Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKeyAlgorithm),
+    SizeLimited(SizeLimitedAlgorithm),
+    Compressed(CompressedAlgorithm),
+    Audited(AuditedAlgorithm),
+}
+
+/// Wraps another algorithm and refuses to seal or unseal sources larger than
+/// `max_size` bytes. The check happens before any key is resolved, so an oversized
+/// ciphertext is rejected without being decrypted.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SizeLimitedAlgorithm {
+    pub max_size: usize,
+    pub algorithm: Box<ByteAlgorithm>,
+}
+
+impl SizeLimitedAlgorithm {
+    fn check_plaintext_size(&self, source: &ByteSource) -> Result<(), CryptoError> {
+        Self::check_size(source, self.max_size)
+    }
+
+    /// Ciphertexts of a `max_size` plaintext carry the overhead of the wrapped
+    /// algorithm, so they are checked against the largest sealed length instead
+    fn check_ciphertext_size(&self, source: &ByteSource) -> Result<(), CryptoError> {
+        Self::check_size(source, self.algorithm.max_sealed_len(self.max_size))
+    }
+
+    fn check_size(source: &ByteSource, max: usize) -> Result<(), CryptoError> {
+        let size = source.len()?;
+        if size > max {
+            return Err(CryptoError::PayloadTooLarge { size, max });
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Algorithm for SizeLimitedAlgorithm {
+    type Source = ByteSource;
+    type Output = ByteSource;
+
+    async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        self.check_ciphertext_size(source)?;
+        self.algorithm.unseal(source).await
+    }
+
+    async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        self.check_plaintext_size(source)?;
+        self.algorithm.seal(source).await
+    }
+}
+
+/// Method used to compress plaintexts before they are sealed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum Compression {
+    None,
+    /// Zstandard, available with the `zstd` feature
+    #[cfg(feature = "zstd")]
+    Zstd,
+}
+
+impl Compression {
+    fn compress(&self, source: &ByteSource) -> Result<ByteSource, CryptoError> {
+        match self {
+            Self::None => Ok(source.clone()),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::encode_all(source.get()?, zstd::DEFAULT_COMPRESSION_LEVEL)
+                .map(ByteSource::from)
+                .map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                }),
+        }
+    }
+
+    /// Largest length a source of `len` bytes can compress to
+    fn max_compressed_len(&self, len: usize) -> usize {
+        match self {
+            Self::None => len,
+            #[cfg(feature = "zstd")]
+            Self::Zstd => zstd::zstd_safe::compress_bound(len),
+        }
+    }
+
+    /// Decompresses the source, failing with `CryptoError::DecompressedTooLarge` as soon
+    /// as more than `max_len` bytes come out, so that a small ciphertext cannot expand
+    /// into an unbounded allocation
+    #[cfg_attr(not(feature = "zstd"), allow(unused_variables))]
+    fn decompress(&self, source: ByteSource, max_len: usize) -> Result<ByteSource, CryptoError> {
+        match self {
+            Self::None => Ok(source),
+            #[cfg(feature = "zstd")]
+            Self::Zstd => {
+                use std::io::Read;
+
+                let internal_error = |e| CryptoError::InternalError {
+                    source: Box::new(e),
+                };
+                let mut decoder =
+                    zstd::stream::read::Decoder::new(source.get()?).map_err(internal_error)?;
+                let mut plaintext = vec![];
+                (&mut decoder)
+                    .take((max_len as u64).saturating_add(1))
+                    .read_to_end(&mut plaintext)
+                    .map_err(internal_error)?;
+                if plaintext.len() > max_len {
+                    return Err(CryptoError::DecompressedTooLarge { max: max_len });
+                }
+                Ok(plaintext.into())
+            }
+        }
+    }
+}
+
+/// Wraps another algorithm and compresses sources before sealing them, decompressing
+/// them again after unsealing. The compression method is stored with the algorithm in the
+/// sealed entry, so unsealing is transparent.
+///
+/// Compressing before encrypting makes the ciphertext length depend on the content of
+/// the plaintext. If an attacker can have their own data sealed alongside a secret and
+/// observe the resulting sizes, they can recover the secret byte by byte, as in the
+/// CRIME and BREACH attacks. Only compress payloads that never mix secrets with data an
+/// attacker controls.
+///
+/// Unsealing refuses to decompress more than `max_decompressed_size` bytes, which
+/// defaults to `DEFAULT_MAX_DECOMPRESSED_SIZE`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct CompressedAlgorithm {
+    pub compression: Compression,
+    #[serde(default = "default_max_decompressed_size")]
+    pub max_decompressed_size: usize,
+    pub algorithm: Box<ByteAlgorithm>,
+}
+
+/// Number of bytes above which decompressing an unsealed plaintext is refused unless
+/// configured otherwise
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 64 * 1024 * 1024;
+
+fn default_max_decompressed_size() -> usize {
+    DEFAULT_MAX_DECOMPRESSED_SIZE
+}
+
+#[async_trait]
+impl Algorithm for CompressedAlgorithm {
+    type Source = ByteSource;
+    type Output = ByteSource;
+
+    async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        let plaintext = self.algorithm.unseal(source).await?;
+        self.compression
+            .decompress(plaintext, self.max_decompressed_size)
+    }
+
+    async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        let compressed = self.compression.compress(source)?;
+        self.algorithm.seal(&compressed).await
+    }
+}
+
+/// Wraps another algorithm and reports every successful seal and unseal to an audit
+/// sink, see `crate::audit`. The sink is not serialized, so an algorithm read back from
+/// a stored entry is no longer audited until it is wrapped again.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AuditedAlgorithm {
+    pub algorithm: Box<ByteAlgorithm>,
+    #[serde(skip)]
+    pub sink: Option<Arc<dyn AuditSink>>,
+}
+
+impl fmt::Debug for AuditedAlgorithm {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditedAlgorithm")
+            .field("algorithm", &self.algorithm)
+            .field("sink", &self.sink.is_some())
+            .finish()
+    }
+}
+
+impl AuditedAlgorithm {
+    /// Reports a completed operation to the sink, if there is one. The operation has
+    /// already succeeded, so a failure to describe it is only reported as a `tracing`
+    /// warning when the `tracing` feature is enabled and never fails the operation.
+    async fn record(&self, operation: AuditOperation, ciphertext: &ByteSource) {
+        let sink = match self.sink {
+            Some(ref sink) => sink,
+            None => return,
+        };
+        let key_id = self.algorithm.key_id().await;
+        match (key_id, ciphertext.len()) {
+            (Ok(key_id), Ok(ciphertext_len)) => sink.record(AuditEvent {
+                operation,
+                key_id,
+                ciphertext_len,
+                timestamp: clock::now(),
+            }),
+            #[cfg(feature = "tracing")]
+            (Err(e), _) => tracing::warn!(error = %e, "audit event could not be recorded"),
+            #[cfg(feature = "tracing")]
+            (_, Err(e)) => tracing::warn!(error = %e, "audit event could not be recorded"),
+            #[cfg(not(feature = "tracing"))]
+            _ => (),
+        }
+    }
+}
+
+#[async_trait]
+impl Algorithm for AuditedAlgorithm {
+    type Source = ByteSource;
+    type Output = ByteSource;
+
+    async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        let plaintext = self.algorithm.unseal(source).await?;
+        self.record(AuditOperation::Unseal, source).await;
+        Ok(plaintext)
+    }
+
+    async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        let ciphertext = self.algorithm.seal(source).await?;
+        self.record(AuditOperation::Seal, &ciphertext).await;
+        Ok(ciphertext)
+    }
 }
 
 #[async_trait]
@@ -35,6 +267,11 @@ impl Algorithm for ByteAlgorithm {
             Self::SodiumOxideSymmetricKey(sosku) => sosku.unseal(source).await,
             Self::SodiumOxideSecretAsymmetricKey(sosaku) => sosaku.unseal(source).await,
             Self::SodiumOxidePublicAsymmetricKey(sopaku) => sopaku.unseal(source).await,
+            Self::PassphraseSymmetric(psa) => psa.unseal(source).await,
+            Self::Aes256GcmSivSymmetricKey(agsku) => agsku.unseal(source).await,
+            Self::SizeLimited(sla) => sla.unseal(source).await,
+            Self::Compressed(ca) => ca.unseal(source).await,
+            Self::Audited(aa) => aa.unseal(source).await,
         }
     }
 
@@ -43,8 +280,528 @@ impl Algorithm for ByteAlgorithm {
             Self::SodiumOxideSymmetricKey(sosku) => sosku.seal(source).await,
             Self::SodiumOxideSecretAsymmetricKey(sosaku) => sosaku.seal(source).await,
             Self::SodiumOxidePublicAsymmetricKey(sopaku) => sopaku.seal(source).await,
+            Self::PassphraseSymmetric(psa) => psa.seal(source).await,
+            Self::Aes256GcmSivSymmetricKey(agsku) => agsku.seal(source).await,
+            Self::SizeLimited(sla) => sla.seal(source).await,
+            Self::Compressed(ca) => ca.seal(source).await,
+            Self::Audited(aa) => aa.seal(source).await,
+        }
+    }
+}
+
+impl ByteAlgorithm {
+    /// Wraps this algorithm so that it refuses to seal or unseal sources larger than
+    /// `max_size` bytes. Wrapping an already limited algorithm replaces its limit.
+    pub fn with_max_size(self, max_size: usize) -> ByteAlgorithm {
+        let algorithm = match self {
+            Self::SizeLimited(sla) => sla.algorithm,
+            algorithm => Box::new(algorithm),
+        };
+        Self::SizeLimited(SizeLimitedAlgorithm {
+            max_size,
+            algorithm,
+        })
+    }
+
+    /// Returns the size limit of this algorithm or of the algorithm it wraps, if it is
+    /// limited. A limit found inside a compressing algorithm bounds the compressed size
+    /// of sources rather than their own size, see `with_compression`.
+    pub fn max_size(&self) -> Option<usize> {
+        match self {
+            Self::SizeLimited(sla) => Some(sla.max_size),
+            Self::Compressed(ca) => ca.algorithm.max_size(),
+            Self::Audited(aa) => aa.algorithm.max_size(),
+            _ => None,
+        }
+    }
+
+    /// Largest length a plaintext of `plaintext_len` bytes can have once sealed by this
+    /// algorithm, including authentication tags and any nonce stored with the ciphertext
+    pub(crate) fn max_sealed_len(&self, plaintext_len: usize) -> usize {
+        match self {
+            Self::SodiumOxideSymmetricKey(_) => plaintext_len.saturating_add(secretbox::MACBYTES),
+            Self::SodiumOxideSecretAsymmetricKey(_) | Self::SodiumOxidePublicAsymmetricKey(_) => {
+                plaintext_len.saturating_add(box_::MACBYTES)
+            }
+            Self::PassphraseSymmetric(_) => plaintext_len
+                .saturating_add(secretbox::NONCEBYTES)
+                .saturating_add(secretbox::MACBYTES),
+            Self::Aes256GcmSivSymmetricKey(_) => {
+                plaintext_len.saturating_add(AES256GCMSIV_TAGBYTES)
+            }
+            Self::SizeLimited(sla) => sla.algorithm.max_sealed_len(plaintext_len),
+            Self::Compressed(ca) => ca
+                .algorithm
+                .max_sealed_len(ca.compression.max_compressed_len(plaintext_len)),
+            Self::Audited(aa) => aa.algorithm.max_sealed_len(plaintext_len),
+        }
+    }
+
+    /// Wraps this algorithm so that every successful seal and unseal is reported to the
+    /// given sink, see `AuditedAlgorithm`. Wrapping an already audited algorithm replaces
+    /// its sink.
+    pub fn with_audit_sink(self, sink: Arc<dyn AuditSink>) -> ByteAlgorithm {
+        let algorithm = match self {
+            Self::Audited(aa) => aa.algorithm,
+            algorithm => Box::new(algorithm),
+        };
+        Self::Audited(AuditedAlgorithm {
+            algorithm,
+            sink: Some(sink),
+        })
+    }
+
+    /// Wraps this algorithm so that it compresses sources before sealing them, see
+    /// `CompressedAlgorithm` for when this is safe. Wrapping an already compressing
+    /// algorithm replaces its compression method. A size limit applied before this, as in
+    /// `algorithm.with_max_size(n).with_compression(c)`, is checked after compressing and
+    /// so bounds the compressed size; one applied afterwards bounds the source itself.
+    pub fn with_compression(self, compression: Compression) -> ByteAlgorithm {
+        let (algorithm, max_decompressed_size) = match self {
+            Self::Compressed(ca) => (ca.algorithm, ca.max_decompressed_size),
+            algorithm => (Box::new(algorithm), DEFAULT_MAX_DECOMPRESSED_SIZE),
+        };
+        Self::Compressed(CompressedAlgorithm {
+            compression,
+            max_decompressed_size,
+            algorithm,
+        })
+    }
+
+    /// Returns the method this algorithm compresses sources with before sealing them
+    pub fn compression(&self) -> Compression {
+        match self {
+            Self::SizeLimited(sla) => sla.algorithm.compression(),
+            Self::Compressed(ca) => ca.compression,
+            Self::Audited(aa) => aa.algorithm.compression(),
+            _ => Compression::None,
+        }
+    }
+
+    /// Marks this algorithm to authenticate the path of the entries it seals as associated
+    /// data, so that a ciphertext moved under another path fails verification. Only
+    /// AEAD algorithms accepting associated data can be bound, others return
+    /// `CryptoError::UnsupportedKeyAlgorithm`.
+    pub fn bind_path(self) -> Result<ByteAlgorithm, CryptoError> {
+        match self {
+            Self::Aes256GcmSivSymmetricKey(mut agsku) => {
+                agsku.bind_path = true;
+                Ok(Self::Aes256GcmSivSymmetricKey(agsku))
+            }
+            Self::SizeLimited(sla) => Ok(Self::SizeLimited(SizeLimitedAlgorithm {
+                max_size: sla.max_size,
+                algorithm: Box::new(sla.algorithm.bind_path()?),
+            })),
+            Self::Compressed(ca) => Ok(Self::Compressed(CompressedAlgorithm {
+                compression: ca.compression,
+                max_decompressed_size: ca.max_decompressed_size,
+                algorithm: Box::new(ca.algorithm.bind_path()?),
+            })),
+            Self::Audited(aa) => Ok(Self::Audited(AuditedAlgorithm {
+                algorithm: Box::new(aa.algorithm.bind_path()?),
+                sink: aa.sink,
+            })),
+            _ => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+
+    /// Whether this algorithm authenticates the path of the entries it seals
+    pub fn binds_path(&self) -> bool {
+        match self {
+            Self::Aes256GcmSivSymmetricKey(agsku) => agsku.bind_path,
+            Self::SizeLimited(sla) => sla.algorithm.binds_path(),
+            Self::Compressed(ca) => ca.algorithm.binds_path(),
+            Self::Audited(aa) => aa.algorithm.binds_path(),
+            _ => false,
+        }
+    }
+
+    /// Seals like `Algorithm::seal` for an entry stored at `path`, which is authenticated
+    /// along with the source if the algorithm binds paths
+    #[async_recursion]
+    pub(crate) async fn seal_for_path(
+        &self,
+        source: &ByteSource,
+        path: &str,
+    ) -> Result<ByteSource, CryptoError> {
+        match self {
+            Self::Aes256GcmSivSymmetricKey(agsku) if agsku.bind_path => {
+                agsku.seal_with_aad(source, path.as_bytes()).await
+            }
+            Self::SizeLimited(sla) => {
+                sla.check_plaintext_size(source)?;
+                sla.algorithm.seal_for_path(source, path).await
+            }
+            Self::Compressed(ca) => {
+                let compressed = ca.compression.compress(source)?;
+                ca.algorithm.seal_for_path(&compressed, path).await
+            }
+            Self::Audited(aa) => {
+                let ciphertext = aa.algorithm.seal_for_path(source, path).await?;
+                aa.record(AuditOperation::Seal, &ciphertext).await;
+                Ok(ciphertext)
+            }
+            _ => self.seal(source).await,
+        }
+    }
+
+    /// Unseals like `Algorithm::unseal` for an entry stored at `path`, failing
+    /// verification if the algorithm binds paths and the ciphertext was sealed elsewhere
+    #[async_recursion]
+    pub(crate) async fn unseal_for_path(
+        &self,
+        source: &ByteSource,
+        path: &str,
+    ) -> Result<ByteSource, CryptoError> {
+        match self {
+            Self::Aes256GcmSivSymmetricKey(agsku) if agsku.bind_path => {
+                agsku.unseal_with_aad(source, path.as_bytes()).await
+            }
+            Self::SizeLimited(sla) => {
+                sla.check_ciphertext_size(source)?;
+                sla.algorithm.unseal_for_path(source, path).await
+            }
+            Self::Compressed(ca) => {
+                let plaintext = ca.algorithm.unseal_for_path(source, path).await?;
+                ca.compression
+                    .decompress(plaintext, ca.max_decompressed_size)
+            }
+            Self::Audited(aa) => {
+                let plaintext = aa.algorithm.unseal_for_path(source, path).await?;
+                aa.record(AuditOperation::Unseal, source).await;
+                Ok(plaintext)
+            }
+            _ => self.unseal(source).await,
+        }
+    }
+
+    /// Unseals like `Algorithm::unseal`, deriving the key from the given passphrase if
+    /// this is a passphrase algorithm. Other algorithms ignore the passphrase.
+    #[async_recursion]
+    pub async fn unseal_with_passphrase(
+        &self,
+        source: &ByteSource,
+        passphrase: &[u8],
+    ) -> Result<ByteSource, CryptoError> {
+        match self {
+            Self::PassphraseSymmetric(psa) => psa.unseal_with_passphrase(source, passphrase),
+            Self::SizeLimited(sla) => {
+                sla.check_ciphertext_size(source)?;
+                sla.algorithm
+                    .unseal_with_passphrase(source, passphrase)
+                    .await
+            }
+            Self::Compressed(ca) => {
+                let plaintext = ca
+                    .algorithm
+                    .unseal_with_passphrase(source, passphrase)
+                    .await?;
+                ca.compression
+                    .decompress(plaintext, ca.max_decompressed_size)
+            }
+            Self::Audited(aa) => {
+                let plaintext = aa
+                    .algorithm
+                    .unseal_with_passphrase(source, passphrase)
+                    .await?;
+                aa.record(AuditOperation::Unseal, source).await;
+                Ok(plaintext)
+            }
+            _ => self.unseal(source).await,
+        }
+    }
+
+    /// Returns an identifier of the key this algorithm seals with which is safe to
+    /// record. Asymmetric algorithms are identified by the fingerprint of a public key,
+    /// symmetric ones by the path of their key entry and passphrase algorithms by their
+    /// salt, so that no digest of a secret key is ever exposed.
+    #[async_recursion]
+    pub async fn key_id(&self) -> Result<String, CryptoError> {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => Ok(sosku.key.path.clone()),
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => sosaku
+                .secret_key
+                .resolve()
+                .await?
+                .public_key()?
+                .fingerprint(),
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                sopaku.public_key.resolve().await?.fingerprint()
+            }
+            Self::PassphraseSymmetric(psa) => Ok(crate::key::fingerprint(psa.salt.as_ref())),
+            Self::Aes256GcmSivSymmetricKey(agsku) => Ok(agsku.key.path.clone()),
+            Self::SizeLimited(sla) => sla.algorithm.key_id().await,
+            Self::Compressed(ca) => ca.algorithm.key_id().await,
+            Self::Audited(aa) => aa.algorithm.key_id().await,
+        }
+    }
+
+    /// Returns the paths of all the referenced key entries this algorithm will fetch when
+    /// sealing or unsealing, including those needed to unseal its own sealed keys
+    pub fn referenced_paths(&self) -> Vec<EntryPath> {
+        let mut paths = vec![];
+        self.collect_referenced_paths(&mut paths);
+        paths
+    }
+
+    pub(crate) fn collect_referenced_paths(&self, paths: &mut Vec<EntryPath>) {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => sosku.key.value.collect_referenced_paths(paths),
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                sosaku.secret_key.value.collect_referenced_paths(paths);
+                if let Some(ref public_key) = sosaku.public_key {
+                    public_key.value.collect_referenced_paths(paths);
+                }
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                sopaku.public_key.value.collect_referenced_paths(paths);
+                sopaku.secret_key.value.collect_referenced_paths(paths);
+            }
+            Self::PassphraseSymmetric(_) => (),
+            Self::Aes256GcmSivSymmetricKey(agsku) => {
+                agsku.key.value.collect_referenced_paths(paths)
+            }
+            Self::SizeLimited(sla) => sla.algorithm.collect_referenced_paths(paths),
+            Self::Compressed(ca) => ca.algorithm.collect_referenced_paths(paths),
+            Self::Audited(aa) => aa.algorithm.collect_referenced_paths(paths),
         }
     }
+
+    /// Whether this algorithm carries any of its secret keys in the clear, in which case
+    /// serializing it, as is done when it is stored with a sealed entry, writes out the key
+    pub fn embeds_secret_key(&self) -> bool {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => sosku.key.value.kind() == StateKind::Unsealed,
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                sosaku.secret_key.value.kind() == StateKind::Unsealed
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                sopaku.secret_key.value.kind() == StateKind::Unsealed
+            }
+            Self::PassphraseSymmetric(_) => false,
+            Self::Aes256GcmSivSymmetricKey(agsku) => agsku.key.value.kind() == StateKind::Unsealed,
+            Self::SizeLimited(sla) => sla.algorithm.embeds_secret_key(),
+            Self::Compressed(ca) => ca.algorithm.embeds_secret_key(),
+            Self::Audited(aa) => aa.algorithm.embeds_secret_key(),
+        }
+    }
+
+    /// Verifies the chain of every key entry this algorithm depends on
+    #[async_recursion]
+    pub(crate) async fn verify_chain_integrity(&self) -> Result<(), CryptoError> {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => sosku.key.verify_chain_integrity().await,
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                sosaku.secret_key.verify_chain_integrity().await?;
+                match sosaku.public_key {
+                    Some(ref public_key) => public_key.verify_chain_integrity().await,
+                    None => Ok(()),
+                }
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                sopaku.public_key.verify_chain_integrity().await?;
+                sopaku.secret_key.verify_chain_integrity().await
+            }
+            Self::PassphraseSymmetric(_) => Ok(()),
+            Self::Aes256GcmSivSymmetricKey(agsku) => agsku.key.verify_chain_integrity().await,
+            Self::SizeLimited(sla) => sla.algorithm.verify_chain_integrity().await,
+            Self::Compressed(ca) => ca.algorithm.verify_chain_integrity().await,
+            Self::Audited(aa) => aa.algorithm.verify_chain_integrity().await,
+        }
+    }
+
+    /// Replaces the algorithm's nonce with a freshly generated one, so that its key can
+    /// safely seal another value
+    pub(crate) fn regenerate_nonce(&mut self) {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => {
+                sosku.nonce = SodiumOxideSymmetricNonce {
+                    nonce: secretbox::gen_nonce(),
+                }
+            }
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                sosaku.nonce = SodiumOxideAsymmetricNonce {
+                    nonce: box_::gen_nonce(),
+                }
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                sopaku.nonce = SodiumOxideAsymmetricNonce {
+                    nonce: box_::gen_nonce(),
+                }
+            }
+            // Passphrase algorithms generate a fresh nonce for every seal
+            Self::PassphraseSymmetric(_) => (),
+            Self::Aes256GcmSivSymmetricKey(agsku) => {
+                agsku.nonce = Aes256GcmSivSymmetricNonce::new()
+            }
+            Self::SizeLimited(sla) => sla.algorithm.regenerate_nonce(),
+            Self::Compressed(ca) => ca.algorithm.regenerate_nonce(),
+            Self::Audited(aa) => aa.algorithm.regenerate_nonce(),
+        }
+    }
+
+    /// Returns a copy of this algorithm that uses the nonce carried by `other` and keeps
+    /// everything else, including its keys, from `self`. Fails with
+    /// `CryptoError::WrongNonceType` if `other` is not the same kind of algorithm.
+    pub(crate) fn with_nonce_of(
+        &self,
+        other: &ByteAlgorithm,
+    ) -> Result<ByteAlgorithm, CryptoError> {
+        let mut algorithm = self.clone();
+        algorithm.copy_nonce_from(other)?;
+        Ok(algorithm)
+    }
+
+    fn copy_nonce_from(&mut self, other: &ByteAlgorithm) -> Result<(), CryptoError> {
+        match (self, other) {
+            (Self::SodiumOxideSymmetricKey(sosku), Self::SodiumOxideSymmetricKey(other)) => {
+                sosku.nonce = other.nonce.clone()
+            }
+            (
+                Self::SodiumOxideSecretAsymmetricKey(sosaku),
+                Self::SodiumOxideSecretAsymmetricKey(other),
+            ) => sosaku.nonce = other.nonce.clone(),
+            (
+                Self::SodiumOxidePublicAsymmetricKey(sopaku),
+                Self::SodiumOxidePublicAsymmetricKey(other),
+            ) => sopaku.nonce = other.nonce.clone(),
+            // Passphrase algorithms carry their nonce in the ciphertext
+            (Self::PassphraseSymmetric(_), Self::PassphraseSymmetric(_)) => (),
+            (Self::Aes256GcmSivSymmetricKey(agsku), Self::Aes256GcmSivSymmetricKey(other)) => {
+                agsku.nonce = other.nonce.clone()
+            }
+            (Self::SizeLimited(sla), Self::SizeLimited(other)) => {
+                return sla.algorithm.copy_nonce_from(&other.algorithm)
+            }
+            (Self::Compressed(ca), Self::Compressed(other)) => {
+                return ca.algorithm.copy_nonce_from(&other.algorithm)
+            }
+            (Self::Audited(aa), Self::Audited(other)) => {
+                return aa.algorithm.copy_nonce_from(&other.algorithm)
+            }
+            _ => return Err(CryptoError::WrongNonceType),
+        }
+        Ok(())
+    }
+
+    /// Replaces the storer of every referenced key entry in this algorithm, recursing
+    /// into the algorithms sealing its keys
+    pub(crate) fn rebind_storer(self, new_storer: &TypeStorer) -> ByteAlgorithm {
+        match self {
+            Self::SodiumOxideSymmetricKey(sosku) => {
+                Self::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                    key: rebind_key(sosku.key, new_storer),
+                    nonce: sosku.nonce,
+                })
+            }
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                Self::SodiumOxideSecretAsymmetricKey(SodiumOxideSecretAsymmetricKeyAlgorithm {
+                    secret_key: rebind_key(sosaku.secret_key, new_storer),
+                    nonce: sosaku.nonce,
+                    public_key: sosaku
+                        .public_key
+                        .map(|public_key| rebind_key(public_key, new_storer)),
+                })
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                Self::SodiumOxidePublicAsymmetricKey(SodiumOxidePublicAsymmetricKeyAlgorithm {
+                    public_key: rebind_key(sopaku.public_key, new_storer),
+                    nonce: sopaku.nonce,
+                    secret_key: rebind_key(sopaku.secret_key, new_storer),
+                })
+            }
+            Self::PassphraseSymmetric(psa) => Self::PassphraseSymmetric(psa),
+            Self::Aes256GcmSivSymmetricKey(agsku) => {
+                Self::Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKeyAlgorithm {
+                    key: rebind_key(agsku.key, new_storer),
+                    nonce: agsku.nonce,
+                    bind_path: agsku.bind_path,
+                })
+            }
+            Self::SizeLimited(sla) => Self::SizeLimited(SizeLimitedAlgorithm {
+                max_size: sla.max_size,
+                algorithm: Box::new(sla.algorithm.rebind_storer(new_storer)),
+            }),
+            Self::Compressed(ca) => Self::Compressed(CompressedAlgorithm {
+                compression: ca.compression,
+                max_decompressed_size: ca.max_decompressed_size,
+                algorithm: Box::new(ca.algorithm.rebind_storer(new_storer)),
+            }),
+            Self::Audited(aa) => Self::Audited(AuditedAlgorithm {
+                algorithm: Box::new(aa.algorithm.rebind_storer(new_storer)),
+                sink: aa.sink,
+            }),
+        }
+    }
+
+    /// Replaces every referenced key entry in this algorithm with the entry fetched from
+    /// `storer`, recursing into the algorithms sealing its keys, see `Entry::bundle`
+    #[async_recursion]
+    pub(crate) async fn bundle<S: Storer>(self, storer: &S) -> Result<ByteAlgorithm, CryptoError> {
+        Ok(match self {
+            Self::SodiumOxideSymmetricKey(sosku) => {
+                Self::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                    key: bundle_key(sosku.key, storer).await?,
+                    nonce: sosku.nonce,
+                })
+            }
+            Self::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                let public_key = match sosaku.public_key {
+                    Some(public_key) => Some(bundle_key(public_key, storer).await?),
+                    None => None,
+                };
+                Self::SodiumOxideSecretAsymmetricKey(SodiumOxideSecretAsymmetricKeyAlgorithm {
+                    secret_key: bundle_key(sosaku.secret_key, storer).await?,
+                    nonce: sosaku.nonce,
+                    public_key,
+                })
+            }
+            Self::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                Self::SodiumOxidePublicAsymmetricKey(SodiumOxidePublicAsymmetricKeyAlgorithm {
+                    public_key: bundle_key(sopaku.public_key, storer).await?,
+                    nonce: sopaku.nonce,
+                    secret_key: bundle_key(sopaku.secret_key, storer).await?,
+                })
+            }
+            Self::PassphraseSymmetric(psa) => Self::PassphraseSymmetric(psa),
+            Self::Aes256GcmSivSymmetricKey(agsku) => {
+                Self::Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKeyAlgorithm {
+                    key: bundle_key(agsku.key, storer).await?,
+                    nonce: agsku.nonce,
+                    bind_path: agsku.bind_path,
+                })
+            }
+            Self::SizeLimited(sla) => Self::SizeLimited(SizeLimitedAlgorithm {
+                max_size: sla.max_size,
+                algorithm: Box::new(sla.algorithm.bundle(storer).await?),
+            }),
+            Self::Compressed(ca) => Self::Compressed(CompressedAlgorithm {
+                compression: ca.compression,
+                max_decompressed_size: ca.max_decompressed_size,
+                algorithm: Box::new(ca.algorithm.bundle(storer).await?),
+            }),
+            Self::Audited(aa) => Self::Audited(AuditedAlgorithm {
+                algorithm: Box::new(aa.algorithm.bundle(storer).await?),
+                sink: aa.sink,
+            }),
+        })
+    }
+}
+
+fn rebind_key<K: StorableType>(key: Box<Entry<K>>, new_storer: &TypeStorer) -> Box<Entry<K>> {
+    Box::new(key.rebind_storer(new_storer.clone()))
+}
+
+async fn bundle_key<K: StorableType, S: Storer>(
+    key: Box<Entry<K>>,
+    storer: &S,
+) -> Result<Box<Entry<K>>, CryptoError> {
+    let key = key.bundle(storer).await?;
+    #[cfg(feature = "tracing")]
+    if let crate::State::Unsealed { .. } = key.value {
+        tracing::warn!(path = %key.path, "bundle embeds an unsealed key");
+    }
+    Ok(Box::new(key))
 }
 
 // impl ByteAlgorithm {
@@ -56,3 +813,185 @@ impl Algorithm for ByteAlgorithm {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::Algorithm;
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, ByteSource, CryptoError, ToEntry,
+        ToSymmetricByteAlgorithm,
+    };
+    use sodiumoxide::crypto::secretbox;
+
+    #[tokio::test]
+    async fn test_bytealgorithm_with_max_size() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap()
+            .with_max_size(64);
+        assert_eq!(algorithm.max_size(), Some(64));
+
+        let plaintext: ByteSource = b"hello, world!".as_ref().into();
+        let ciphertext = algorithm.seal(&plaintext).await.unwrap();
+        let unsealed = algorithm.unseal(&ciphertext).await.unwrap();
+        assert_eq!(unsealed.get().unwrap(), b"hello, world!");
+
+        // Garbage would fail verification, but is rejected before decryption is attempted.
+        // Ciphertexts may exceed the limit by the MAC.
+        let oversized: ByteSource = vec![0u8; 100].into();
+        match algorithm.unseal(&oversized).await {
+            Err(CryptoError::PayloadTooLarge { size: 100, max: 80 }) => (),
+            _ => panic!("Oversized ciphertext should have been rejected"),
+        }
+        match algorithm.seal(&oversized).await {
+            Err(CryptoError::PayloadTooLarge { size: 100, max: 64 }) => (),
+            _ => panic!("Oversized plaintext should have been rejected"),
+        }
+
+        let algorithm = algorithm.with_max_size(128);
+        assert_eq!(algorithm.max_size(), Some(128));
+        algorithm.seal(&oversized).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bytealgorithm_with_max_size_boundary() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap()
+            .with_max_size(64);
+
+        // A plaintext of exactly max_size seals, and its ciphertext unseals
+        let plaintext: ByteSource = vec![7u8; 64].into();
+        let ciphertext = algorithm.seal(&plaintext).await.unwrap();
+        assert_eq!(ciphertext.len().unwrap(), 64 + secretbox::MACBYTES);
+        let unsealed = algorithm.unseal(&ciphertext).await.unwrap();
+        assert_eq!(unsealed.get().unwrap(), vec![7u8; 64].as_slice());
+
+        let plaintext: ByteSource = vec![7u8; 65].into();
+        match algorithm.seal(&plaintext).await {
+            Err(CryptoError::PayloadTooLarge { size: 65, max: 64 }) => (),
+            _ => panic!("Plaintext over the limit should have been rejected"),
+        }
+        let ciphertext: ByteSource = vec![0u8; 65 + secretbox::MACBYTES].into();
+        match algorithm.unseal(&ciphertext).await {
+            Err(CryptoError::PayloadTooLarge { size: 81, max: 80 }) => (),
+            _ => panic!("Ciphertext over the limit should have been rejected"),
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_bytealgorithm_with_compression() {
+        use super::Compression;
+        use crate::{Data, HasByteSource, State};
+
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap()
+            .with_compression(Compression::Zstd);
+        assert_eq!(algorithm.compression(), Compression::Zstd);
+
+        let plaintext = "hello, world! ".repeat(100 * 1024 / 14);
+        let entry = Data::String(plaintext.clone())
+            .to_sealed_entry(".data.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        let ciphertext_len = match entry.value {
+            State::Sealed { ref ciphertext, .. } => ciphertext.len().unwrap(),
+            _ => panic!("Entry should have been sealed"),
+        };
+        let plaintext_len = Data::String(plaintext.clone()).byte_source().len().unwrap();
+        assert!(ciphertext_len * 10 < plaintext_len);
+        assert_eq!(entry.resolve().await.unwrap(), &Data::String(plaintext));
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_bytealgorithm_with_compression_and_max_size_ordering() {
+        use super::Compression;
+
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap();
+        let plaintext: ByteSource = vec![0u8; 1024].into();
+
+        // Limited then compressed: the limit applies to the compressed bytes
+        let compressed_limit = algorithm
+            .clone()
+            .with_max_size(64)
+            .with_compression(Compression::Zstd);
+        assert_eq!(compressed_limit.max_size(), Some(64));
+        assert_eq!(compressed_limit.compression(), Compression::Zstd);
+        let ciphertext = compressed_limit.seal(&plaintext).await.unwrap();
+        assert_eq!(
+            compressed_limit
+                .unseal(&ciphertext)
+                .await
+                .unwrap()
+                .len()
+                .unwrap(),
+            1024
+        );
+
+        // Compressed then limited: the limit applies to the source before compressing
+        let source_limit = algorithm
+            .with_compression(Compression::Zstd)
+            .with_max_size(64);
+        assert_eq!(source_limit.max_size(), Some(64));
+        assert_eq!(source_limit.compression(), Compression::Zstd);
+        match source_limit.seal(&plaintext).await {
+            Err(CryptoError::PayloadTooLarge {
+                size: 1024,
+                max: 64,
+            }) => (),
+            _ => panic!("Source larger than the limit should have been refused"),
+        }
+    }
+
+    #[cfg(feature = "zstd")]
+    #[tokio::test]
+    async fn test_bytealgorithm_with_compression_bounds_decompression() {
+        use super::{ByteAlgorithm, CompressedAlgorithm, Compression};
+
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap();
+        let algorithm = ByteAlgorithm::Compressed(CompressedAlgorithm {
+            compression: Compression::Zstd,
+            max_decompressed_size: 1024,
+            algorithm: Box::new(algorithm),
+        });
+
+        // A plaintext of exactly the limit round-trips
+        let plaintext: ByteSource = vec![0u8; 1024].into();
+        let ciphertext = algorithm.seal(&plaintext).await.unwrap();
+        assert_eq!(
+            algorithm.unseal(&ciphertext).await.unwrap().len().unwrap(),
+            1024
+        );
+
+        // A megabyte of zeroes compresses to a few bytes, but is refused on unsealing
+        let bomb: ByteSource = vec![0u8; 1024 * 1024].into();
+        let ciphertext = algorithm.seal(&bomb).await.unwrap();
+        assert!(ciphertext.len().unwrap() < 1024);
+        match algorithm.unseal(&ciphertext).await {
+            Err(CryptoError::DecompressedTooLarge { max: 1024 }) => (),
+            _ => panic!("Decompressing past the limit should have failed"),
+        }
+    }
+}
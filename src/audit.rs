@@ -0,0 +1,84 @@
+//! Optional audit trail of seal and unseal operations. An algorithm wrapped with
+//! `ByteAlgorithm::with_audit_sink` reports every successful seal or unseal to its sink.
+//! Events never contain plaintext, only the operation, an identifier of the key that is
+//! safe to record, the ciphertext length and the time of the operation.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Kind of operation recorded in an `AuditEvent`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum AuditOperation {
+    Seal,
+    Unseal,
+}
+
+/// A single seal or unseal performed by an audited `ByteAlgorithm`
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AuditEvent {
+    pub operation: AuditOperation,
+    /// Identifier of the key, as returned by `ByteAlgorithm::key_id`
+    pub key_id: String,
+    pub ciphertext_len: usize,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Destination for audit events. Sinks are called synchronously from within the
+/// operation being recorded, so they should hand events off rather than block.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, event: AuditEvent);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditEvent, AuditOperation, AuditSink};
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, Algorithm, ByteSource, ToEntry,
+        ToSymmetricByteAlgorithm,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Default)]
+    struct VecSink {
+        events: Mutex<Vec<AuditEvent>>,
+    }
+
+    impl AuditSink for VecSink {
+        fn record(&self, event: AuditEvent) {
+            self.events.lock().unwrap().push(event);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_sink_records_operations() {
+        let sink = Arc::new(VecSink::default());
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.audited.".to_owned())
+            })
+            .await
+            .unwrap();
+        let unaudited = algorithm.clone();
+        let algorithm = algorithm.with_audit_sink(sink.clone());
+        assert_eq!(algorithm.key_id().await.unwrap(), ".keys.audited.");
+
+        let plaintext: ByteSource = "hello, world!".into();
+        let first = algorithm.seal(&plaintext).await.unwrap();
+        let _second = algorithm.seal(&plaintext).await.unwrap();
+        let unsealed = algorithm.unseal(&first).await.unwrap();
+        unaudited.seal(&plaintext).await.unwrap();
+        assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+
+        let events = sink.events.lock().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0].operation, AuditOperation::Seal);
+        assert_eq!(events[1].operation, AuditOperation::Seal);
+        assert_eq!(events[2].operation, AuditOperation::Unseal);
+        for event in events.iter() {
+            assert_eq!(event.key_id, ".keys.audited.");
+            assert_eq!(event.ciphertext_len, first.get().unwrap().len());
+            assert!(!serde_json::to_string(event).unwrap().contains("hello"));
+        }
+        assert!(events[0].timestamp <= events[2].timestamp);
+    }
+}
@@ -0,0 +1,107 @@
+//! Source of the current time for everything in this crate that records or checks a
+//! time, such as byte source modification times, audit events and time locks. The
+//! system clock is used unless a clock is installed for the current thread with
+//! `set_thread_clock`, which lets tests of time-dependent behaviour run deterministically.
+
+use chrono::{DateTime, Duration, Utc};
+use std::{
+    cell::RefCell,
+    sync::{Arc, Mutex},
+};
+
+thread_local! {
+    static THREAD_CLOCK: RefCell<Option<Arc<dyn Clock>>> = RefCell::new(None);
+}
+
+/// Provides the current time
+pub trait Clock: Send + Sync {
+    fn now(&self) -> DateTime<Utc>;
+}
+
+/// Clock reading the system time
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// Clock that stands still at a given time until it is moved explicitly
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    pub fn new(now: DateTime<Utc>) -> Self {
+        MockClock {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// Moves the clock to the given time
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.now.lock().unwrap() = now;
+    }
+
+    /// Moves the clock forward by the given duration
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.now.lock().unwrap()
+    }
+}
+
+/// Installs the clock used by every operation on the current thread, replacing any
+/// previously installed clock. Tasks on a multi-threaded runtime can move between
+/// threads, so this is meant for single-threaded code such as tests.
+pub fn set_thread_clock(clock: Arc<dyn Clock>) {
+    THREAD_CLOCK.with(|c| *c.borrow_mut() = Some(clock));
+}
+
+/// Removes the clock installed for the current thread, going back to the system clock
+pub fn clear_thread_clock() {
+    THREAD_CLOCK.with(|c| *c.borrow_mut() = None);
+}
+
+/// Returns the current time according to the clock installed for the current thread,
+/// or the system time if there is none
+pub(crate) fn now() -> DateTime<Utc> {
+    THREAD_CLOCK
+        .with(|c| c.borrow().as_ref().map(|clock| clock.now()))
+        .unwrap_or_else(|| SystemClock.now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clear_thread_clock, now, set_thread_clock, MockClock};
+    use crate::VectorByteSource;
+    use chrono::{Duration, TimeZone, Utc};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_mock_clock_sets_last_modified() {
+        let fixed = Utc.with_ymd_and_hms(2021, 6, 1, 12, 0, 0).unwrap();
+        let clock = Arc::new(MockClock::new(fixed));
+        set_thread_clock(clock.clone());
+
+        let mut source = VectorByteSource::new_now(Some(b"hello"));
+        assert_eq!(source.get_last_modified().unwrap(), &fixed);
+        clock.advance(Duration::minutes(5));
+        source.set(b"world").unwrap();
+        assert_eq!(
+            source.get_last_modified().unwrap(),
+            &(fixed + Duration::minutes(5))
+        );
+        assert_eq!(now(), fixed + Duration::minutes(5));
+
+        clear_thread_clock();
+        assert!(now() > fixed + Duration::minutes(5));
+    }
+}
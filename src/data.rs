@@ -1,9 +1,13 @@
 use crate::{
-    Builder, ByteSource, CryptoError, HasBuilder, HasByteSource, HasIndex, StorableType,
-    TypeBuilder, TypeBuilderContainer,
+    key::sodiumoxide::{SodiumOxideSymmetricKey, SodiumOxideSymmetricKeyAlgorithm},
+    nonce::sodiumoxide::SodiumOxideSymmetricNonce,
+    Builder, ByteAlgorithm, ByteSource, CryptoError, Entry, EntryPath, HasBuilder, HasByteSource,
+    HasIndex, StorableType, SymmetricKey, ToEntry, TypeBuilder, TypeBuilderContainer,
 };
 use mongodb::bson::{self, Document};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
 use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::secretbox;
 use std::{convert::TryFrom, fmt::Display, str::FromStr};
 use strum::EnumIter;
 
@@ -67,6 +71,13 @@ pub struct BinaryData {
     pub binary_type: BinaryType,
 }
 
+impl BinaryData {
+    /// Decodes the base64-encoded binary payload into its raw bytes
+    pub fn decoded_bytes(&self) -> Result<Vec<u8>, CryptoError> {
+        base64::decode(&self.binary).map_err(|_| CryptoError::NotDeserializableToBaseDataType)
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum Data {
     Bool(bool),
@@ -75,10 +86,98 @@ pub enum Data {
     F64(f64),
     String(String),
     Binary(Option<BinaryData>),
+    Decimal(Decimal),
 }
 
 impl StorableType for Data {}
 
+impl Data {
+    /// Envelope-encrypts the data under a freshly generated symmetric data key. The
+    /// data key is sealed under `kek` and returned as its own entry at `key_path`, and
+    /// the returned data entry at `data_path` embeds that same wrapped key, so it can be
+    /// resolved by anyone able to unseal with `kek`.
+    pub async fn seal_with_fresh_key(
+        &self,
+        kek: &ByteAlgorithm,
+        data_path: EntryPath,
+        key_path: EntryPath,
+    ) -> Result<(Entry<Data>, Entry<SymmetricKey>), CryptoError> {
+        // Every data key is wrapped under a fresh nonce, since the same kek wraps many
+        let mut kek = kek.clone();
+        kek.regenerate_nonce();
+        let wrapped_key = SodiumOxideSymmetricKey::new()
+            .to_sealed_entry(key_path, kek)
+            .await?;
+        let algorithm = ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+            key: Box::new(wrapped_key.clone()),
+            nonce: SodiumOxideSymmetricNonce {
+                nonce: secretbox::gen_nonce(),
+            },
+        });
+        let data_entry = self.clone().to_sealed_entry(data_path, algorithm).await?;
+        Ok((data_entry, wrapped_key.cast()?))
+    }
+
+    /// Coerces the data to a u64. Strings are parsed, and signed integers and integral
+    /// decimals are converted if they are in range; anything else fails with
+    /// `CryptoError::NotDowncastable`.
+    pub fn as_u64(&self) -> Result<u64, CryptoError> {
+        match self {
+            Data::U64(n) => Some(*n),
+            Data::I64(n) => u64::try_from(*n).ok(),
+            Data::String(s) => u64::from_str(s.trim()).ok(),
+            Data::Decimal(d) if d.fract().is_zero() => d.to_u64(),
+            _ => None,
+        }
+        .ok_or(CryptoError::NotDowncastable)
+    }
+
+    /// Coerces the data to an i64 the same way as `as_u64`
+    pub fn as_i64(&self) -> Result<i64, CryptoError> {
+        match self {
+            Data::I64(n) => Some(*n),
+            Data::U64(n) => i64::try_from(*n).ok(),
+            Data::String(s) => i64::from_str(s.trim()).ok(),
+            Data::Decimal(d) if d.fract().is_zero() => d.to_i64(),
+            _ => None,
+        }
+        .ok_or(CryptoError::NotDowncastable)
+    }
+
+    /// Coerces the data to an f64. Integers and decimals are converted, possibly losing
+    /// precision, and strings are parsed if they hold a finite number.
+    pub fn as_f64(&self) -> Result<f64, CryptoError> {
+        match self {
+            Data::F64(n) => Some(*n),
+            Data::U64(n) => Some(*n as f64),
+            Data::I64(n) => Some(*n as f64),
+            Data::String(s) => f64::from_str(s.trim()).ok().filter(|n| n.is_finite()),
+            Data::Decimal(d) => d.to_f64(),
+            _ => None,
+        }
+        .ok_or(CryptoError::NotDowncastable)
+    }
+
+    /// Coerces the data to a bool. Only the strings `true` and `false` are converted.
+    pub fn as_bool(&self) -> Result<bool, CryptoError> {
+        match self {
+            Data::Bool(b) => Some(*b),
+            Data::String(s) => bool::from_str(s.trim()).ok(),
+            _ => None,
+        }
+        .ok_or(CryptoError::NotDowncastable)
+    }
+
+    /// Coerces the data to a string. Numbers and bools are formatted; binary data is not
+    /// converted.
+    pub fn as_string(&self) -> Result<String, CryptoError> {
+        match self {
+            Data::Binary(_) => Err(CryptoError::NotDowncastable),
+            d => Ok(d.to_string()),
+        }
+    }
+}
+
 impl Display for Data {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -89,6 +188,7 @@ impl Display for Data {
                 Data::U64(n) => n.to_string(),
                 Data::I64(n) => n.to_string(),
                 Data::F64(n) => n.to_string(),
+                Data::Decimal(d) => d.to_string(),
                 Data::String(s) => s.to_owned(),
                 Data::Binary(b) => {
                     if let Some(b) = b {
@@ -104,7 +204,11 @@ impl Display for Data {
 
 impl From<Data> for ByteSource {
     fn from(d: Data) -> ByteSource {
-        d.to_string().as_str().into()
+        match d {
+            // Strings already hold their UTF-8 bytes, so hand the buffer over as is
+            Data::String(s) => s.into_bytes().into(),
+            d => d.to_string().as_str().into(),
+        }
     }
 }
 
@@ -133,13 +237,17 @@ impl HasBuilder for Data {
             Self::F64(_) => DataBuilder::F64(F64DataBuilder {}),
             Self::String(_) => DataBuilder::String(StringDataBuilder {}),
             Self::Binary(_) => DataBuilder::Binary(BinaryDataBuilder {}),
+            Self::Decimal(_) => DataBuilder::Decimal(DecimalDataBuilder {}),
         }
     }
 }
 
 impl HasByteSource for Data {
     fn byte_source(&self) -> ByteSource {
-        self.clone().into()
+        match self {
+            Data::String(s) => s.as_str().into(),
+            _ => self.clone().into(),
+        }
     }
 }
 
@@ -152,6 +260,7 @@ pub enum DataBuilder {
     F64(F64DataBuilder),
     String(StringDataBuilder),
     Binary(BinaryDataBuilder),
+    Decimal(DecimalDataBuilder),
 }
 
 impl TryFrom<TypeBuilderContainer> for DataBuilder {
@@ -182,11 +291,12 @@ impl Builder for DataBuilder {
             Self::F64(ndb) => ndb.build(bytes),
             Self::String(sdb) => sdb.build(bytes),
             Self::Binary(bdb) => bdb.build(bytes),
+            Self::Decimal(ddb) => ddb.build(bytes),
         }
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct BoolDataBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for BoolDataBuilder {
@@ -223,7 +333,20 @@ impl Builder for BoolDataBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+/// Parses an integer, reporting a string of digits that does not fit in the target type,
+/// including a negative one for an unsigned type, as `CryptoError::NumericOverflow`
+fn parse_integer<T: FromStr>(s: &str, target_type: &'static str) -> Result<T, CryptoError> {
+    T::from_str(s).map_err(|_| {
+        let digits = s.strip_prefix(&['+', '-'][..]).unwrap_or(s);
+        if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+            CryptoError::NumericOverflow { target_type }
+        } else {
+            CryptoError::NotDeserializableToBaseDataType
+        }
+    })
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct U64DataBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for U64DataBuilder {
@@ -251,8 +374,7 @@ impl Builder for U64DataBuilder {
             Some(bytes) => {
                 let s = String::from_utf8(bytes.to_vec())
                     .map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
-                let n =
-                    u64::from_str(&s).map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
+                let n = parse_integer::<u64>(&s, "u64")?;
                 Ok(Data::U64(n))
             }
             None => Ok(Data::U64(0)),
@@ -260,7 +382,7 @@ impl Builder for U64DataBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct I64DataBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for I64DataBuilder {
@@ -288,8 +410,7 @@ impl Builder for I64DataBuilder {
             Some(bytes) => {
                 let s = String::from_utf8(bytes.to_vec())
                     .map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
-                let n =
-                    i64::from_str(&s).map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
+                let n = parse_integer::<i64>(&s, "i64")?;
                 Ok(Data::I64(n))
             }
             None => Ok(Data::I64(0)),
@@ -297,7 +418,7 @@ impl Builder for I64DataBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct F64DataBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for F64DataBuilder {
@@ -327,6 +448,9 @@ impl Builder for F64DataBuilder {
                     .map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
                 let n =
                     f64::from_str(&s).map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
+                if !n.is_finite() {
+                    return Err(CryptoError::NumericOverflow { target_type: "f64" });
+                }
                 Ok(Data::F64(n))
             }
             None => Ok(Data::F64(0.0)),
@@ -334,7 +458,46 @@ impl Builder for F64DataBuilder {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct DecimalDataBuilder {}
+
+impl TryFrom<TypeBuilderContainer> for DecimalDataBuilder {
+    type Error = CryptoError;
+
+    fn try_from(builder: TypeBuilderContainer) -> Result<Self, Self::Error> {
+        match builder.0 {
+            TypeBuilder::Data(DataBuilder::Decimal(ddb)) => Ok(ddb),
+            _ => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
+impl From<DecimalDataBuilder> for TypeBuilder {
+    fn from(ddb: DecimalDataBuilder) -> TypeBuilder {
+        TypeBuilder::Data(DataBuilder::Decimal(ddb))
+    }
+}
+
+impl Builder for DecimalDataBuilder {
+    type Output = Data;
+
+    /// Parses the bytes as a decimal string. Values with more fractional digits than a
+    /// decimal can represent are rejected instead of being rounded.
+    fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
+        match bytes {
+            Some(bytes) => {
+                let s = String::from_utf8(bytes.to_vec())
+                    .map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
+                let d = Decimal::from_str_exact(&s)
+                    .map_err(|_| CryptoError::NotDeserializableToBaseDataType)?;
+                Ok(Data::Decimal(d))
+            }
+            None => Ok(Data::Decimal(Decimal::ZERO)),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct StringDataBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for StringDataBuilder {
@@ -354,6 +517,18 @@ impl From<StringDataBuilder> for TypeBuilder {
     }
 }
 
+impl StringDataBuilder {
+    /// Builds a string without rejecting invalid UTF-8, replacing any invalid sequences
+    /// with U+FFFD. `build` remains strict and should be preferred unless the bytes are
+    /// known to come from a source that may not be valid UTF-8.
+    pub fn build_lossy(&self, bytes: Option<&[u8]>) -> Data {
+        match bytes {
+            Some(bytes) => Data::String(String::from_utf8_lossy(bytes).into_owned()),
+            None => Data::String("".to_owned()),
+        }
+    }
+}
+
 impl Builder for StringDataBuilder {
     type Output = Data;
 
@@ -372,6 +547,17 @@ impl Builder for StringDataBuilder {
 #[derive(Serialize, Deserialize, Debug, Clone, Copy)]
 pub struct BinaryDataBuilder {}
 
+impl BinaryDataBuilder {
+    /// Builds the binary data like `build`, but also rejects a payload that is not valid base64
+    pub fn build_validated(&self, data: Option<&[u8]>) -> Result<Data, CryptoError> {
+        let d = self.build(data)?;
+        if let Data::Binary(Some(ref bd)) = d {
+            bd.decoded_bytes()?;
+        }
+        Ok(d)
+    }
+}
+
 impl TryFrom<TypeBuilderContainer> for BinaryDataBuilder {
     type Error = CryptoError;
 
@@ -409,16 +595,21 @@ impl Builder for BinaryDataBuilder {
 #[cfg(test)]
 mod tests {
     use super::{
-        BinaryDataBuilder, BoolDataBuilder, Data, DataBuilder, F64DataBuilder, I64DataBuilder,
-        StringDataBuilder, U64DataBuilder,
+        BinaryDataBuilder, BoolDataBuilder, Data, DataBuilder, DecimalDataBuilder, F64DataBuilder,
+        I64DataBuilder, StringDataBuilder, U64DataBuilder,
     };
     use crate::{
-        key::sodiumoxide::SodiumOxideSymmetricKeyBuilder, BinaryData, BinaryType, Builder,
-        ByteSource, HasBuilder, HasIndex, KeyBuilder, SymmetricKeyBuilder, TypeBuilder,
-        TypeBuilderContainer,
+        key::sodiumoxide::{SodiumOxideSymmetricKey, SodiumOxideSymmetricKeyBuilder},
+        BinaryData, BinaryType, Builder, ByteAlgorithm, ByteSource, CryptoError, HasBuilder,
+        HasIndex, KeyBuilder, MemoryStorer, State, Storer, SymmetricKey, SymmetricKeyBuilder,
+        ToEntry, ToSymmetricByteAlgorithm, TypeBuilder, TypeBuilderContainer,
     };
     use mongodb::bson::{self, Document};
-    use std::convert::{Into, TryInto};
+    use std::{
+        alloc::{GlobalAlloc, Layout, System},
+        cell::Cell,
+        convert::{Into, TryInto},
+    };
 
     #[test]
     fn test_display_bool_data() {
@@ -832,6 +1023,29 @@ mod tests {
         udb.build(Some(b"-10")).unwrap();
     }
 
+    #[test]
+    fn test_u64databuilder_build_range_errors() {
+        let udb = U64DataBuilder {};
+        match udb.build(Some(b"99999999999999999999")) {
+            Err(e @ CryptoError::NumericOverflow { target_type: "u64" }) => {
+                // The value may be plaintext and must not end up in logs
+                assert!(!e.to_string().contains("99999999999999999999"));
+                assert!(!format!("{:?}", e).contains("99999999999999999999"));
+            }
+            _ => panic!("Overflowing value should have been reported as out of range"),
+        }
+        match udb.build(Some(b"-10")) {
+            Err(CryptoError::NumericOverflow { target_type, .. }) => {
+                assert_eq!(target_type, "u64")
+            }
+            _ => panic!("Negative value should have been reported as out of range"),
+        }
+        match udb.build(Some(b"10a")) {
+            Err(CryptoError::NotDeserializableToBaseDataType) => (),
+            _ => panic!("Malformed value should have been reported as not deserializable"),
+        }
+    }
+
     #[test]
     fn test_u64databuilder_from_typebuildercontainer_valid() {
         let tbc = TypeBuilderContainer(TypeBuilder::Data(DataBuilder::U64(U64DataBuilder {})));
@@ -896,6 +1110,17 @@ mod tests {
         udb.build(Some(b"somestr")).unwrap();
     }
 
+    #[test]
+    fn test_f64databuilder_build_non_finite() {
+        let udb = F64DataBuilder {};
+        match udb.build(Some(b"1e400")) {
+            Err(CryptoError::NumericOverflow { target_type, .. }) => {
+                assert_eq!(target_type, "f64")
+            }
+            _ => panic!("Non-finite value should have been reported as out of range"),
+        }
+    }
+
     #[test]
     fn test_f64databuilder_from_typebuildercontainer_valid() {
         let tbc = TypeBuilderContainer(TypeBuilder::Data(DataBuilder::F64(F64DataBuilder {})));
@@ -911,6 +1136,52 @@ mod tests {
         let _: F64DataBuilder = tbc.try_into().unwrap();
     }
 
+    #[test]
+    fn test_decimaldatabuilder_build_exact_sum() {
+        let ddb = DecimalDataBuilder {};
+        let sum = match (
+            ddb.build(Some(b"0.1")).unwrap(),
+            ddb.build(Some(b"0.2")).unwrap(),
+        ) {
+            (Data::Decimal(a), Data::Decimal(b)) => Data::Decimal(a + b),
+            _ => panic!("Extracted data should have been decimal-type"),
+        };
+        assert_eq!(sum.to_string(), "0.3");
+        match ddb.build(Some(sum.to_string().as_bytes())).unwrap() {
+            Data::Decimal(d) => assert_eq!(d.to_string(), "0.3"),
+            _ => panic!("Extracted data should have been decimal-type"),
+        }
+    }
+
+    #[test]
+    fn test_decimaldatabuilder_build_high_precision() {
+        let ddb = DecimalDataBuilder {};
+        let value = "12345678.12345678901234567890";
+        let d = ddb.build(Some(value.as_bytes())).unwrap();
+        assert_eq!(d.to_string(), value);
+        let bs: ByteSource = d.into();
+        assert_eq!(bs.get().unwrap(), value.as_bytes());
+    }
+
+    #[test]
+    fn test_decimaldatabuilder_build_invalid() {
+        let ddb = DecimalDataBuilder {};
+        assert!(ddb.build(Some(b"1.2.3")).is_err());
+        assert!(ddb.build(Some(b"abc")).is_err());
+        // 29 fractional digits exceed the maximum scale and must not be rounded
+        assert!(ddb.build(Some(b"0.12345678901234567890123456789")).is_err());
+    }
+
+    #[test]
+    fn test_decimaldata_to_builder() {
+        let d = DecimalDataBuilder {}.build(Some(b"1.50")).unwrap();
+        match d.builder() {
+            DataBuilder::Decimal(_) => (),
+            _ => panic!("Builder should have been a decimal builder"),
+        }
+        assert_eq!(d.to_string(), "1.50");
+    }
+
     #[test]
     fn test_stringdatabuilder_build_valid() {
         let sdb = StringDataBuilder {};
@@ -928,6 +1199,31 @@ mod tests {
         udb.build(Some(vec![0xc3, 0x28].as_ref())).unwrap();
     }
 
+    #[test]
+    fn test_stringdatabuilder_build_invalid_strict_error() {
+        let sdb = StringDataBuilder {};
+        match sdb.build(Some(&[0xc3, 0x28][..])) {
+            Err(CryptoError::NotDeserializableToBaseDataType) => (),
+            _ => panic!("Strict build of invalid UTF-8 should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_stringdatabuilder_build_lossy() {
+        let sdb = StringDataBuilder {};
+        match sdb.build_lossy(Some(&[0xc3, 0x28][..])) {
+            Data::String(s) => {
+                assert!(s.contains('\u{FFFD}'));
+                assert_eq!(s, "\u{FFFD}(");
+            }
+            _ => panic!("Extracted data should have been a string-type"),
+        }
+        match sdb.build_lossy(Some(b"hello, world!")) {
+            Data::String(s) => assert_eq!(s, "hello, world!"),
+            _ => panic!("Extracted data should have been a string-type"),
+        }
+    }
+
     #[test]
     fn test_stringdatabuilder_from_typebuildercontainer_valid() {
         let tbc =
@@ -1118,6 +1414,27 @@ mod tests {
         udb.build(Some(b"-10")).unwrap();
     }
 
+    #[test]
+    fn test_binarydatabuilder_build_validated_base64() {
+        let bdb = BinaryDataBuilder {};
+        let d = bdb
+            .build_validated(Some(
+                b"{\"binary\":\"aGVsbG8sIHdvcmxkIQ==\",\"binary_type\":\"ImagePNG\"}",
+            ))
+            .unwrap();
+        match d {
+            Data::Binary(Some(bd)) => assert_eq!(bd.decoded_bytes().unwrap(), b"hello, world!"),
+            _ => panic!("Extracted data should have been a binary-type"),
+        }
+
+        let invalid = b"{\"binary\":\"not base64!\",\"binary_type\":\"ImagePNG\"}";
+        match bdb.build_validated(Some(invalid)) {
+            Err(CryptoError::NotDeserializableToBaseDataType) => (),
+            _ => panic!("Invalid base64 payload should have been rejected"),
+        }
+        assert!(bdb.build(Some(invalid)).is_ok());
+    }
+
     #[test]
     #[should_panic]
     fn test_binarydatabuilder_build_invalid_binary_type() {
@@ -1141,4 +1458,99 @@ mod tests {
         )));
         let _: BinaryDataBuilder = tbc.try_into().unwrap();
     }
+
+    /// Counts the allocations made on each thread, so that tests running in parallel do
+    /// not disturb each other's counts
+    struct CountingAllocator;
+
+    thread_local! {
+        static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    }
+
+    unsafe impl GlobalAlloc for CountingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            ALLOCATIONS.with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    fn allocations_during<R>(f: impl FnOnce() -> R) -> (R, usize) {
+        let before = ALLOCATIONS.with(|count| count.get());
+        let result = f();
+        (result, ALLOCATIONS.with(|count| count.get()) - before)
+    }
+
+    #[test]
+    fn test_string_data_byte_source_skips_display() {
+        let data = Data::String("hello, world!".to_owned());
+
+        // Only the byte buffer itself is allocated, with no intermediate strings
+        let (byte_source, allocations) = allocations_during(|| data.byte_source());
+        assert_eq!(byte_source.get().unwrap(), b"hello, world!");
+        assert_eq!(allocations, 1);
+
+        // Converting an owned string reuses its buffer
+        let (byte_source, allocations) = allocations_during(|| ByteSource::from(data));
+        assert_eq!(byte_source.get().unwrap(), b"hello, world!");
+        assert_eq!(allocations, 0);
+    }
+
+    #[tokio::test]
+    async fn test_seal_with_fresh_key() {
+        let kek = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.kek.".to_owned())
+            })
+            .await
+            .unwrap();
+        let data = Data::String("hello, world!".to_owned());
+        let (data_entry, key_entry) = data
+            .seal_with_fresh_key(&kek, ".data.greeting.".to_owned(), ".keys.dek.".to_owned())
+            .await
+            .unwrap();
+        assert!(matches!(data_entry.value, State::Sealed { .. }));
+        assert!(matches!(key_entry.value, State::Sealed { .. }));
+
+        let storer = MemoryStorer::new();
+        storer.create(key_entry).await.unwrap();
+        storer.create(data_entry).await.unwrap();
+
+        let data_entry = storer.get::<Data>(".data.greeting.").await.unwrap();
+        assert_eq!(data_entry.resolve().await.unwrap(), &data);
+        let key_entry = storer.get::<SymmetricKey>(".keys.dek.").await.unwrap();
+        assert!(key_entry.resolve().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_seal_with_fresh_key_wraps_under_fresh_nonces() {
+        let kek = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.kek.".to_owned())
+            })
+            .await
+            .unwrap();
+        let data = Data::String("hello, world!".to_owned());
+        let mut nonces = vec![];
+        for _ in 0..2 {
+            let (_, key_entry) = data
+                .seal_with_fresh_key(&kek, ".data.greeting.".to_owned(), ".keys.dek.".to_owned())
+                .await
+                .unwrap();
+            match key_entry.value {
+                State::Sealed {
+                    algorithm: ByteAlgorithm::SodiumOxideSymmetricKey(sosku),
+                    ..
+                } => nonces.push(sosku.nonce.nonce),
+                _ => panic!("Data key should have been sealed under the kek"),
+            }
+        }
+        assert_ne!(nonces[0], nonces[1]);
+    }
 }
@@ -1,15 +1,25 @@
 use crate::{
-    Algorithm, ByteAlgorithm, ByteSource, CryptoError, Data, DataBuilder, HasByteSource, HasIndex,
-    Key, KeyBuilder, Storer, ToPublicAsymmetricByteAlgorithm, ToSecretAsymmetricByteAlgorithm,
-    ToSymmetricByteAlgorithm, TypeStorer,
+    clock,
+    source::{NotFoundKind, SourceError},
+    trace::traced,
+    Algorithm, AsymmetricKeyBuilder, ByteAlgorithm, ByteSource, CryptoError, Data, DataBuilder,
+    HasByteSource, HasIndex, Key, KeyBuilder, Signer, Storer, ToPublicAsymmetricByteAlgorithm,
+    ToSecretAsymmetricByteAlgorithm, ToSymmetricByteAlgorithm, TypeStorer, Verifier,
 };
 use async_recursion::async_recursion;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
+use futures::{
+    future::{self, Either},
+    pin_mut,
+    stream::{BoxStream, Stream, StreamExt},
+    Future,
+};
 use mongodb::bson::Document;
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
-use std::convert::TryFrom;
+use std::{collections::BTreeMap, convert::TryFrom, time::Duration};
+use tokio_util::sync::CancellationToken;
 
 pub type EntryPath = String;
 
@@ -19,10 +29,62 @@ pub struct Entry<T> {
     pub path: EntryPath,
     pub builder: TypeBuilder,
     pub value: State,
+    /// Free-form labels attached to the entry, such as its owner or rotation schedule
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
     #[serde(skip)]
     resolved_value: OnceCell<T>,
 }
 
+/// Clones the entry's description without its cached resolved value, since `T` is not
+/// required to be `Clone`, so the clone resolves independently of the original. Prefer
+/// the consuming transformations such as `Entry::rename`, which keep the cache.
+impl<T> Clone for Entry<T> {
+    fn clone(&self) -> Self {
+        Entry {
+            path: self.path.clone(),
+            builder: self.builder,
+            value: self.value.clone(),
+            metadata: self.metadata.clone(),
+            resolved_value: OnceCell::new(),
+        }
+    }
+}
+
+/// Wire formats an entry can be serialized to with `Entry::to_bytes`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum SerializationFormat {
+    /// JSON, with byte sources base64-encoded
+    Json,
+    /// MessagePack, with byte sources stored as raw binary
+    MessagePack,
+}
+
+/// Kind of state an entry was in, without any of the state's contents
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum StateKind {
+    Referenced,
+    Sealed,
+    Unsealed,
+}
+
+/// A single entry passed through while resolving, see `Entry::resolve_with_provenance`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProvenanceHop {
+    pub path: EntryPath,
+    pub state: StateKind,
+    /// Identifier of the key the entry was unsealed with, as returned by
+    /// `ByteAlgorithm::key_id`, if the entry was sealed. It never contains a digest of
+    /// secret key material, so provenance can be recorded alongside the entries.
+    pub key_id: Option<String>,
+}
+
+/// Every entry passed through while resolving an entry, in the order they were visited
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Provenance {
+    pub hops: Vec<ProvenanceHop>,
+}
+
 pub trait StorableType:
     HasByteSource + HasBuilder + HasIndex<Index = Document> + Unpin + Send + std::fmt::Debug + 'static
 {
@@ -87,11 +149,89 @@ impl<T: ToPublicAsymmetricByteAlgorithm + StorableType> Entry<T> {
     }
 }
 
+impl Entry<Data> {
+    /// Resolves the entry, applies the given transformation to its data, and returns the
+    /// result as a new unsealed entry at the same path. The builder is re-derived from the
+    /// transformed data since the transformation may change its variant.
+    pub async fn map_value<F>(self, f: F) -> Result<Entry<Data>, CryptoError>
+    where
+        F: FnOnce(Data) -> Result<Data, CryptoError>,
+    {
+        let path = self.path.clone();
+        let metadata = self.metadata.clone();
+        let data = self.take_resolve().await?;
+        Ok(f(data)?.to_unsealed_entry(path)?.with_metadata(metadata))
+    }
+
+    /// Resolves the entry and coerces its data to a u64, see `Data::as_u64`
+    pub async fn resolve_as_u64(&self) -> Result<u64, CryptoError> {
+        self.resolve().await?.as_u64()
+    }
+
+    /// Resolves the entry and coerces its data to an i64, see `Data::as_i64`
+    pub async fn resolve_as_i64(&self) -> Result<i64, CryptoError> {
+        self.resolve().await?.as_i64()
+    }
+
+    /// Resolves the entry and coerces its data to an f64, see `Data::as_f64`
+    pub async fn resolve_as_f64(&self) -> Result<f64, CryptoError> {
+        self.resolve().await?.as_f64()
+    }
+
+    /// Resolves the entry and coerces its data to a bool, see `Data::as_bool`
+    pub async fn resolve_as_bool(&self) -> Result<bool, CryptoError> {
+        self.resolve().await?.as_bool()
+    }
+
+    /// Resolves the entry and coerces its data to a string, see `Data::as_string`
+    pub async fn resolve_as_string(&self) -> Result<String, CryptoError> {
+        self.resolve().await?.as_string()
+    }
+}
+
 impl<T: StorableType> Entry<T> {
     pub fn cast<U: StorableType>(self) -> Result<Entry<U>, CryptoError> {
         let builder =
             <U as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?.into();
-        Ok(Entry::new(self.path, builder, self.value))
+        Ok(Entry::new(self.path, builder, self.value).with_metadata(self.metadata))
+    }
+
+    /// Builds an entry referencing the entry of type `T` at `path` in the given storer.
+    /// Unlike `ToEntry::to_ref_entry`, no value of type `T` is needed since the builder
+    /// is derived from the type itself.
+    pub fn reference<S: Into<TypeStorer>>(path: EntryPath, storer: S) -> Self
+    where
+        <T as HasBuilder>::Builder: Default,
+    {
+        Entry::new(
+            path.clone(),
+            <T as HasBuilder>::Builder::default().into(),
+            State::Referenced {
+                path,
+                storer: storer.into(),
+            },
+        )
+    }
+
+    /// Builds a sealed entry around ciphertext that was already sealed under the given
+    /// algorithm, such as ciphertext produced by another holder of the same key. Nothing
+    /// is sealed or verified until the entry is resolved. For an algorithm binding the
+    /// entry path, the ciphertext must have been sealed for this same path.
+    pub fn from_sealed_bytes(
+        path: EntryPath,
+        builder: TypeBuilder,
+        ciphertext: ByteSource,
+        algorithm: ByteAlgorithm,
+    ) -> Self {
+        Entry::new(
+            path,
+            builder,
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                unlock_at: None,
+            },
+        )
     }
 
     pub fn new(path: EntryPath, builder: TypeBuilder, value: State) -> Self {
@@ -99,12 +239,309 @@ impl<T: StorableType> Entry<T> {
             path,
             builder,
             value,
+            metadata: BTreeMap::new(),
             resolved_value: OnceCell::new(),
         }
     }
 
-    #[async_recursion]
+    /// Replaces the storer of every referenced entry embedded in this one, including the
+    /// key entries of sealing algorithms at any depth. Useful before exporting an entry
+    /// whose references point at a storer the recipient cannot reach.
+    pub fn rebind_storer(mut self, new_storer: TypeStorer) -> Entry<T> {
+        self.value = self.value.rebind_storer(&new_storer);
+        self
+    }
+
+    /// Moves the entry to the given path. The entry's value is left as is, so a value that
+    /// was already resolved stays cached and is not resolved again.
+    pub fn rename(mut self, path: EntryPath) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Returns the entry with its metadata replaced by the given map. A value that was
+    /// already resolved stays cached.
+    pub fn with_metadata(mut self, metadata: BTreeMap<String, String>) -> Self {
+        self.metadata = metadata;
+        self
+    }
+
+    /// Sets a metadata value on the entry, returning the previous value for the key. A
+    /// value that was already resolved stays cached.
+    pub fn set_metadata<K: Into<String>, V: Into<String>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Option<String> {
+        self.metadata.insert(key.into(), value.into())
+    }
+
+    /// Returns the metadata value stored under the given key
+    pub fn get_metadata(&self, key: &str) -> Option<&str> {
+        self.metadata.get(key).map(|value| value.as_str())
+    }
+
+    /// Returns the fingerprint of the entry's bytes if it is an unsealed public key.
+    /// Sealed and referenced entries have no fingerprint since their bytes are not
+    /// available without resolution. Nor do secret keys and data: storers index the
+    /// fingerprint in the clear, and an unkeyed hash of a secret lets anyone reading
+    /// the store check guesses of it.
+    pub fn fingerprint(&self) -> Option<String> {
+        public_key_fingerprint(&self.builder, &self.value)
+    }
+
+    /// Returns the OID of the entry's key algorithm, derived from its builder so that it
+    /// is known whatever state the entry is in. Entries that are not asymmetric keys
+    /// have none.
+    pub fn algorithm_oid(&self) -> Option<String> {
+        match self.builder {
+            TypeBuilder::Key(ref kb) => kb.algorithm_oid().map(|oid| oid.to_string()),
+            TypeBuilder::Data(_) => None,
+        }
+    }
+
+    /// Returns when the entry's bytes were last modified, see `State::last_modified`
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        self.value.last_modified()
+    }
+
+    /// Returns the length in bytes of the entry's JSON serialization. The serialization
+    /// is streamed through a counting writer, so the JSON is never held in memory.
+    pub fn serialized_size(&self) -> Result<usize, CryptoError> {
+        let mut writer = CountingWriter::default();
+        serde_json::to_writer(&mut writer, self).map_err(|e| CryptoError::InternalError {
+            source: Box::new(e),
+        })?;
+        Ok(writer.count)
+    }
+
+    /// Serializes the entry in the given format
+    pub fn to_bytes(&self, format: SerializationFormat) -> Result<Vec<u8>, CryptoError> {
+        match format {
+            SerializationFormat::Json => {
+                serde_json::to_vec(self).map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                })
+            }
+            SerializationFormat::MessagePack => {
+                rmp_serde::to_vec_named(self).map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                })
+            }
+        }
+    }
+
+    /// Signs the entry's MessagePack serialization, covering its path, builder, state and
+    /// metadata
+    pub fn sign<S: Signer + ?Sized>(&self, signer: &S) -> Result<ByteSource, CryptoError> {
+        let bytes = self.to_bytes(SerializationFormat::MessagePack)?;
+        signer.sign(bytes.as_slice().into())
+    }
+
+    /// Verifies a signature produced by `sign` against the entry as it is now
+    pub fn verify_signature<V: Verifier + ?Sized>(
+        &self,
+        verifier: &V,
+        signature: ByteSource,
+    ) -> Result<(), CryptoError> {
+        let bytes = self.to_bytes(SerializationFormat::MessagePack)?;
+        verifier.verify(bytes.as_slice().into(), signature)
+    }
+
+    /// Deserializes an entry previously serialized with `to_bytes` in the given format
+    pub fn from_bytes(bytes: &[u8], format: SerializationFormat) -> Result<Self, CryptoError> {
+        match format {
+            SerializationFormat::Json => {
+                serde_json::from_slice(bytes).map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                })
+            }
+            SerializationFormat::MessagePack => {
+                rmp_serde::from_read_ref(bytes).map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                })
+            }
+        }
+    }
+
+    /// Produces a patch containing only the fields of `other` that differ from this entry.
+    /// Fields are compared by their serialized form since states are not comparable directly.
+    pub fn diff(&self, other: &Entry<T>) -> Result<EntryPatch, CryptoError> {
+        let builder = if to_json(&self.builder)? != to_json(&other.builder)? {
+            Some(other.builder)
+        } else {
+            None
+        };
+        let other_value = to_json(&other.value)?;
+        let value = if to_json(&self.value)? != other_value {
+            Some(
+                serde_json::from_value(other_value).map_err(|e| CryptoError::InternalError {
+                    source: Box::new(e),
+                })?,
+            )
+        } else {
+            None
+        };
+        let metadata = if self.metadata != other.metadata {
+            Some(other.metadata.clone())
+        } else {
+            None
+        };
+        Ok(EntryPatch {
+            builder,
+            value,
+            metadata,
+        })
+    }
+
+    /// Verifies that the entry's value can be unsealed without building the final type.
+    /// Sealed values are run through their algorithm's unseal, which authenticates the
+    /// ciphertext; any failure is returned as an error. Referenced entries are
+    /// dereferenced first, and unsealed values always succeed.
+    pub async fn can_unseal(&self) -> Result<bool, CryptoError> {
+        match self.value {
+            State::Referenced {
+                ref path,
+                ref storer,
+            } => {
+                let entry = storer.get::<T>(path).await?.dereference().await?;
+                match entry.value {
+                    State::Sealed {
+                        ref ciphertext,
+                        ref algorithm,
+                        ..
+                    } => algorithm
+                        .unseal_for_path(ciphertext, &entry.path)
+                        .await
+                        .map(|_| true),
+                    _ => Ok(true),
+                }
+            }
+            State::Sealed {
+                ref ciphertext,
+                ref algorithm,
+                ..
+            } => algorithm
+                .unseal_for_path(ciphertext, &self.path)
+                .await
+                .map(|_| true),
+            State::Unsealed { .. } => Ok(true),
+        }
+    }
+
+    /// Verifies every link in the entry's chain without building any final types. References
+    /// are followed, the keys of each sealing algorithm are verified in turn, and each
+    /// ciphertext is then authenticated by unsealing it. Returns
+    /// `CryptoError::BrokenChainLink` naming the first entry that could not be fetched or
+    /// failed to authenticate, starting from the innermost key.
+    pub async fn verify_chain_integrity(&self) -> Result<(), CryptoError> {
+        match self.value {
+            State::Referenced {
+                ref path,
+                ref storer,
+            } => {
+                let mut entry = storer
+                    .get::<T>(path)
+                    .await
+                    .map_err(|e| broken_link(path, e))?;
+                loop {
+                    let next = match entry.value {
+                        State::Referenced {
+                            ref path,
+                            ref storer,
+                        } => storer
+                            .get::<T>(path)
+                            .await
+                            .map_err(|e| broken_link(path, e))?,
+                        _ => break,
+                    };
+                    entry = next;
+                }
+                entry.verify_sealed_integrity().await
+            }
+            _ => self.verify_sealed_integrity().await,
+        }
+    }
+
+    /// Verifies the keys of the entry's sealing algorithm and then its own ciphertext
+    async fn verify_sealed_integrity(&self) -> Result<(), CryptoError> {
+        match self.value {
+            State::Sealed {
+                ref ciphertext,
+                ref algorithm,
+                ..
+            } => {
+                algorithm.verify_chain_integrity().await?;
+                algorithm
+                    .unseal_for_path(ciphertext, &self.path)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| broken_link(&self.path, e))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Resolves the entry like `take_resolve`, additionally returning the path and state
+    /// of every entry followed to get to the value, along with the identifier of the key
+    /// that unsealed it. The chain is always traversed, even if the entry was already
+    /// resolved.
+    pub async fn resolve_with_provenance(self) -> Result<(T, Provenance), CryptoError> {
+        let mut provenance = Provenance::default();
+        let mut entry = self;
+        loop {
+            let next = match entry.value {
+                State::Referenced {
+                    ref path,
+                    ref storer,
+                } => {
+                    provenance.hops.push(ProvenanceHop {
+                        path: entry.path.clone(),
+                        state: entry.value.kind(),
+                        key_id: None,
+                    });
+                    storer.get::<T>(path).await?
+                }
+                State::Sealed {
+                    ref ciphertext,
+                    ref algorithm,
+                    ref unlock_at,
+                } => {
+                    check_time_lock(unlock_at)?;
+                    let builder =
+                        <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(entry.builder))?;
+                    provenance.hops.push(ProvenanceHop {
+                        path: entry.path.clone(),
+                        state: entry.value.kind(),
+                        key_id: Some(algorithm.key_id().await?),
+                    });
+                    let unsealed = algorithm.unseal_for_path(ciphertext, &entry.path).await;
+                    return Ok((build_unsealed(&builder, unsealed)?, provenance));
+                }
+                State::Unsealed { ref bytes } => {
+                    let builder =
+                        <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(entry.builder))?;
+                    provenance.hops.push(ProvenanceHop {
+                        path: entry.path.clone(),
+                        state: entry.value.kind(),
+                        key_id: None,
+                    });
+                    return Ok((
+                        builder.build(unsealed_bytes(&entry.builder, bytes)?)?,
+                        provenance,
+                    ));
+                }
+            };
+            entry = next;
+        }
+    }
+
     pub async fn dereference(self) -> Result<Entry<T>, CryptoError> {
+        traced!("dereference", &self.path, self.dereference_untraced()).await
+    }
+
+    #[async_recursion]
+    async fn dereference_untraced(self) -> Result<Entry<T>, CryptoError> {
         match self.value {
             State::Referenced {
                 ref path,
@@ -117,8 +554,54 @@ impl<T: StorableType> Entry<T> {
         }
     }
 
+    /// Returns the entry with every reference replaced by the entry fetched from `storer`,
+    /// including the key entries of sealing algorithms at any depth. Sealed and unsealed
+    /// values are carried over as they are, so the bundle can be resolved by a system with
+    /// no access to the storer.
+    ///
+    /// The key at the root of every sealing chain is embedded unsealed, so a bundle holds
+    /// everything needed to decrypt its value and must be protected like the plaintext
+    /// itself. When the `tracing` feature is enabled, a warning is logged for each
+    /// unsealed key embedded in the bundle.
+    #[async_recursion]
+    pub async fn bundle<S: Storer>(mut self, storer: &S) -> Result<Entry<T>, CryptoError> {
+        self.value = match self.value {
+            State::Referenced { ref path, .. } => {
+                storer.get::<T>(path).await?.bundle(storer).await?.value
+            }
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                unlock_at,
+            } => State::Sealed {
+                ciphertext,
+                algorithm: algorithm.bundle(storer).await?,
+                unlock_at,
+            },
+            State::Unsealed { bytes } => State::Unsealed { bytes },
+        };
+        Ok(self)
+    }
+
+    /// Resolves the entry through any references and seals and returns a self-contained
+    /// entry at the same path holding the plaintext inline, with no dependency on a storer
+    /// or an algorithm. Intended for exporting a snapshot of an entry.
+    pub async fn flatten(self) -> Result<Entry<T>, CryptoError> {
+        let path = self.path.clone();
+        let metadata = self.metadata.clone();
+        Ok(self
+            .take_resolve()
+            .await?
+            .to_unsealed_entry(path)?
+            .with_metadata(metadata))
+    }
+
+    pub async fn take_resolve(self) -> Result<T, CryptoError> {
+        traced!("take_resolve", &self.path, self.take_resolve_untraced()).await
+    }
+
     #[async_recursion]
-    pub async fn take_resolve(mut self) -> Result<T, CryptoError> {
+    async fn take_resolve_untraced(mut self) -> Result<T, CryptoError> {
         match self.resolved_value.take() {
             None => match self.value {
                 State::Referenced {
@@ -131,16 +614,18 @@ impl<T: StorableType> Entry<T> {
                 State::Sealed {
                     ref ciphertext,
                     ref algorithm,
+                    ref unlock_at,
                 } => {
+                    check_time_lock(unlock_at)?;
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
-                    let plaintext = algorithm.unseal(ciphertext).await?;
-                    builder.build(Some(plaintext.get()?))
+                    let unsealed = algorithm.unseal_for_path(ciphertext, &self.path).await;
+                    build_unsealed(&builder, unsealed)
                 }
                 State::Unsealed { bytes, .. } => {
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
-                    builder.build(Some(bytes.get()?))
+                    builder.build(unsealed_bytes(&self.builder, &bytes)?)
                 }
             },
             Some(value) => Ok(value),
@@ -161,20 +646,22 @@ impl<T: StorableType> Entry<T> {
                 State::Sealed {
                     ref ciphertext,
                     ref algorithm,
+                    ref unlock_at,
                 } => {
+                    check_time_lock(unlock_at)?;
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
-                    let plaintext = algorithm.unseal(ciphertext).await?;
-                    Ok((
-                        builder.build(Some(plaintext.get()?))?,
-                        self.path,
-                        self.value,
-                    ))
+                    let unsealed = algorithm.unseal_for_path(ciphertext, &self.path).await;
+                    Ok((build_unsealed(&builder, unsealed)?, self.path, self.value))
                 }
                 State::Unsealed { ref bytes, .. } => {
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
-                    Ok((builder.build(Some(bytes.get()?))?, self.path, self.value))
+                    Ok((
+                        builder.build(unsealed_bytes(&self.builder, bytes)?)?,
+                        self.path,
+                        self.value,
+                    ))
                 }
             },
             Some(value) => Ok((value, self.path, self.value)),
@@ -201,7 +688,49 @@ impl<T: StorableType> Entry<T> {
         }
     }
 
+    /// Resolves the entry like `resolve`, but gives up with `CryptoError::ResolutionTimeout`
+    /// if resolution, including any referenced entry lookups, takes longer than `duration`
+    pub async fn resolve_timeout(&self, duration: Duration) -> Result<&T, CryptoError> {
+        tokio::time::timeout(duration, self.resolve())
+            .await
+            .map_err(|_| CryptoError::ResolutionTimeout)?
+    }
+
+    /// Resolves the entry like `resolve`, but returns `CryptoError::Cancelled` as soon as
+    /// the token is cancelled. The token is checked before each referenced entry is
+    /// fetched, and a fetch that is in flight when it is cancelled is abandoned.
+    pub async fn resolve_cancellable(&self, token: &CancellationToken) -> Result<&T, CryptoError> {
+        if let Some(value) = self.resolved_value.get() {
+            return Ok(value);
+        }
+        let (path, storer) = match self.value {
+            State::Referenced {
+                ref path,
+                ref storer,
+            } => (path, storer),
+            _ => return cancellable(token, self.resolve()).await?,
+        };
+
+        let mut entry = cancellable(token, storer.get::<T>(path)).await??;
+        loop {
+            let next = match entry.value {
+                State::Referenced {
+                    ref path,
+                    ref storer,
+                } => cancellable(token, storer.get::<T>(path)).await??,
+                _ => break,
+            };
+            entry = next;
+        }
+        let value = cancellable(token, entry.take_resolve()).await??;
+        Ok(self.resolved_value.get_or_init(|| value))
+    }
+
     pub async fn resolve(&self) -> Result<&T, CryptoError> {
+        traced!("resolve", &self.path, self.resolve_untraced()).await
+    }
+
+    async fn resolve_untraced(&self) -> Result<&T, CryptoError> {
         match self.resolved_value.get() {
             None => match self.value {
                 State::Referenced {
@@ -215,26 +744,208 @@ impl<T: StorableType> Entry<T> {
                 State::Sealed {
                     ref ciphertext,
                     ref algorithm,
+                    ref unlock_at,
                 } => {
+                    check_time_lock(unlock_at)?;
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
-                    let plaintext = algorithm.unseal(ciphertext).await?;
+                    let unsealed = algorithm.unseal_for_path(ciphertext, &self.path).await;
                     self.resolved_value
-                        .get_or_try_init(|| builder.build(Some(plaintext.get()?)))
+                        .get_or_try_init(|| build_unsealed(&builder, unsealed))
                 }
                 State::Unsealed { ref bytes, .. } => {
                     let builder =
                         <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
                     self.resolved_value
-                        .get_or_try_init(|| builder.build(Some(bytes.get()?)))
+                        .get_or_try_init(|| builder.build(unsealed_bytes(&self.builder, bytes)?))
                 }
             },
             Some(value) => Ok(value),
         }
     }
+
+    /// Resolves the entry like `resolve`, deriving the key of an entry sealed under a
+    /// passphrase algorithm from the given passphrase. Entries referencing other entries
+    /// are resolved with `resolve`, as the passphrase only applies to this one.
+    pub async fn resolve_with_passphrase(&self, passphrase: &[u8]) -> Result<&T, CryptoError> {
+        if let Some(value) = self.resolved_value.get() {
+            return Ok(value);
+        }
+        match self.value {
+            State::Sealed {
+                ref ciphertext,
+                ref algorithm,
+                ref unlock_at,
+            } => {
+                check_time_lock(unlock_at)?;
+                let builder =
+                    <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(self.builder))?;
+                // Passphrase algorithms cannot bind paths, so a bound algorithm is unsealed
+                // without the passphrase
+                let unsealed = if algorithm.binds_path() {
+                    algorithm.unseal_for_path(ciphertext, &self.path).await
+                } else {
+                    algorithm
+                        .unseal_with_passphrase(ciphertext, passphrase)
+                        .await
+                };
+                self.resolved_value
+                    .get_or_try_init(|| build_unsealed(&builder, unsealed))
+            }
+            _ => self.resolve().await,
+        }
+    }
+
+    /// Whether a value has already been resolved and cached by this entry
+    pub fn is_resolved(&self) -> bool {
+        self.resolved_value.get().is_some()
+    }
+
+    /// Consumes the entry and returns its cached resolved value, if any, without
+    /// attempting to resolve it
+    pub fn into_inner(self) -> Option<T> {
+        self.resolved_value.into_inner()
+    }
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Returns the bytes of an unsealed state to build from. A vector source holding no bytes
+/// builds data to its default value, as the data builders do when given no bytes. Keys
+/// are left to fail, since their builders generate a new random key instead.
+fn unsealed_bytes<'a>(
+    builder: &TypeBuilder,
+    bytes: &'a ByteSource,
+) -> Result<Option<&'a [u8]>, CryptoError> {
+    match (builder, bytes.get()) {
+        (_, Ok(bytes)) => Ok(Some(bytes)),
+        (
+            TypeBuilder::Data(_),
+            Err(SourceError::NotFound {
+                kind: NotFoundKind::Vector,
+            }),
+        ) => Ok(None),
+        (_, Err(e)) => Err(e.into()),
+    }
+}
+
+/// Returns the fingerprint indexed for an entry with the given builder and value, which
+/// only unsealed public keys have, see `Entry::fingerprint`
+pub(crate) fn public_key_fingerprint(builder: &TypeBuilder, value: &State) -> Option<String> {
+    match (builder, value) {
+        (
+            TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(_))),
+            State::Unsealed { ref bytes },
+        ) => bytes.get().ok().map(crate::key::fingerprint),
+        _ => None,
+    }
+}
+
+/// Fails with `CryptoError::TimeLockNotElapsed` if the time lock has not yet passed
+fn check_time_lock(unlock_at: &Option<DateTime<Utc>>) -> Result<(), CryptoError> {
+    match *unlock_at {
+        Some(unlock_at) if clock::now() < unlock_at => {
+            Err(CryptoError::TimeLockNotElapsed { unlock_at })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn broken_link(path: &str, source: CryptoError) -> CryptoError {
+    CryptoError::BrokenChainLink {
+        path: path.to_owned(),
+        source: Box::new(source),
+    }
+}
+
+/// Drives the future to completion unless the token is cancelled first
+async fn cancellable<F: Future>(
+    token: &CancellationToken,
+    fut: F,
+) -> Result<F::Output, CryptoError> {
+    if token.is_cancelled() {
+        return Err(CryptoError::Cancelled);
+    }
+    let cancelled = token.cancelled();
+    pin_mut!(fut, cancelled);
+    match future::select(fut, cancelled).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(CryptoError::Cancelled),
+    }
+}
+
+/// Writer that discards its input and only counts the bytes written to it
+#[derive(Default)]
+struct CountingWriter {
+    count: usize,
+}
+
+impl std::io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.count += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn to_json<S: Serialize>(value: &S) -> Result<serde_json::Value, CryptoError> {
+    serde_json::to_value(value).map_err(|e| CryptoError::InternalError {
+        source: Box::new(e),
+    })
+}
+
+/// The set of entry fields that changed between two versions of an entry at the same path
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct EntryPatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder: Option<TypeBuilder>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value: Option<State>,
+    /// Replaces the entry's whole metadata map when present
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl EntryPatch {
+    /// Returns true if the patch does not change any field
+    pub fn is_empty(&self) -> bool {
+        self.builder.is_none() && self.value.is_none() && self.metadata.is_none()
+    }
+
+    /// Applies the patch to the given entry, replacing each field present in the patch.
+    /// An empty patch returns the entry as is, keeping any value already resolved cached.
+    pub fn apply<T: StorableType>(self, entry: Entry<T>) -> Entry<T> {
+        if self.is_empty() {
+            return entry;
+        }
+        Entry::new(
+            entry.path,
+            self.builder.unwrap_or(entry.builder),
+            self.value.unwrap_or(entry.value),
+        )
+        .with_metadata(self.metadata.unwrap_or(entry.metadata))
+    }
+}
+
+/// Builds a value from the result of unsealing a ciphertext.
+///
+/// An attacker able to submit modified ciphertexts and time the response could otherwise
+/// distinguish a failed MAC check (early return before building) from a ciphertext that
+/// authenticated but whose plaintext failed to build. To avoid revealing which step failed,
+/// the builder is always run, against an empty placeholder if unsealing failed, and the
+/// outcome is only decided once both steps have completed. This narrows the gap but does
+/// not make builders themselves constant-time.
+fn build_unsealed<B: Builder>(
+    builder: &B,
+    unsealed: Result<ByteSource, CryptoError>,
+) -> Result<B::Output, CryptoError> {
+    let plaintext = unsealed.and_then(|bs| Ok(bs.get()?.to_vec()));
+    let built = builder.build(Some(plaintext.as_deref().unwrap_or(&[])));
+    plaintext.and(built)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "t", content = "c")]
 pub enum State {
     Referenced {
@@ -244,12 +955,67 @@ pub enum State {
     Sealed {
         ciphertext: ByteSource,
         algorithm: ByteAlgorithm,
+        /// Time before which the entry refuses to resolve, see `ToEntry::to_time_locked_entry`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unlock_at: Option<DateTime<Utc>>,
     },
     Unsealed {
         bytes: ByteSource,
     },
 }
 
+impl State {
+    /// Returns the kind of this state
+    pub fn kind(&self) -> StateKind {
+        match self {
+            State::Referenced { .. } => StateKind::Referenced,
+            State::Sealed { .. } => StateKind::Sealed,
+            State::Unsealed { .. } => StateKind::Unsealed,
+        }
+    }
+
+    /// Replaces the storer of this state, if it is a reference, and of every reference
+    /// embedded in the keys of its sealing algorithm
+    pub(crate) fn rebind_storer(self, new_storer: &TypeStorer) -> State {
+        match self {
+            State::Referenced { path, .. } => State::Referenced {
+                path,
+                storer: new_storer.clone(),
+            },
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                unlock_at,
+            } => State::Sealed {
+                ciphertext,
+                algorithm: algorithm.rebind_storer(new_storer),
+                unlock_at,
+            },
+            State::Unsealed { bytes } => State::Unsealed { bytes },
+        }
+    }
+
+    /// Appends the path of every entry this state will fetch from a storer when it is
+    /// resolved, following the keys of sealing algorithms down to their references
+    pub(crate) fn collect_referenced_paths(&self, paths: &mut Vec<EntryPath>) {
+        match self {
+            State::Referenced { ref path, .. } => paths.push(path.clone()),
+            State::Sealed { ref algorithm, .. } => algorithm.collect_referenced_paths(paths),
+            State::Unsealed { .. } => (),
+        }
+    }
+
+    /// Returns when the state's bytes were last modified. References carry no bytes
+    /// of their own and have no modification time.
+    pub fn last_modified(&self) -> Option<DateTime<Utc>> {
+        match self {
+            State::Unsealed { ref bytes } => bytes.get_last_modified().ok().copied(),
+            State::Sealed { ref ciphertext, .. } => ciphertext.get_last_modified().ok().copied(),
+            State::Referenced { .. } => None,
+        }
+    }
+}
+
 pub trait HasBuilder {
     type Builder: Builder<Output = Self>;
 
@@ -287,22 +1053,58 @@ pub trait ToEntry: StorableType + Sized {
         algorithm: ByteAlgorithm,
     ) -> Result<Entry<Self>, CryptoError> {
         let byte_source = self.byte_source();
-        let ciphertext = algorithm.seal(&byte_source).await?;
+        let ciphertext = algorithm.seal_for_path(&byte_source, &path).await?;
         Ok(Entry::new(
             path,
             self.builder().into(),
             State::Sealed {
                 ciphertext,
                 algorithm,
+                unlock_at: None,
             },
         ))
     }
 
-    fn to_unsealed_entry(self, path: EntryPath) -> Result<Entry<Self>, CryptoError> {
+    /// Seals the value like `to_sealed_entry`, additionally authenticating `path` as
+    /// associated data. Moving the sealed value under another path makes it fail to
+    /// resolve with `CryptoError::CiphertextFailedVerification`. Only algorithms accepting
+    /// associated data can be bound, see `ByteAlgorithm::bind_path`.
+    async fn to_path_bound_entry(
+        self,
+        path: EntryPath,
+        algorithm: ByteAlgorithm,
+    ) -> Result<Entry<Self>, CryptoError> {
+        self.to_sealed_entry(path, algorithm.bind_path()?).await
+    }
+
+    /// Seals the value like `to_sealed_entry`, but the entry refuses to resolve with
+    /// `CryptoError::TimeLockNotElapsed` until `unlock_at` has passed. The lock is only
+    /// enforced by this crate, anyone holding the algorithm's key can still unseal the
+    /// ciphertext directly.
+    async fn to_time_locked_entry(
+        self,
+        path: EntryPath,
+        algorithm: ByteAlgorithm,
+        unlock_at: DateTime<Utc>,
+    ) -> Result<Entry<Self>, CryptoError> {
+        let byte_source = self.byte_source();
+        let ciphertext = algorithm.seal_for_path(&byte_source, &path).await?;
         Ok(Entry::new(
             path,
             self.builder().into(),
-            State::Unsealed {
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                unlock_at: Some(unlock_at),
+            },
+        ))
+    }
+
+    fn to_unsealed_entry(self, path: EntryPath) -> Result<Entry<Self>, CryptoError> {
+        Ok(Entry::new(
+            path,
+            self.builder().into(),
+            State::Unsealed {
                 bytes: self.byte_source(),
             },
         ))
@@ -323,6 +1125,49 @@ pub enum Type {
 
 impl StorableType for Type {}
 
+impl Entry<Type> {
+    /// Narrows the entry to a borrowed view of data without consuming it, which is
+    /// useful for picking the data entries out of a mixed listing. Returns
+    /// `CryptoError::NotDowncastable` if the entry does not hold data.
+    pub fn as_data(&self) -> Result<DataView<'_>, CryptoError> {
+        let builder = DataBuilder::try_from(TypeBuilderContainer(self.builder))?;
+        Ok(DataView {
+            entry: self,
+            builder,
+        })
+    }
+}
+
+/// Borrowed view of an `Entry<Type>` known to hold data, see `Entry::as_data`
+#[derive(Debug, Clone, Copy)]
+pub struct DataView<'a> {
+    entry: &'a Entry<Type>,
+    builder: DataBuilder,
+}
+
+impl<'a> DataView<'a> {
+    pub fn path(&self) -> &'a str {
+        &self.entry.path
+    }
+
+    pub fn builder(&self) -> DataBuilder {
+        self.builder
+    }
+
+    /// Returns the entry being viewed
+    pub fn entry(&self) -> &'a Entry<Type> {
+        self.entry
+    }
+
+    /// Resolves the underlying entry and returns its data
+    pub async fn resolve(&self) -> Result<&'a Data, CryptoError> {
+        match self.entry.resolve().await? {
+            Type::Data(data) => Ok(data),
+            Type::Key(_) => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
 impl HasIndex for Type {
     type Index = Document;
 
@@ -377,13 +1222,946 @@ impl Builder for TypeBuilder {
     }
 }
 
+/// Item of a stream that can be resolved by `ResolveEach`. Streams of entries and
+/// streams of fallible entries, such as those returned by `RedactStorer::list_stream`,
+/// are both supported; an error item is passed through as is.
+pub trait ResolvableItem<T: StorableType>: Send {
+    fn into_entry(self) -> Result<Entry<T>, CryptoError>;
+}
+
+impl<T: StorableType> ResolvableItem<T> for Entry<T> {
+    fn into_entry(self) -> Result<Entry<T>, CryptoError> {
+        Ok(self)
+    }
+}
+
+impl<T: StorableType> ResolvableItem<T> for Result<Entry<T>, CryptoError> {
+    fn into_entry(self) -> Result<Entry<T>, CryptoError> {
+        self
+    }
+}
+
+/// Extends streams of entries with lazy resolution
+pub trait ResolveEach<T: StorableType>: Stream + Sized
+where
+    Self::Item: ResolvableItem<T>,
+{
+    /// Resolves each entry as it is polled, following references and unsealing it like
+    /// `Entry::take_resolve`. Entries are resolved one at a time and in order, and only
+    /// once the previous value has been consumed.
+    fn resolve_each<'a>(self) -> BoxStream<'a, Result<T, CryptoError>>
+    where
+        Self: Send + 'a;
+}
+
+impl<T: StorableType, S: Stream + Sized> ResolveEach<T> for S
+where
+    S::Item: ResolvableItem<T>,
+{
+    fn resolve_each<'a>(self) -> BoxStream<'a, Result<T, CryptoError>>
+    where
+        Self: Send + 'a,
+    {
+        self.then(|item| async move { item.into_entry()?.take_resolve().await })
+            .boxed()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::{Type, TypeBuilder, TypeBuilderContainer};
+    use super::{
+        Entry, ProvenanceHop, ResolveEach, SerializationFormat, State, StateKind, Type,
+        TypeBuilder, TypeBuilderContainer,
+    };
     use crate::{
-        BoolDataBuilder, Builder, Data, DataBuilder, HasBuilder, HasIndex, StringDataBuilder,
+        clock::{clear_thread_clock, set_thread_clock, MockClock},
+        key::{
+            aesgcmsiv::Aes256GcmSivSymmetricKeyBuilder,
+            ring::{RingEd25519PublicAsymmetricKeyBuilder, RingEd25519SecretAsymmetricKeyBuilder},
+            sodiumoxide::{
+                SodiumOxideCurve25519PublicAsymmetricKeyBuilder,
+                SodiumOxideCurve25519SecretAsymmetricKeyBuilder,
+                SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder,
+                SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder,
+                SodiumOxideEd25519PublicAsymmetricKeyBuilder,
+                SodiumOxideEd25519SecretAsymmetricKeyBuilder, SodiumOxideSymmetricKey,
+                SodiumOxideSymmetricKeyAlgorithm, SodiumOxideSymmetricKeyBuilder,
+            },
+            EncryptingKeyBuilder, SigningKeyBuilder, VerifyingKeyBuilder,
+        },
+        nonce::sodiumoxide::SodiumOxideSymmetricNonce,
+        storage::tests::MockStorer,
+        Algorithm, AsymmetricKeyBuilder, BinaryDataBuilder, BoolDataBuilder, Builder,
+        ByteAlgorithm, ByteSource, CryptoError, Data, DataBuilder, DecimalDataBuilder,
+        F64DataBuilder, HasBuilder, HasIndex, I64DataBuilder, IndexedStorer, KeyBuilder,
+        MemoryStorer, PublicAsymmetricKeyBuilder, RedactStorer, SecretAsymmetricKeyBuilder, Storer,
+        StringDataBuilder, SymmetricKeyBuilder, ToEntry, ToSymmetricByteAlgorithm, U64DataBuilder,
+        VectorByteSource,
     };
-    use std::convert::TryInto;
+    use chrono::{TimeZone, Utc};
+    use futures::{stream, StreamExt, TryStreamExt};
+    use std::{
+        convert::{TryFrom, TryInto},
+        sync::Arc,
+        time::Duration,
+    };
+    use tokio_util::sync::CancellationToken;
+
+    impl TypeBuilder {
+        /// Returns a builder of every concrete type, for tests that need to cover them all.
+        /// Add new builders here so that `assert_builder_roundtrip` checks their downcasts.
+        pub(crate) fn all_variants() -> Vec<TypeBuilder> {
+            vec![
+                DataBuilder::Bool(BoolDataBuilder::default()).into(),
+                DataBuilder::U64(U64DataBuilder::default()).into(),
+                DataBuilder::I64(I64DataBuilder::default()).into(),
+                DataBuilder::F64(F64DataBuilder::default()).into(),
+                DataBuilder::String(StringDataBuilder::default()).into(),
+                DataBuilder::Binary(BinaryDataBuilder {}).into(),
+                DataBuilder::Decimal(DecimalDataBuilder::default()).into(),
+                SodiumOxideSymmetricKeyBuilder::default().into(),
+                Aes256GcmSivSymmetricKeyBuilder::default().into(),
+                SodiumOxideCurve25519SecretAsymmetricKeyBuilder::default().into(),
+                SodiumOxideCurve25519PublicAsymmetricKeyBuilder::default().into(),
+                SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder::default().into(),
+                SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder::default().into(),
+                SodiumOxideEd25519SecretAsymmetricKeyBuilder::default().into(),
+                SodiumOxideEd25519PublicAsymmetricKeyBuilder::default().into(),
+                RingEd25519SecretAsymmetricKeyBuilder::default().into(),
+                RingEd25519PublicAsymmetricKeyBuilder::default().into(),
+            ]
+        }
+    }
+
+    /// Panics unless the builder downcasts into `B`
+    fn assert_downcasts<B: TryFrom<TypeBuilderContainer>>(builder: TypeBuilder) {
+        if B::try_from(TypeBuilderContainer(builder)).is_err() {
+            panic!(
+                "{:?} should have been downcastable into {}",
+                builder,
+                std::any::type_name::<B>()
+            );
+        }
+    }
+
+    /// Round-trips the builder through JSON and checks that the result downcasts into
+    /// every builder type along its path, down to the concrete builder. The matches are
+    /// exhaustive, so a new builder variant fails to compile here until it is covered.
+    fn assert_builder_roundtrip(builder: TypeBuilder) {
+        let json = serde_json::to_string(&builder).unwrap();
+        let decoded: TypeBuilder = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&decoded).unwrap(), json);
+        assert_downcasts::<TypeBuilder>(decoded);
+
+        match decoded {
+            TypeBuilder::Data(db) => {
+                assert_downcasts::<DataBuilder>(decoded);
+                match db {
+                    DataBuilder::Bool(_) => assert_downcasts::<BoolDataBuilder>(decoded),
+                    DataBuilder::U64(_) => assert_downcasts::<U64DataBuilder>(decoded),
+                    DataBuilder::I64(_) => assert_downcasts::<I64DataBuilder>(decoded),
+                    DataBuilder::F64(_) => assert_downcasts::<F64DataBuilder>(decoded),
+                    DataBuilder::String(_) => assert_downcasts::<StringDataBuilder>(decoded),
+                    DataBuilder::Binary(_) => assert_downcasts::<BinaryDataBuilder>(decoded),
+                    DataBuilder::Decimal(_) => assert_downcasts::<DecimalDataBuilder>(decoded),
+                }
+            }
+            TypeBuilder::Key(kb) => {
+                assert_downcasts::<KeyBuilder>(decoded);
+                match kb {
+                    KeyBuilder::Symmetric(skb) => {
+                        assert_downcasts::<SymmetricKeyBuilder>(decoded);
+                        assert_downcasts::<EncryptingKeyBuilder>(decoded);
+                        match skb {
+                            SymmetricKeyBuilder::SodiumOxide(_) => {
+                                assert_downcasts::<SodiumOxideSymmetricKeyBuilder>(decoded)
+                            }
+                            SymmetricKeyBuilder::Aes256GcmSiv(_) => {
+                                assert_downcasts::<Aes256GcmSivSymmetricKeyBuilder>(decoded)
+                            }
+                        }
+                    }
+                    KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(pakb)) => {
+                        assert_downcasts::<AsymmetricKeyBuilder>(decoded);
+                        assert_downcasts::<PublicAsymmetricKeyBuilder>(decoded);
+                        match pakb {
+                            PublicAsymmetricKeyBuilder::SodiumOxideCurve25519(_) => {
+                                assert_downcasts::<SodiumOxideCurve25519PublicAsymmetricKeyBuilder>(
+                                    decoded,
+                                )
+                            }
+                            PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(_) => {
+                                assert_downcasts::<
+                                    SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder,
+                                >(decoded)
+                            }
+                            PublicAsymmetricKeyBuilder::SodiumOxideEd25519(_) => {
+                                assert_downcasts::<SodiumOxideEd25519PublicAsymmetricKeyBuilder>(
+                                    decoded,
+                                );
+                                assert_downcasts::<VerifyingKeyBuilder>(decoded);
+                            }
+                            PublicAsymmetricKeyBuilder::RingEd25519(_) => {
+                                assert_downcasts::<RingEd25519PublicAsymmetricKeyBuilder>(decoded);
+                                assert_downcasts::<VerifyingKeyBuilder>(decoded);
+                            }
+                        }
+                    }
+                    KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Secret(sakb)) => {
+                        assert_downcasts::<AsymmetricKeyBuilder>(decoded);
+                        assert_downcasts::<SecretAsymmetricKeyBuilder>(decoded);
+                        match sakb {
+                            SecretAsymmetricKeyBuilder::SodiumOxideCurve25519(_) => {
+                                assert_downcasts::<SodiumOxideCurve25519SecretAsymmetricKeyBuilder>(
+                                    decoded,
+                                );
+                                assert_downcasts::<EncryptingKeyBuilder>(decoded);
+                            }
+                            SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(_) => {
+                                assert_downcasts::<
+                                    SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder,
+                                >(decoded)
+                            }
+                            SecretAsymmetricKeyBuilder::SodiumOxideEd25519(_) => {
+                                assert_downcasts::<SodiumOxideEd25519SecretAsymmetricKeyBuilder>(
+                                    decoded,
+                                );
+                                assert_downcasts::<SigningKeyBuilder>(decoded);
+                            }
+                            SecretAsymmetricKeyBuilder::RingEd25519(_) => {
+                                assert_downcasts::<RingEd25519SecretAsymmetricKeyBuilder>(decoded);
+                                assert_downcasts::<SigningKeyBuilder>(decoded);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_all_builder_variants_roundtrip() {
+        let variants = TypeBuilder::all_variants();
+        assert_eq!(variants.len(), 17);
+        for builder in variants {
+            assert_builder_roundtrip(builder);
+        }
+    }
+
+    async fn get_sealed_data_entry() -> Entry<Data> {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".key.".to_owned())
+            })
+            .await
+            .unwrap();
+        Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), algorithm)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_entry_time_lock() {
+        let unlock_at = Utc.with_ymd_and_hms(2030, 1, 1, 0, 0, 0).unwrap();
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".key.".to_owned())
+            })
+            .await
+            .unwrap();
+        let entry = Data::String("hello, world!".to_owned())
+            .to_time_locked_entry(".data.".to_owned(), algorithm, unlock_at)
+            .await
+            .unwrap();
+        let entry: Entry<Data> =
+            serde_json::from_str(&serde_json::to_string(&entry).unwrap()).unwrap();
+
+        let clock = Arc::new(MockClock::new(unlock_at - chrono::Duration::seconds(1)));
+        set_thread_clock(clock.clone());
+        match entry.resolve().await {
+            Err(CryptoError::TimeLockNotElapsed { unlock_at: u }) => assert_eq!(u, unlock_at),
+            _ => panic!("Time-locked entry should not have resolved before its unlock time"),
+        }
+        clock.advance(chrono::Duration::seconds(1));
+        let resolved = entry.resolve().await;
+        clear_thread_clock();
+        assert_eq!(resolved.unwrap(), &Data::String("hello, world!".to_owned()));
+
+        // Entries sealed without a lock serialize exactly as before
+        let unlocked = get_sealed_data_entry().await;
+        assert!(!serde_json::to_string(&unlocked)
+            .unwrap()
+            .contains("unlock_at"));
+    }
+
+    #[tokio::test]
+    async fn test_entry_diff_sealed_ciphertext() {
+        let entry = get_sealed_data_entry().await;
+        let (ciphertext, algorithm) = match entry.value {
+            State::Sealed {
+                ref ciphertext,
+                ref algorithm,
+                ..
+            } => (ciphertext, algorithm),
+            _ => panic!("Entry should have been sealed"),
+        };
+        let new_ciphertext = algorithm.seal(&"goodbye, world!".into()).await.unwrap();
+        let new_ciphertext_bytes = new_ciphertext.get().unwrap().to_vec();
+        assert_ne!(ciphertext.get().unwrap(), new_ciphertext_bytes.as_slice());
+        let updated = Entry::<Data>::new(
+            entry.path.clone(),
+            entry.builder,
+            State::Sealed {
+                ciphertext: new_ciphertext,
+                algorithm: serde_json::from_value(serde_json::to_value(algorithm).unwrap())
+                    .unwrap(),
+                unlock_at: None,
+            },
+        );
+
+        let patch = entry.diff(&updated).unwrap();
+        assert!(patch.builder.is_none());
+        match patch.value {
+            Some(State::Sealed { ref ciphertext, .. }) => {
+                assert_eq!(ciphertext.get().unwrap(), new_ciphertext_bytes.as_slice())
+            }
+            _ => panic!("Patch should have contained the new sealed value"),
+        }
+        assert!(entry.diff(&entry).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_entry_take_resolve_mac_failure() {
+        let entry = get_sealed_data_entry().await;
+        let entry = match entry.value {
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                ..
+            } => {
+                let mut bytes = ciphertext.get().unwrap().to_vec();
+                let last = bytes.len() - 1;
+                bytes[last] ^= 0xff;
+                Entry::<Data>::new(
+                    entry.path,
+                    entry.builder,
+                    State::Sealed {
+                        ciphertext: bytes.as_slice().into(),
+                        algorithm,
+                        unlock_at: None,
+                    },
+                )
+            }
+            _ => panic!("Entry should have been sealed"),
+        };
+        match entry.take_resolve().await {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Corrupted ciphertext should have failed verification"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_take_resolve_build_failure() {
+        let entry = get_sealed_data_entry().await;
+        let entry = Entry::<Data>::new(
+            entry.path,
+            TypeBuilder::Data(DataBuilder::Bool(BoolDataBuilder {})),
+            entry.value,
+        );
+        match entry.take_resolve().await {
+            Err(CryptoError::NotDeserializableToBaseDataType) => (),
+            _ => panic!("Sealed string should not have been buildable as a bool"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_flatten_referenced_sealed() {
+        let storer = MemoryStorer::new();
+        storer.create(get_sealed_data_entry().await).await.unwrap();
+        let entry = Entry::<Data>::new(
+            ".reference.".to_owned(),
+            TypeBuilder::Data(DataBuilder::String(StringDataBuilder {})),
+            State::Referenced {
+                path: ".data.".to_owned(),
+                storer: storer.clone().into(),
+            },
+        );
+
+        let flattened = entry.flatten().await.unwrap();
+        assert_eq!(flattened.path, ".reference.");
+        match flattened.value {
+            State::Unsealed { ref bytes } => {
+                assert_eq!(bytes.get().unwrap(), "hello, world!".as_bytes())
+            }
+            _ => panic!("Flattened entry should have been unsealed"),
+        }
+
+        storer.delete::<Data>(".data.").await.unwrap();
+        match flattened.resolve().await.unwrap() {
+            Data::String(s) => assert_eq!(s, "hello, world!"),
+            _ => panic!("Flattened data should have been a string"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_from_sealed_bytes() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+            })
+            .await
+            .unwrap();
+        let sealed = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), algorithm.clone())
+            .await
+            .unwrap();
+        let ciphertext = match sealed.value {
+            State::Sealed { ref ciphertext, .. } => ciphertext.get().unwrap().to_vec(),
+            _ => panic!("Entry should have been sealed"),
+        };
+
+        let entry = Entry::<Data>::from_sealed_bytes(
+            ".received.".to_owned(),
+            sealed.builder,
+            ciphertext.as_slice().into(),
+            algorithm,
+        );
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_reference() {
+        let storer = MemoryStorer::new();
+        let key = SodiumOxideSymmetricKey::new();
+        let key_bytes = key.key.as_ref().to_vec();
+        storer
+            .create(
+                key.to_unsealed_entry(".keys.symmetric.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let entry =
+            Entry::<SodiumOxideSymmetricKey>::reference(".keys.symmetric.".to_owned(), storer);
+        match entry.value {
+            State::Referenced { ref path, .. } => assert_eq!(path, ".keys.symmetric."),
+            _ => panic!("Entry should have been a reference"),
+        }
+        assert_eq!(
+            entry.resolve().await.unwrap().key.as_ref(),
+            key_bytes.as_slice()
+        );
+    }
+
+    #[test]
+    fn test_entry_serialized_size_unsealed() {
+        let entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        assert_eq!(
+            entry.serialized_size().unwrap(),
+            serde_json::to_vec(&entry).unwrap().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_serialized_size_sealed_key() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.master.".to_owned())
+            })
+            .await
+            .unwrap();
+        let entry = SodiumOxideSymmetricKey::new()
+            .to_sealed_entry(".keys.sealed.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        assert_eq!(
+            entry.serialized_size().unwrap(),
+            serde_json::to_vec(&entry).unwrap().len()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_entry_to_bytes_messagepack() {
+        let key = SodiumOxideSymmetricKey::new();
+        let key_bytes = key.key.as_ref().to_vec();
+        let entry = key
+            .to_unsealed_entry(".keys.symmetric.".to_owned())
+            .unwrap();
+
+        let json = entry.to_bytes(SerializationFormat::Json).unwrap();
+        let msgpack = entry.to_bytes(SerializationFormat::MessagePack).unwrap();
+        assert!(msgpack.len() < json.len());
+        // The key bytes are stored raw rather than base64-encoded
+        assert!(msgpack
+            .windows(key_bytes.len())
+            .any(|w| w == key_bytes.as_slice()));
+
+        let decoded: Entry<SodiumOxideSymmetricKey> =
+            Entry::from_bytes(&msgpack, SerializationFormat::MessagePack).unwrap();
+        assert_eq!(decoded.to_bytes(SerializationFormat::Json).unwrap(), json);
+        assert_eq!(
+            decoded.resolve().await.unwrap().key.as_ref(),
+            key_bytes.as_slice()
+        );
+    }
+
+    /// Collects the backend name of every storer embedded in the state
+    fn storer_names(state: &State, names: &mut Vec<&'static str>) {
+        match state {
+            State::Referenced { storer, .. } => names.push(storer.backend_name()),
+            State::Sealed { algorithm, .. } => algorithm_storer_names(algorithm, names),
+            State::Unsealed { .. } => (),
+        }
+    }
+
+    fn algorithm_storer_names(algorithm: &ByteAlgorithm, names: &mut Vec<&'static str>) {
+        match algorithm {
+            ByteAlgorithm::SodiumOxideSymmetricKey(sosku) => storer_names(&sosku.key.value, names),
+            ByteAlgorithm::SodiumOxideSecretAsymmetricKey(sosaku) => {
+                storer_names(&sosaku.secret_key.value, names);
+                if let Some(ref public_key) = sosaku.public_key {
+                    storer_names(&public_key.value, names);
+                }
+            }
+            ByteAlgorithm::SodiumOxidePublicAsymmetricKey(sopaku) => {
+                storer_names(&sopaku.public_key.value, names);
+                storer_names(&sopaku.secret_key.value, names);
+            }
+            ByteAlgorithm::PassphraseSymmetric(_) => (),
+            ByteAlgorithm::Aes256GcmSivSymmetricKey(agsku) => storer_names(&agsku.key.value, names),
+            ByteAlgorithm::SizeLimited(sla) => algorithm_storer_names(&sla.algorithm, names),
+            ByteAlgorithm::Compressed(ca) => algorithm_storer_names(&ca.algorithm, names),
+            ByteAlgorithm::Audited(aa) => algorithm_storer_names(&aa.algorithm, names),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_rebind_storer() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".keys.kek.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let nonce = || SodiumOxideSymmetricNonce::from_slice(&[0u8; 24]).unwrap();
+
+        // Data sealed by a data key, which is itself sealed by a referenced KEK
+        let kek_algorithm =
+            ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                key: Box::new(Entry::reference(".keys.kek.".to_owned(), storer.clone())),
+                nonce: nonce(),
+            });
+        let data_key = SodiumOxideSymmetricKey::new()
+            .to_sealed_entry(".keys.dek.".to_owned(), kek_algorithm)
+            .await
+            .unwrap();
+        let data_algorithm =
+            ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                key: Box::new(data_key),
+                nonce: nonce(),
+            });
+        let entry = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), data_algorithm)
+            .await
+            .unwrap();
+        let mut names = vec![];
+        storer_names(&entry.value, &mut names);
+        assert_eq!(names, vec!["memory"]);
+
+        let entry = entry.rebind_storer(RedactStorer::new("http://localhost:8080").into());
+        let mut names = vec![];
+        storer_names(&entry.value, &mut names);
+        assert_eq!(names, vec!["redact"]);
+        assert_eq!(entry.path, ".data.");
+
+        let reference = Entry::<Data>::reference(".data.".to_owned(), storer)
+            .rebind_storer(RedactStorer::new("http://localhost:8080").into());
+        let mut names = vec![];
+        storer_names(&reference.value, &mut names);
+        assert_eq!(names, vec!["redact"]);
+    }
+
+    /// Builds data sealed by a data key, which is sealed by a referenced KEK, which is
+    /// itself stored sealed by an embedded master key
+    async fn get_two_level_chain() -> (MemoryStorer, Entry<Data>) {
+        let storer = MemoryStorer::new();
+        let nonce = || SodiumOxideSymmetricNonce::from_slice(&[0u8; 24]).unwrap();
+        let master_algorithm =
+            ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                key: Box::new(
+                    SodiumOxideSymmetricKey::new()
+                        .to_unsealed_entry(".keys.master.".to_owned())
+                        .unwrap(),
+                ),
+                nonce: nonce(),
+            });
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_sealed_entry(".keys.kek.".to_owned(), master_algorithm)
+                    .await
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let kek_algorithm =
+            ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                key: Box::new(Entry::reference(".keys.kek.".to_owned(), storer.clone())),
+                nonce: nonce(),
+            });
+        let data_key = SodiumOxideSymmetricKey::new()
+            .to_sealed_entry(".keys.dek.".to_owned(), kek_algorithm)
+            .await
+            .unwrap();
+        let entry = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(
+                ".data.".to_owned(),
+                ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                    key: Box::new(data_key),
+                    nonce: nonce(),
+                }),
+            )
+            .await
+            .unwrap();
+        (storer, entry)
+    }
+
+    #[tokio::test]
+    async fn test_entry_bundle() {
+        let (storer, entry) = get_two_level_chain().await;
+        let mut names = vec![];
+        storer_names(&entry.value, &mut names);
+        assert_eq!(names, vec!["memory"]);
+
+        let bundled = entry.bundle(&storer).await.unwrap();
+        let mut names = vec![];
+        storer_names(&bundled.value, &mut names);
+        assert!(names.is_empty());
+        assert_eq!(bundled.path, ".data.");
+
+        // The bundle no longer needs the storer it was built from
+        storer
+            .delete::<SodiumOxideSymmetricKey>(".keys.kek.")
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&bundled).unwrap();
+        let bundled: Entry<Data> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            bundled.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_each() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".keys.kek.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let mut paths = vec![];
+        for i in 0..3u64 {
+            let algorithm = Entry::<SodiumOxideSymmetricKey>::reference(
+                ".keys.kek.".to_owned(),
+                storer.clone(),
+            )
+            .to_symmetric_byte_algorithm(None)
+            .await
+            .unwrap();
+            let path = format!(".data.{}.", i);
+            storer
+                .create(
+                    Data::U64(i)
+                        .to_sealed_entry(path.clone(), algorithm)
+                        .await
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            paths.push(path);
+        }
+
+        let entries = stream::iter(paths.clone()).then(|path| {
+            let storer = storer.clone();
+            async move { storer.get::<Data>(&path).await }
+        });
+        let values = entries
+            .resolve_each()
+            .try_collect::<Vec<Data>>()
+            .await
+            .unwrap();
+        assert_eq!(values, vec![Data::U64(0), Data::U64(1), Data::U64(2)]);
+
+        // A stream of plain entries resolves the same way
+        let mut entries = vec![];
+        for path in paths.iter() {
+            entries.push(storer.get::<Data>(path).await.unwrap());
+        }
+        let mut values = stream::iter(entries).resolve_each();
+        assert_eq!(values.next().await.unwrap().unwrap(), Data::U64(0));
+        assert_eq!(values.next().await.unwrap().unwrap(), Data::U64(1));
+        assert_eq!(values.next().await.unwrap().unwrap(), Data::U64(2));
+        assert!(values.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_entry_verify_chain_integrity() {
+        let (_, entry) = get_two_level_chain().await;
+        entry.verify_chain_integrity().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_entry_verify_chain_integrity_corrupted_kek() {
+        let (storer, entry) = get_two_level_chain().await;
+        let kek = storer
+            .get::<SodiumOxideSymmetricKey>(".keys.kek.")
+            .await
+            .unwrap();
+        let value = match kek.value {
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                ..
+            } => {
+                let mut bytes = ciphertext.get().unwrap().to_vec();
+                bytes[0] ^= 0x01;
+                State::Sealed {
+                    ciphertext: bytes.as_slice().into(),
+                    algorithm,
+                    unlock_at: None,
+                }
+            }
+            _ => panic!("KEK should have been stored sealed"),
+        };
+        storer
+            .create(Entry::<SodiumOxideSymmetricKey>::new(
+                kek.path,
+                kek.builder,
+                value,
+            ))
+            .await
+            .unwrap();
+
+        match entry.verify_chain_integrity().await {
+            Err(CryptoError::BrokenChainLink { path, .. }) => assert_eq!(path, ".keys.kek."),
+            _ => panic!("Verification should have failed at the KEK"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_without_metadata_deserializes() {
+        let entry = Data::U64(5)
+            .to_unsealed_entry(".number.".to_owned())
+            .unwrap();
+        let json = serde_json::to_value(&entry).unwrap();
+        assert!(json.get("metadata").is_none());
+
+        let entry: Entry<Data> = serde_json::from_value(json).unwrap();
+        assert!(entry.metadata.is_empty());
+        match entry.resolve().await.unwrap() {
+            Data::U64(n) => assert_eq!(*n, 5),
+            _ => panic!("Resolved data should have been a u64"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_resolve_timeout() {
+        // A backend that accepts connections but never answers them
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+            }
+        });
+
+        let entry = Entry::<Data>::reference(".data.".to_owned(), RedactStorer::new(&url));
+        match entry.resolve_timeout(Duration::from_millis(100)).await {
+            Err(CryptoError::ResolutionTimeout) => (),
+            _ => panic!("Resolution against an unresponsive storer should have timed out"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_resolve_cancellable() {
+        // A backend that signals when a request arrives and then never answers it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let request_arrived = CancellationToken::new();
+        let signal = request_arrived.clone();
+        tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((socket, _)) = listener.accept().await {
+                connections.push(socket);
+                signal.cancel();
+            }
+        });
+
+        let entry = Entry::<Data>::reference(".data.".to_owned(), RedactStorer::new(&url));
+        let token = CancellationToken::new();
+        let (result, _) = tokio::time::timeout(Duration::from_secs(5), async {
+            tokio::join!(entry.resolve_cancellable(&token), async {
+                request_arrived.cancelled().await;
+                token.cancel();
+            })
+        })
+        .await
+        .expect("Cancelling the token should have stopped resolution promptly");
+        match result {
+            Err(CryptoError::Cancelled) => (),
+            _ => panic!("Resolution should have been cancelled"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_resolve_cancellable_already_cancelled() {
+        let entry = Data::U64(5)
+            .to_unsealed_entry(".number.".to_owned())
+            .unwrap();
+        let token = CancellationToken::new();
+        token.cancel();
+        match entry.resolve_cancellable(&token).await {
+            Err(CryptoError::Cancelled) => (),
+            _ => panic!("Resolution with a cancelled token should have been cancelled"),
+        }
+        match entry
+            .resolve_cancellable(&CancellationToken::new())
+            .await
+            .unwrap()
+        {
+            Data::U64(n) => assert_eq!(*n, 5),
+            _ => panic!("Resolved data should have been a u64"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_resolve_timeout_unsealed() {
+        let entry = Data::U64(5)
+            .to_unsealed_entry(".number.".to_owned())
+            .unwrap();
+        match entry.resolve_timeout(Duration::from_secs(1)).await.unwrap() {
+            Data::U64(n) => assert_eq!(*n, 5),
+            _ => panic!("Resolved data should have been a u64"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_map_value() {
+        let entry = Data::U64(5)
+            .to_unsealed_entry(".number.".to_owned())
+            .unwrap();
+        let mapped = entry
+            .map_value(|data| match data {
+                Data::U64(n) => Ok(Data::U64(n * 2)),
+                _ => Err(CryptoError::NotDowncastable),
+            })
+            .await
+            .unwrap();
+        assert_eq!(mapped.path, ".number.");
+        match mapped.resolve().await.unwrap() {
+            Data::U64(n) => assert_eq!(*n, 10),
+            _ => panic!("Mapped data should have been a u64"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_map_value_changes_variant() {
+        let entry = Data::U64(5)
+            .to_unsealed_entry(".number.".to_owned())
+            .unwrap();
+        let mapped = entry
+            .map_value(|data| Ok(Data::String(data.to_string())))
+            .await
+            .unwrap();
+        match mapped.builder {
+            TypeBuilder::Data(DataBuilder::String(_)) => (),
+            _ => panic!("Mapped entry should have a string data builder"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_entry_resolve_as_coerces_data() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                Data::String("42".to_owned())
+                    .to_unsealed_entry(".config.port.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(
+                Data::Bool(true)
+                    .to_unsealed_entry(".config.enabled.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let port = storer.get::<Data>(".config.port.").await.unwrap();
+        assert_eq!(port.resolve_as_u64().await.unwrap(), 42);
+        assert_eq!(port.resolve_as_string().await.unwrap(), "42");
+
+        let enabled = storer.get::<Data>(".config.enabled.").await.unwrap();
+        match enabled.resolve_as_u64().await {
+            Err(CryptoError::NotDowncastable) => (),
+            _ => panic!("A bool should not have been coerced to a u64"),
+        }
+        assert!(enabled.resolve_as_bool().await.unwrap());
+        assert_eq!(enabled.resolve_as_string().await.unwrap(), "true");
+    }
+
+    #[tokio::test]
+    async fn test_entry_can_unseal_valid() {
+        let entry = get_sealed_data_entry().await;
+        assert!(entry.can_unseal().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_entry_can_unseal_corrupted() {
+        let entry = get_sealed_data_entry().await;
+        let entry = match entry.value {
+            State::Sealed {
+                ciphertext,
+                algorithm,
+                ..
+            } => {
+                let mut bytes = ciphertext.get().unwrap().to_vec();
+                bytes[0] ^= 0xff;
+                let ciphertext: ByteSource = bytes.as_slice().into();
+                Entry::<Data>::new(
+                    entry.path,
+                    entry.builder,
+                    State::Sealed {
+                        ciphertext,
+                        algorithm,
+                        unlock_at: None,
+                    },
+                )
+            }
+            _ => panic!("Entry should have been sealed"),
+        };
+        match entry.can_unseal().await {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Corrupted ciphertext should have failed verification"),
+        }
+    }
 
     #[test]
     fn test_type_to_index() {
@@ -427,4 +2205,176 @@ mod tests {
             _ => panic!("Extracted data should have been a bool-type"),
         }
     }
+
+    #[tokio::test]
+    async fn test_as_data_picks_data_from_mixed_listing() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".mixed.greeting.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".mixed.key.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(
+                Data::U64(42)
+                    .to_unsealed_entry(".mixed.answer.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let entries = storer.list::<Type>(".mixed.", 0, 10).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        let mut data = vec![];
+        for view in entries.iter().filter_map(|entry| entry.as_data().ok()) {
+            data.push((view.path(), view.resolve().await.unwrap()));
+        }
+        data.sort_by_key(|(path, _)| *path);
+        assert_eq!(
+            data,
+            vec![
+                (".mixed.answer.", &Data::U64(42)),
+                (
+                    ".mixed.greeting.",
+                    &Data::String("hello, world!".to_owned())
+                ),
+            ]
+        );
+
+        let key_entry = entries.iter().find(|e| e.path == ".mixed.key.").unwrap();
+        match key_entry.as_data() {
+            Err(CryptoError::NotDowncastable) => (),
+            _ => panic!("Key entry should not have been viewable as data"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_with_provenance() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.data.".to_owned())
+            })
+            .await
+            .unwrap();
+        let key_id = algorithm.key_id().await.unwrap();
+        let data = Data::String("hello, world!".to_owned());
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                data.clone()
+                    .to_sealed_entry(".data.sealed.".to_owned(), algorithm)
+                    .await
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let reference = Entry::<Data>::new(
+            ".data.ref.".to_owned(),
+            data.builder().into(),
+            State::Referenced {
+                path: ".data.sealed.".to_owned(),
+                storer: storer.into(),
+            },
+        );
+        let (value, provenance) = reference.resolve_with_provenance().await.unwrap();
+        assert_eq!(value, data);
+        assert_eq!(
+            provenance.hops,
+            vec![
+                ProvenanceHop {
+                    path: ".data.ref.".to_owned(),
+                    state: StateKind::Referenced,
+                    key_id: None,
+                },
+                ProvenanceHop {
+                    path: ".data.sealed.".to_owned(),
+                    state: StateKind::Sealed,
+                    key_id: Some(key_id),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolve_absent_unsealed_bytes() {
+        let absent = || State::Unsealed {
+            bytes: VectorByteSource::new_now(None).into(),
+        };
+        let entry = Entry::<Data>::new(
+            ".data.".to_owned(),
+            DataBuilder::String(StringDataBuilder {}).into(),
+            absent(),
+        );
+        assert_eq!(entry.resolve().await.unwrap(), &Data::String("".to_owned()));
+        let entry = Entry::<Data>::new(
+            ".data.".to_owned(),
+            DataBuilder::U64(U64DataBuilder {}).into(),
+            absent(),
+        );
+        assert_eq!(entry.take_resolve().await.unwrap(), Data::U64(0));
+
+        // A key must never silently resolve to a freshly generated one
+        let entry = Entry::<SodiumOxideSymmetricKey>::new(
+            ".keys.".to_owned(),
+            SodiumOxideSymmetricKey::new().builder().into(),
+            absent(),
+        );
+        match entry.resolve().await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Resolving a key without bytes should have failed"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_is_resolved_and_into_inner() {
+        let entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        assert!(!entry.is_resolved());
+        assert_eq!(entry.clone().into_inner(), None);
+
+        entry.resolve().await.unwrap();
+        assert!(entry.is_resolved());
+        assert_eq!(
+            entry.into_inner(),
+            Some(Data::String("hello, world!".to_owned()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rename_keeps_resolved_value() {
+        let unsealed = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        let mut storer = MockStorer::new();
+        storer
+            .expect_private_get::<Data>()
+            .withf(|path| path == ".data.")
+            .times(1)
+            .return_once(move |_| Ok(unsealed));
+        let mut entry = Data::String("hello, world!".to_owned())
+            .to_ref_entry(".data.".to_owned(), storer)
+            .unwrap();
+        entry.resolve().await.unwrap();
+
+        entry.set_metadata("owner", "alice");
+        let entry = entry.rename(".renamed.".to_owned());
+        assert_eq!(entry.path, ".renamed.");
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
 }
@@ -2,6 +2,8 @@
 //! every function in this crate returning a Result except those used in the
 //! `Storer` trait.
 
+use crate::key::KeyRole;
+use chrono::{DateTime, Utc};
 use std::{
     error::Error,
     fmt::{self, Display, Formatter},
@@ -40,6 +42,83 @@ pub enum CryptoError {
 
     /// A signature was not verifiable
     BadSignature,
+
+    /// Provided PEM document could not be parsed
+    InvalidPem,
+
+    /// Key's algorithm is not supported by the requested operation
+    UnsupportedKeyAlgorithm,
+
+    /// Nonce could not be converted to the requested length without weakening it
+    UnsafeNonceConversion { from: usize, to: usize },
+
+    /// Resolving an entry did not complete within the allotted time
+    ResolutionTimeout,
+
+    /// Operation was abandoned before completing, either because its cancellation token
+    /// was cancelled or because the runtime running it shut down
+    Cancelled,
+
+    /// Provided key backup is malformed or uses an unsupported version or parameters
+    InvalidKeyBackup,
+
+    /// Key given to build an algorithm cannot perform the role the algorithm needs
+    WrongKeyRole { expected: KeyRole, actual: KeyRole },
+
+    /// Storage backend could not be reached or did not respond successfully
+    StorerUnavailable {
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Entry at the given path in a chain of references and sealed keys could not be
+    /// fetched or failed to authenticate
+    BrokenChainLink {
+        path: String,
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Sealed frame is truncated or uses an unsupported version or algorithm
+    InvalidFrame,
+
+    /// Algorithm derives its key from a passphrase, which was not supplied
+    PassphraseRequired,
+
+    /// Passphrase derivation parameters are malformed or exceed the allowed limits
+    InvalidPassphraseParams,
+
+    /// Sealed entry is time-locked and cannot be resolved until the given time
+    TimeLockNotElapsed { unlock_at: DateTime<Utc> },
+
+    /// Provided key material is too predictable to be used as a key
+    WeakKeyMaterial,
+
+    /// Payload exceeds the maximum size allowed by the algorithm
+    PayloadTooLarge { size: usize, max: usize },
+
+    /// Key cannot be deleted because the entry at the given path is sealed under it
+    KeyStillInUse { referencing_path: String },
+
+    /// Given value is a well-formed number outside of the range of the target type. The
+    /// value itself is not kept, as it may be decrypted plaintext.
+    NumericOverflow { target_type: &'static str },
+
+    /// Requested page size is not positive or exceeds the configured maximum
+    InvalidPageSize { requested: i64, max: i64 },
+
+    /// Configured maximum page size is not positive
+    InvalidMaxPageSize { max: i64 },
+
+    /// Algorithm carries its secret key in the clear and cannot be stored with entries
+    EmbeddedSecretKey,
+
+    /// Path prefix would match every stored entry
+    InvalidPathPrefix { prefix: String },
+
+    /// Unsealed plaintext decompresses to more than the allowed number of bytes
+    DecompressedTooLarge { max: usize },
+
+    /// Metadata key cannot be used as a single field name in a storer query
+    InvalidMetadataKey { key: String },
 }
 
 impl Error for CryptoError {
@@ -54,6 +133,29 @@ impl Error for CryptoError {
             CryptoError::NotDeserializableToBaseDataType => None,
             CryptoError::WrongNonceType => None,
             CryptoError::BadSignature => None,
+            CryptoError::InvalidPem => None,
+            CryptoError::UnsupportedKeyAlgorithm => None,
+            CryptoError::UnsafeNonceConversion { .. } => None,
+            CryptoError::ResolutionTimeout => None,
+            CryptoError::Cancelled => None,
+            CryptoError::InvalidKeyBackup => None,
+            CryptoError::WrongKeyRole { .. } => None,
+            CryptoError::StorerUnavailable { ref source } => Some(source.as_ref()),
+            CryptoError::BrokenChainLink { ref source, .. } => Some(source.as_ref()),
+            CryptoError::InvalidFrame => None,
+            CryptoError::PassphraseRequired => None,
+            CryptoError::InvalidPassphraseParams => None,
+            CryptoError::TimeLockNotElapsed { .. } => None,
+            CryptoError::WeakKeyMaterial => None,
+            CryptoError::PayloadTooLarge { .. } => None,
+            CryptoError::KeyStillInUse { .. } => None,
+            CryptoError::NumericOverflow { .. } => None,
+            CryptoError::InvalidPageSize { .. } => None,
+            CryptoError::InvalidMaxPageSize { .. } => None,
+            CryptoError::EmbeddedSecretKey => None,
+            CryptoError::InvalidPathPrefix { .. } => None,
+            CryptoError::DecompressedTooLarge { .. } => None,
+            CryptoError::InvalidMetadataKey { .. } => None,
         }
     }
 }
@@ -105,6 +207,103 @@ impl Display for CryptoError {
             CryptoError::BadSignature => {
                 write!(f, "Signature verification failed")
             }
+            CryptoError::InvalidPem => {
+                write!(f, "Provided PEM document could not be parsed")
+            }
+            CryptoError::UnsupportedKeyAlgorithm => {
+                write!(f, "Key algorithm is not supported for this operation")
+            }
+            CryptoError::UnsafeNonceConversion { ref from, ref to } => {
+                write!(
+                    f,
+                    "Nonce of {} bytes cannot be safely converted to {} bytes",
+                    from, to
+                )
+            }
+            CryptoError::ResolutionTimeout => {
+                write!(f, "Entry resolution timed out")
+            }
+            CryptoError::Cancelled => {
+                write!(f, "Operation was cancelled")
+            }
+            CryptoError::InvalidKeyBackup => {
+                write!(f, "Provided key backup could not be read")
+            }
+            CryptoError::WrongKeyRole {
+                ref expected,
+                ref actual,
+            } => {
+                write!(f, "Expected a {} key but got a {} key", expected, actual)
+            }
+            CryptoError::StorerUnavailable { .. } => {
+                write!(f, "Storage backend is unavailable")
+            }
+            CryptoError::BrokenChainLink { ref path, .. } => {
+                write!(f, "Integrity check failed for entry at {}", path)
+            }
+            CryptoError::InvalidFrame => {
+                write!(f, "Provided sealed frame could not be read")
+            }
+            CryptoError::PassphraseRequired => {
+                write!(f, "A passphrase is required to use this algorithm")
+            }
+            CryptoError::InvalidPassphraseParams => {
+                write!(f, "Passphrase derivation parameters are invalid")
+            }
+            CryptoError::TimeLockNotElapsed { ref unlock_at } => {
+                write!(f, "Entry is time-locked until {}", unlock_at)
+            }
+            CryptoError::WeakKeyMaterial => {
+                write!(f, "Provided key material is too weak to be used as a key")
+            }
+            CryptoError::PayloadTooLarge { size, max } => {
+                write!(
+                    f,
+                    "Payload of {} bytes exceeds the maximum of {} bytes",
+                    size, max
+                )
+            }
+            CryptoError::KeyStillInUse {
+                ref referencing_path,
+            } => {
+                write!(
+                    f,
+                    "Key is still in use by the entry at {}",
+                    referencing_path
+                )
+            }
+            CryptoError::NumericOverflow { target_type } => {
+                write!(f, "Value is out of the range of {}", target_type)
+            }
+            CryptoError::InvalidPageSize { requested, max } => {
+                write!(
+                    f,
+                    "Page size {} is not between 1 and the maximum of {}",
+                    requested, max
+                )
+            }
+            CryptoError::InvalidMaxPageSize { max } => {
+                write!(f, "Maximum page size {} is not positive", max)
+            }
+            CryptoError::EmbeddedSecretKey => {
+                write!(
+                    f,
+                    "Algorithm embeds an unsealed secret key and must reference it instead"
+                )
+            }
+            CryptoError::InvalidPathPrefix { ref prefix } => {
+                write!(f, "Path prefix \"{}\" would match every entry", prefix)
+            }
+            CryptoError::DecompressedTooLarge { max } => {
+                write!(f, "Plaintext decompresses to more than {} bytes", max)
+            }
+            CryptoError::InvalidMetadataKey { ref key } => {
+                write!(
+                    f,
+                    "Metadata key \"{}\" is empty, contains '.' or starts with '$'",
+                    key
+                )
+            }
         }
     }
 }
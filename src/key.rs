@@ -1,7 +1,26 @@
+pub mod aesgcmsiv;
+pub mod backup;
+pub mod deletion;
+pub mod integrity;
+pub mod kw;
+pub mod passphrase;
+pub mod pem;
+pub mod pool;
+pub mod recipient;
 pub mod ring;
 pub mod sodiumoxide;
+pub mod verification;
+
+pub use self::deletion::safe_delete_key;
+pub use self::integrity::{audit_keys, verify_keypair};
+pub use self::kw::{aes_kw_unwrap, aes_kw_wrap};
+pub use self::pem::{from_pem, to_pem};
+pub use self::pool::SignerPool;
+pub use self::recipient::seal_to_path;
+pub use self::verification::{verify_entries, VerificationReport};
 
 use self::{
+    aesgcmsiv::{Aes256GcmSivSymmetricKey, Aes256GcmSivSymmetricKeyBuilder},
     ring::{
         RingEd25519PublicAsymmetricKey, RingEd25519PublicAsymmetricKeyBuilder,
         RingEd25519SecretAsymmetricKey, RingEd25519SecretAsymmetricKeyBuilder,
@@ -9,21 +28,35 @@ use self::{
     sodiumoxide::{
         SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideCurve25519PublicAsymmetricKeyBuilder,
         SodiumOxideCurve25519SecretAsymmetricKey, SodiumOxideCurve25519SecretAsymmetricKeyBuilder,
+        SodiumOxideCurve25519XChaCha20PublicAsymmetricKey,
+        SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder,
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKey,
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder,
         SodiumOxideEd25519PublicAsymmetricKey, SodiumOxideEd25519PublicAsymmetricKeyBuilder,
         SodiumOxideEd25519SecretAsymmetricKey, SodiumOxideEd25519SecretAsymmetricKeyBuilder,
         SodiumOxideSymmetricKey, SodiumOxideSymmetricKeyBuilder,
     },
 };
 use crate::{
-    Builder, ByteAlgorithm, ByteSource, CryptoError, Entry, HasBuilder, HasByteSource, HasIndex,
-    StorableType, SymmetricNonce, TypeBuilder, TypeBuilderContainer,
+    nonce::{aesgcmsiv::Aes256GcmSivSymmetricNonce, sodiumoxide::SodiumOxideSymmetricNonce},
+    storage::segment_prefix,
+    AsymmetricNonce, Builder, ByteAlgorithm, ByteSource, CryptoError, Entry, EntryPath, HasBuilder,
+    HasByteSource, HasIndex, StorableType, SymmetricNonce, TypeBuilder, TypeBuilderContainer,
 };
 use async_trait::async_trait;
 use futures::Future;
 use mongodb::bson::{self, Document};
 use serde::{Deserialize, Serialize};
-use spki::AlgorithmIdentifier;
-use std::convert::TryFrom;
+use spki::{AlgorithmIdentifier, ObjectIdentifier};
+use std::{
+    convert::TryFrom,
+    fmt::{self, Display, Formatter},
+    mem,
+};
+use subtle::ConstantTimeEq;
+
+pub(crate) const ED25519_OID: ObjectIdentifier = ObjectIdentifier::new("1.3.101.112");
+pub(crate) const X25519_OID: ObjectIdentifier = ObjectIdentifier::new("1.3.101.110");
 
 pub trait Signer {
     fn sign(&self, bytes: ByteSource) -> Result<ByteSource, CryptoError>;
@@ -33,6 +66,18 @@ pub trait Verifier {
     fn verify(&self, msg: ByteSource, signature: ByteSource) -> Result<(), CryptoError>;
 }
 
+/// Stands in for secret key material in `Debug` output, showing only its length
+pub(crate) struct RedactedBytes(pub(crate) Option<usize>);
+
+impl fmt::Debug for RedactedBytes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(len) => write!(f, "<redacted {} bytes>", len),
+            None => write!(f, "<redacted>"),
+        }
+    }
+}
+
 #[async_trait]
 pub trait ToSymmetricByteAlgorithm {
     type Key: StorableType;
@@ -46,6 +91,24 @@ pub trait ToSymmetricByteAlgorithm {
     where
         F: FnOnce(Self::Key) -> Fut + Send,
         Fut: Future<Output = Result<Entry<Self::Key>, CryptoError>> + Send;
+
+    /// Same as `to_byte_algorithm`, but leaves the caller with ownership of the key by
+    /// rebuilding a copy of it from its byte source, which works for keys that are not
+    /// `Clone`
+    async fn to_byte_algorithm_ref<F, Fut>(
+        &self,
+        nonce: Option<Self::Nonce>,
+        f: F,
+    ) -> Result<ByteAlgorithm, CryptoError>
+    where
+        Self: StorableType + Sync,
+        Self::Nonce: Send,
+        F: FnOnce(Self::Key) -> Fut + Send,
+        Fut: Future<Output = Result<Entry<Self::Key>, CryptoError>> + Send,
+    {
+        let key = self.builder().build(Some(self.byte_source().get()?))?;
+        key.to_byte_algorithm(nonce, f).await
+    }
 }
 
 pub trait SymmetricSealer {
@@ -70,6 +133,45 @@ pub trait SymmetricUnsealer {
     ) -> Result<Self::UnsealedOutput, CryptoError>;
 }
 
+/// Seals with a commitment to the key prepended to the ciphertext, so that a
+/// ciphertext can only be unsealed by the key it was sealed under. Regular AEAD
+/// ciphertexts can in principle be crafted to decrypt under more than one key.
+pub trait CommittingSymmetricSealer: SymmetricSealer {
+    fn seal_committing(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<(Self::SealedOutput, Self::Nonce), CryptoError>;
+}
+
+/// Unseals ciphertexts produced by `CommittingSymmetricSealer`, verifying the key
+/// commitment before the AEAD tag.
+pub trait CommittingSymmetricUnsealer: SymmetricUnsealer {
+    fn unseal_committing(
+        &self,
+        ciphertext: &ByteSource,
+        nonce: &Self::Nonce,
+    ) -> Result<Self::UnsealedOutput, CryptoError>;
+}
+
+/// Seals into a self-contained frame that carries a version byte, an algorithm byte and
+/// the nonce ahead of the ciphertext, so that the frame alone is enough to unseal it
+/// given the key.
+pub trait FramedSymmetricSealer: SymmetricSealer {
+    fn seal_framed(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<Self::SealedOutput, CryptoError>;
+}
+
+/// Unseals frames produced by `FramedSymmetricSealer`, failing with
+/// `CryptoError::InvalidFrame` if the frame is truncated or was not produced by this
+/// algorithm.
+pub trait FramedSymmetricUnsealer: SymmetricUnsealer {
+    fn unseal_framed(&self, frame: &ByteSource) -> Result<Self::UnsealedOutput, CryptoError>;
+}
+
 #[async_trait]
 pub trait ToSecretAsymmetricByteAlgorithm {
     type SecretKey: StorableType;
@@ -166,6 +268,93 @@ pub trait HasAlgorithmIdentifier {
     fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a>;
 }
 
+/// Trait indicating a type can produce a stable fingerprint of its key material
+pub trait HasFingerprint {
+    fn fingerprint(&self) -> Result<String, CryptoError>;
+}
+
+/// Trait indicating a key can be deterministically regenerated from a 32-byte seed
+pub trait FromSeed: Sized {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError>;
+}
+
+/// Computes the hex-encoded SHA-256 fingerprint of the given key bytes
+pub fn fingerprint(bytes: &[u8]) -> String {
+    hex::encode(::ring::digest::digest(&::ring::digest::SHA256, bytes))
+}
+
+/// Verifies the signature against each of the given keys in order and returns the
+/// index of the first one it verifies under. Curve25519 keys cannot verify signatures
+/// and are skipped. Returns `CryptoError::BadSignature` if no key verifies it.
+pub fn verify_any(
+    msg: &ByteSource,
+    signature: &ByteSource,
+    keys: &[PublicAsymmetricKey],
+) -> Result<usize, CryptoError> {
+    keys.iter()
+        .position(|key| {
+            let result = match key {
+                PublicAsymmetricKey::SodiumOxideEd25519(k) => {
+                    k.verify(msg.clone(), signature.clone())
+                }
+                PublicAsymmetricKey::RingEd25519(k) => k.verify(msg.clone(), signature.clone()),
+                PublicAsymmetricKey::SodiumOxideCurve25519(_)
+                | PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(_) => {
+                    Err(CryptoError::UnsupportedKeyAlgorithm)
+                }
+            };
+            result.is_ok()
+        })
+        .ok_or(CryptoError::BadSignature)
+}
+
+/// Role a key plays in cryptographic operations, used to report a key given where a
+/// key of another role is required
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum KeyRole {
+    Symmetric,
+    SecretAsymmetric,
+    PublicAsymmetric,
+}
+
+impl Display for KeyRole {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            KeyRole::Symmetric => write!(f, "symmetric"),
+            KeyRole::SecretAsymmetric => write!(f, "secret asymmetric"),
+            KeyRole::PublicAsymmetric => write!(f, "public asymmetric"),
+        }
+    }
+}
+
+impl KeyRole {
+    /// Returns the role of keys built by the given builder, if it builds keys
+    pub fn of_builder(builder: &TypeBuilder) -> Option<KeyRole> {
+        match builder {
+            TypeBuilder::Key(KeyBuilder::Symmetric(_)) => Some(KeyRole::Symmetric),
+            TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Secret(_))) => {
+                Some(KeyRole::SecretAsymmetric)
+            }
+            TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(_))) => {
+                Some(KeyRole::PublicAsymmetric)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Casts an entry to a concrete key type, reporting a key of the wrong role as such
+/// rather than as a failed downcast
+fn cast_with_role<T: StorableType, U: StorableType>(
+    entry: Entry<T>,
+    expected: KeyRole,
+) -> Result<Entry<U>, CryptoError> {
+    match KeyRole::of_builder(&entry.builder) {
+        Some(actual) if actual != expected => Err(CryptoError::WrongKeyRole { expected, actual }),
+        _ => entry.cast::<U>(),
+    }
+}
+
 #[derive(Debug)]
 pub enum Key {
     Symmetric(SymmetricKey),
@@ -208,6 +397,63 @@ impl HasByteSource for Key {
     }
 }
 
+/// Asymmetric keys, public or secret, are fingerprinted by their public key. A symmetric
+/// key has no public part, and a digest of its bytes would identify the key material,
+/// so it cannot be fingerprinted.
+impl HasFingerprint for Key {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        match self {
+            Key::Asymmetric(AsymmetricKey::Public(pk)) => pk.fingerprint(),
+            Key::Asymmetric(AsymmetricKey::Secret(sk)) => sk.public_key()?.fingerprint(),
+            Key::Symmetric(_) => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+}
+
+impl Key {
+    /// Returns the conventional path for storing this key under the given namespace,
+    /// `{namespace}.{id}.`. Asymmetric keys, public or secret, are identified by the
+    /// fingerprint of their public key, so a key can always be found again from it.
+    /// A symmetric key has no public part, and any digest of its bytes would let anyone
+    /// reading the path check guesses of it, so it is given a random id instead. Ids are
+    /// hex-encoded, which keeps the path safe to use in filesystem paths and URLs.
+    pub fn default_path(&self, namespace: &str) -> Result<EntryPath, CryptoError> {
+        let id = match self {
+            Key::Asymmetric(_) => self.fingerprint()?,
+            Key::Symmetric(_) => uuid::Uuid::new_v4().to_simple().to_string(),
+        };
+        Ok(format!("{}{}.", segment_prefix(namespace), id))
+    }
+
+    /// Returns true if both keys are of the same type and hold the same key material.
+    /// Public keys are compared by their public component and secret keys by their
+    /// secret component, so a public key never equals a secret key. Key material is
+    /// compared in constant time.
+    pub fn same_key(&self, other: &Key) -> bool {
+        let same_type = match (self, other) {
+            (Key::Symmetric(a), Key::Symmetric(b)) => mem::discriminant(a) == mem::discriminant(b),
+            (
+                Key::Asymmetric(AsymmetricKey::Public(a)),
+                Key::Asymmetric(AsymmetricKey::Public(b)),
+            ) => mem::discriminant(a) == mem::discriminant(b),
+            (
+                Key::Asymmetric(AsymmetricKey::Secret(a)),
+                Key::Asymmetric(AsymmetricKey::Secret(b)),
+            ) => mem::discriminant(a) == mem::discriminant(b),
+            _ => false,
+        };
+        if !same_type {
+            return false;
+        }
+
+        let (bytes, other_bytes) = (self.byte_source(), other.byte_source());
+        match (bytes.get(), other_bytes.get()) {
+            (Ok(bytes), Ok(other_bytes)) => bytes.ct_eq(other_bytes).into(),
+            _ => false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 #[serde(tag = "t", content = "c")]
 pub enum KeyBuilder {
@@ -232,6 +478,28 @@ impl From<KeyBuilder> for TypeBuilder {
     }
 }
 
+impl KeyBuilder {
+    /// Returns the OID of the algorithm of the keys this builder builds, as given by
+    /// their `HasAlgorithmIdentifier` implementations. Symmetric keys have none.
+    pub fn algorithm_oid(&self) -> Option<ObjectIdentifier> {
+        match self {
+            KeyBuilder::Symmetric(_) => None,
+            KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(pakb)) => Some(match pakb {
+                PublicAsymmetricKeyBuilder::SodiumOxideCurve25519(_)
+                | PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(_) => X25519_OID,
+                PublicAsymmetricKeyBuilder::SodiumOxideEd25519(_)
+                | PublicAsymmetricKeyBuilder::RingEd25519(_) => ED25519_OID,
+            }),
+            KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Secret(sakb)) => Some(match sakb {
+                SecretAsymmetricKeyBuilder::SodiumOxideCurve25519(_)
+                | SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(_) => X25519_OID,
+                SecretAsymmetricKeyBuilder::SodiumOxideEd25519(_)
+                | SecretAsymmetricKeyBuilder::RingEd25519(_) => ED25519_OID,
+            }),
+        }
+    }
+}
+
 impl Builder for KeyBuilder {
     type Output = Key;
 
@@ -246,6 +514,82 @@ impl Builder for KeyBuilder {
 #[derive(Debug)]
 pub enum SymmetricKey {
     SodiumOxide(SodiumOxideSymmetricKey),
+    Aes256GcmSiv(Aes256GcmSivSymmetricKey),
+}
+
+impl SymmetricKey {
+    /// Seals the plaintext with a commitment to this key, see `CommittingSymmetricSealer`
+    pub fn seal_committing(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&SymmetricNonce>,
+    ) -> Result<(ByteSource, SymmetricNonce), CryptoError> {
+        match self {
+            SymmetricKey::SodiumOxide(sosk) => {
+                let nonce = nonce.map(sodiumoxide_nonce).transpose()?;
+                let (ciphertext, nonce) = sosk.seal_committing(plaintext, nonce)?;
+                Ok((ciphertext, SymmetricNonce::SodiumOxide(nonce)))
+            }
+            SymmetricKey::Aes256GcmSiv(_) => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+
+    /// Unseals a ciphertext produced by `seal_committing`, failing if it was not sealed
+    /// under this key
+    pub fn unseal_committing(
+        &self,
+        ciphertext: &ByteSource,
+        nonce: &SymmetricNonce,
+    ) -> Result<ByteSource, CryptoError> {
+        match (self, nonce) {
+            (SymmetricKey::SodiumOxide(sosk), SymmetricNonce::SodiumOxide(sosn)) => {
+                sosk.unseal_committing(ciphertext, sosn)
+            }
+            (SymmetricKey::SodiumOxide(_), _) => Err(CryptoError::WrongNonceType),
+            (SymmetricKey::Aes256GcmSiv(_), _) => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+
+    /// Seals the plaintext into a self-contained frame, see `FramedSymmetricSealer`
+    pub fn seal_framed(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&SymmetricNonce>,
+    ) -> Result<ByteSource, CryptoError> {
+        match self {
+            SymmetricKey::SodiumOxide(sosk) => {
+                let nonce = nonce.map(sodiumoxide_nonce).transpose()?;
+                sosk.seal_framed(plaintext, nonce)
+            }
+            SymmetricKey::Aes256GcmSiv(_) => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+
+    /// Unseals a frame produced by `seal_framed`
+    pub fn unseal_framed(&self, frame: &ByteSource) -> Result<ByteSource, CryptoError> {
+        match self {
+            SymmetricKey::SodiumOxide(sosk) => sosk.unseal_framed(frame),
+            SymmetricKey::Aes256GcmSiv(_) => Err(CryptoError::UnsupportedKeyAlgorithm),
+        }
+    }
+}
+
+/// Narrows a symmetric nonce to the sodiumoxide nonce, failing if it belongs to another
+/// algorithm
+fn sodiumoxide_nonce(nonce: &SymmetricNonce) -> Result<&SodiumOxideSymmetricNonce, CryptoError> {
+    match nonce {
+        SymmetricNonce::SodiumOxide(sosn) => Ok(sosn),
+        _ => Err(CryptoError::WrongNonceType),
+    }
+}
+
+/// Narrows a symmetric nonce to the AES-256-GCM-SIV nonce, failing if it belongs to
+/// another algorithm
+fn aes256gcmsiv_nonce(nonce: &SymmetricNonce) -> Result<&Aes256GcmSivSymmetricNonce, CryptoError> {
+    match nonce {
+        SymmetricNonce::Aes256GcmSiv(agsn) => Ok(agsn),
+        _ => Err(CryptoError::WrongNonceType),
+    }
 }
 
 #[async_trait]
@@ -264,9 +608,7 @@ impl ToSymmetricByteAlgorithm for SymmetricKey {
     {
         match self {
             SymmetricKey::SodiumOxide(sosk) => {
-                let nonce = nonce.map(|n| match n {
-                    SymmetricNonce::SodiumOxide(sosn) => sosn,
-                });
+                let nonce = nonce.as_ref().map(sodiumoxide_nonce).transpose()?.cloned();
                 sosk.to_byte_algorithm(nonce, |key| async move {
                     f(SymmetricKey::SodiumOxide(key))
                         .await?
@@ -274,6 +616,15 @@ impl ToSymmetricByteAlgorithm for SymmetricKey {
                 })
                 .await
             }
+            SymmetricKey::Aes256GcmSiv(agsk) => {
+                let nonce = nonce.as_ref().map(aes256gcmsiv_nonce).transpose()?.cloned();
+                agsk.to_byte_algorithm(nonce, |key| async move {
+                    f(SymmetricKey::Aes256GcmSiv(key))
+                        .await?
+                        .cast::<Aes256GcmSivSymmetricKey>()
+                })
+                .await
+            }
         }
     }
 }
@@ -291,12 +642,15 @@ impl SymmetricSealer for SymmetricKey {
     ) -> Result<(Self::SealedOutput, Self::Nonce), CryptoError> {
         match self {
             Self::SodiumOxide(sosk) => {
-                let nonce = nonce.map(|n| match n {
-                    SymmetricNonce::SodiumOxide(sosn) => sosn,
-                });
+                let nonce = nonce.map(sodiumoxide_nonce).transpose()?;
                 let (output, nonce) = sosk.seal(plaintext, nonce)?;
                 Ok((output, SymmetricNonce::SodiumOxide(nonce)))
             }
+            Self::Aes256GcmSiv(agsk) => {
+                let nonce = nonce.map(aes256gcmsiv_nonce).transpose()?;
+                let (output, nonce) = agsk.seal(plaintext, nonce)?;
+                Ok((output, SymmetricNonce::Aes256GcmSiv(nonce)))
+            }
         }
     }
 }
@@ -324,6 +678,7 @@ impl HasBuilder for SymmetricKey {
     fn builder(&self) -> Self::Builder {
         match self {
             Self::SodiumOxide(sosk) => SymmetricKeyBuilder::SodiumOxide(sosk.builder()),
+            Self::Aes256GcmSiv(agsk) => SymmetricKeyBuilder::Aes256GcmSiv(agsk.builder()),
         }
     }
 }
@@ -332,6 +687,7 @@ impl HasByteSource for SymmetricKey {
     fn byte_source(&self) -> ByteSource {
         match self {
             Self::SodiumOxide(sosk) => sosk.byte_source(),
+            Self::Aes256GcmSiv(agsk) => agsk.byte_source(),
         }
     }
 }
@@ -340,6 +696,7 @@ impl HasByteSource for SymmetricKey {
 #[serde(tag = "t", content = "c")]
 pub enum SymmetricKeyBuilder {
     SodiumOxide(SodiumOxideSymmetricKeyBuilder),
+    Aes256GcmSiv(Aes256GcmSivSymmetricKeyBuilder),
 }
 
 impl TryFrom<TypeBuilderContainer> for SymmetricKeyBuilder {
@@ -365,6 +722,7 @@ impl Builder for SymmetricKeyBuilder {
     fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
         match self {
             Self::SodiumOxide(soskb) => Ok(SymmetricKey::SodiumOxide(soskb.build(bytes)?)),
+            Self::Aes256GcmSiv(agskb) => Ok(SymmetricKey::Aes256GcmSiv(agskb.build(bytes)?)),
         }
     }
 }
@@ -414,6 +772,97 @@ impl HasByteSource for AsymmetricKey {
     }
 }
 
+#[async_trait]
+impl ToSecretAsymmetricByteAlgorithm for AsymmetricKey {
+    type SecretKey = AsymmetricKey;
+    type Nonce = AsymmetricNonce;
+    type PublicKey = AsymmetricKey;
+
+    async fn to_byte_algorithm<F, Fut>(
+        self,
+        public_key: Option<Entry<Self::PublicKey>>,
+        nonce: Option<Self::Nonce>,
+        f: F,
+    ) -> Result<ByteAlgorithm, CryptoError>
+    where
+        F: FnOnce(Self::SecretKey) -> Fut + Send,
+        Fut: Future<Output = Result<Entry<Self::SecretKey>, CryptoError>> + Send,
+    {
+        let secret_key = match self {
+            AsymmetricKey::Secret(SecretAsymmetricKey::SodiumOxideCurve25519(sosak)) => sosak,
+            AsymmetricKey::Secret(_) => return Err(CryptoError::UnsupportedKeyAlgorithm),
+            AsymmetricKey::Public(_) => {
+                return Err(CryptoError::WrongKeyRole {
+                    expected: KeyRole::SecretAsymmetric,
+                    actual: KeyRole::PublicAsymmetric,
+                })
+            }
+        };
+        let public_key = match public_key {
+            Some(pk) => Some(cast_with_role(pk, KeyRole::PublicAsymmetric)?),
+            None => None,
+        };
+        let nonce = match nonce {
+            Some(AsymmetricNonce::SodiumOxide(soan)) => Some(soan),
+            Some(_) => return Err(CryptoError::WrongNonceType),
+            None => None,
+        };
+        secret_key
+            .to_byte_algorithm(public_key, nonce, |key| async move {
+                f(AsymmetricKey::Secret(
+                    SecretAsymmetricKey::SodiumOxideCurve25519(key),
+                ))
+                .await?
+                .cast::<SodiumOxideCurve25519SecretAsymmetricKey>()
+            })
+            .await
+    }
+}
+
+#[async_trait]
+impl ToPublicAsymmetricByteAlgorithm for AsymmetricKey {
+    type SecretKey = AsymmetricKey;
+    type Nonce = AsymmetricNonce;
+    type PublicKey = AsymmetricKey;
+
+    async fn to_byte_algorithm<F, Fut>(
+        self,
+        secret_key: Entry<Self::SecretKey>,
+        nonce: Option<Self::Nonce>,
+        f: F,
+    ) -> Result<ByteAlgorithm, CryptoError>
+    where
+        F: FnOnce(Self::PublicKey) -> Fut + Send,
+        Fut: Future<Output = Result<Entry<Self::PublicKey>, CryptoError>> + Send,
+    {
+        let public_key = match self {
+            AsymmetricKey::Public(PublicAsymmetricKey::SodiumOxideCurve25519(sopak)) => sopak,
+            AsymmetricKey::Public(_) => return Err(CryptoError::UnsupportedKeyAlgorithm),
+            AsymmetricKey::Secret(_) => {
+                return Err(CryptoError::WrongKeyRole {
+                    expected: KeyRole::PublicAsymmetric,
+                    actual: KeyRole::SecretAsymmetric,
+                })
+            }
+        };
+        let secret_key = cast_with_role(secret_key, KeyRole::SecretAsymmetric)?;
+        let nonce = match nonce {
+            Some(AsymmetricNonce::SodiumOxide(soan)) => Some(soan),
+            Some(_) => return Err(CryptoError::WrongNonceType),
+            None => None,
+        };
+        public_key
+            .to_byte_algorithm(secret_key, nonce, |key| async move {
+                f(AsymmetricKey::Public(
+                    PublicAsymmetricKey::SodiumOxideCurve25519(key),
+                ))
+                .await?
+                .cast::<SodiumOxideCurve25519PublicAsymmetricKey>()
+            })
+            .await
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
 #[serde(tag = "t", content = "c")]
 pub enum AsymmetricKeyBuilder {
@@ -452,6 +901,7 @@ impl Builder for AsymmetricKeyBuilder {
 #[derive(Debug)]
 pub enum PublicAsymmetricKey {
     SodiumOxideCurve25519(SodiumOxideCurve25519PublicAsymmetricKey),
+    SodiumOxideCurve25519XChaCha20(SodiumOxideCurve25519XChaCha20PublicAsymmetricKey),
     SodiumOxideEd25519(SodiumOxideEd25519PublicAsymmetricKey),
     RingEd25519(RingEd25519PublicAsymmetricKey),
 }
@@ -486,6 +936,9 @@ impl HasBuilder for PublicAsymmetricKey {
             PublicAsymmetricKey::SodiumOxideCurve25519(sopak) => {
                 PublicAsymmetricKeyBuilder::SodiumOxideCurve25519(sopak.builder())
             }
+            PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(sopak) => {
+                PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sopak.builder())
+            }
             PublicAsymmetricKey::SodiumOxideEd25519(sopak) => {
                 PublicAsymmetricKeyBuilder::SodiumOxideEd25519(sopak.builder())
             }
@@ -500,16 +953,29 @@ impl HasByteSource for PublicAsymmetricKey {
     fn byte_source(&self) -> ByteSource {
         match self {
             PublicAsymmetricKey::SodiumOxideCurve25519(sopak) => sopak.byte_source(),
+            PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(sopak) => sopak.byte_source(),
             PublicAsymmetricKey::SodiumOxideEd25519(sopak) => sopak.byte_source(),
             PublicAsymmetricKey::RingEd25519(rpak) => rpak.byte_source(),
         }
     }
 }
 
+impl HasFingerprint for PublicAsymmetricKey {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        match self {
+            PublicAsymmetricKey::SodiumOxideCurve25519(k) => k.fingerprint(),
+            PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(k) => k.fingerprint(),
+            PublicAsymmetricKey::SodiumOxideEd25519(k) => k.fingerprint(),
+            PublicAsymmetricKey::RingEd25519(k) => k.fingerprint(),
+        }
+    }
+}
+
 impl HasAlgorithmIdentifier for PublicAsymmetricKey {
     fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
         match self {
             PublicAsymmetricKey::SodiumOxideCurve25519(k) => k.algorithm_identifier(),
+            PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(k) => k.algorithm_identifier(),
             PublicAsymmetricKey::SodiumOxideEd25519(k) => k.algorithm_identifier(),
             PublicAsymmetricKey::RingEd25519(k) => k.algorithm_identifier(),
         }
@@ -520,6 +986,7 @@ impl HasAlgorithmIdentifier for PublicAsymmetricKey {
 #[serde(tag = "t", content = "c")]
 pub enum PublicAsymmetricKeyBuilder {
     SodiumOxideCurve25519(SodiumOxideCurve25519PublicAsymmetricKeyBuilder),
+    SodiumOxideCurve25519XChaCha20(SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder),
     SodiumOxideEd25519(SodiumOxideEd25519PublicAsymmetricKeyBuilder),
     RingEd25519(RingEd25519PublicAsymmetricKeyBuilder),
 }
@@ -551,6 +1018,9 @@ impl Builder for PublicAsymmetricKeyBuilder {
             PublicAsymmetricKeyBuilder::SodiumOxideCurve25519(sopakb) => Ok(
                 PublicAsymmetricKey::SodiumOxideCurve25519(sopakb.build(bytes)?),
             ),
+            PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sopakb) => Ok(
+                PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(sopakb.build(bytes)?),
+            ),
             PublicAsymmetricKeyBuilder::SodiumOxideEd25519(sopakb) => Ok(
                 PublicAsymmetricKey::SodiumOxideEd25519(sopakb.build(bytes)?),
             ),
@@ -564,6 +1034,7 @@ impl Builder for PublicAsymmetricKeyBuilder {
 #[derive(Debug)]
 pub enum SecretAsymmetricKey {
     SodiumOxideCurve25519(SodiumOxideCurve25519SecretAsymmetricKey),
+    SodiumOxideCurve25519XChaCha20(SodiumOxideCurve25519XChaCha20SecretAsymmetricKey),
     SodiumOxideEd25519(SodiumOxideEd25519SecretAsymmetricKey),
     RingEd25519(RingEd25519SecretAsymmetricKey),
 }
@@ -598,6 +1069,9 @@ impl HasBuilder for SecretAsymmetricKey {
             SecretAsymmetricKey::SodiumOxideCurve25519(sosak) => {
                 SecretAsymmetricKeyBuilder::SodiumOxideCurve25519(sosak.builder())
             }
+            SecretAsymmetricKey::SodiumOxideCurve25519XChaCha20(sosak) => {
+                SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sosak.builder())
+            }
             SecretAsymmetricKey::SodiumOxideEd25519(sosak) => {
                 SecretAsymmetricKeyBuilder::SodiumOxideEd25519(sosak.builder())
             }
@@ -612,6 +1086,7 @@ impl HasByteSource for SecretAsymmetricKey {
     fn byte_source(&self) -> ByteSource {
         match self {
             SecretAsymmetricKey::SodiumOxideCurve25519(sosak) => sosak.byte_source(),
+            SecretAsymmetricKey::SodiumOxideCurve25519XChaCha20(sosak) => sosak.byte_source(),
             SecretAsymmetricKey::SodiumOxideEd25519(sosak) => sosak.byte_source(),
             SecretAsymmetricKey::RingEd25519(rsak) => rsak.byte_source(),
         }
@@ -647,6 +1122,7 @@ impl StorableType for SigningKey {}
 pub enum EncryptingKey {
     SodiumOxideCurve25519(SodiumOxideCurve25519SecretAsymmetricKey),
     SodiumOxideSymmetricKey(SodiumOxideSymmetricKey),
+    Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKey),
 }
 
 impl StorableType for EncryptingKey {}
@@ -661,6 +1137,7 @@ pub enum SigningKeyBuilder {
 pub enum EncryptingKeyBuilder {
     SodiumOxideCurve25519(SodiumOxideCurve25519SecretAsymmetricKeyBuilder),
     SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyBuilder),
+    Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKeyBuilder),
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -747,6 +1224,9 @@ impl HasBuilder for EncryptingKey {
             EncryptingKey::SodiumOxideSymmetricKey(ssk) => {
                 EncryptingKeyBuilder::SodiumOxideSymmetricKey(ssk.builder())
             }
+            EncryptingKey::Aes256GcmSivSymmetricKey(agsk) => {
+                EncryptingKeyBuilder::Aes256GcmSivSymmetricKey(agsk.builder())
+            }
         }
     }
 }
@@ -791,6 +1271,9 @@ impl TryFrom<TypeBuilderContainer> for EncryptingKeyBuilder {
             TypeBuilder::Key(KeyBuilder::Symmetric(SymmetricKeyBuilder::SodiumOxide(ssk))) => {
                 Ok(EncryptingKeyBuilder::SodiumOxideSymmetricKey(ssk))
             }
+            TypeBuilder::Key(KeyBuilder::Symmetric(SymmetricKeyBuilder::Aes256GcmSiv(agsk))) => {
+                Ok(EncryptingKeyBuilder::Aes256GcmSivSymmetricKey(agsk))
+            }
             _ => Err(CryptoError::NotDowncastable),
         }
     }
@@ -826,6 +1309,7 @@ impl From<EncryptingKeyBuilder> for TypeBuilder {
         match ekb {
             EncryptingKeyBuilder::SodiumOxideCurve25519(b) => b.into(),
             EncryptingKeyBuilder::SodiumOxideSymmetricKey(b) => b.into(),
+            EncryptingKeyBuilder::Aes256GcmSivSymmetricKey(b) => b.into(),
         }
     }
 }
@@ -861,6 +1345,9 @@ impl Builder for EncryptingKeyBuilder {
             Self::SodiumOxideSymmetricKey(sk) => {
                 Ok(EncryptingKey::SodiumOxideSymmetricKey(sk.build(bytes)?))
             }
+            Self::Aes256GcmSivSymmetricKey(sk) => {
+                Ok(EncryptingKey::Aes256GcmSivSymmetricKey(sk.build(bytes)?))
+            }
         }
     }
 }
@@ -925,11 +1412,47 @@ impl HasByteSource for EncryptingKey {
     fn byte_source(&self) -> ByteSource {
         match self {
             EncryptingKey::SodiumOxideSymmetricKey(k) => k.byte_source(),
+            EncryptingKey::Aes256GcmSivSymmetricKey(k) => k.byte_source(),
             EncryptingKey::SodiumOxideCurve25519(k) => k.byte_source(),
         }
     }
 }
 
+impl From<EncryptingKey> for Key {
+    fn from(ek: EncryptingKey) -> Self {
+        match ek {
+            EncryptingKey::SodiumOxideCurve25519(k) => Key::Asymmetric(AsymmetricKey::Secret(
+                SecretAsymmetricKey::SodiumOxideCurve25519(k),
+            )),
+            EncryptingKey::SodiumOxideSymmetricKey(k) => {
+                Key::Symmetric(SymmetricKey::SodiumOxide(k))
+            }
+            EncryptingKey::Aes256GcmSivSymmetricKey(k) => {
+                Key::Symmetric(SymmetricKey::Aes256GcmSiv(k))
+            }
+        }
+    }
+}
+
+impl TryFrom<Key> for EncryptingKey {
+    type Error = CryptoError;
+
+    fn try_from(key: Key) -> Result<Self, Self::Error> {
+        match key {
+            Key::Asymmetric(AsymmetricKey::Secret(SecretAsymmetricKey::SodiumOxideCurve25519(
+                k,
+            ))) => Ok(EncryptingKey::SodiumOxideCurve25519(k)),
+            Key::Symmetric(SymmetricKey::SodiumOxide(k)) => {
+                Ok(EncryptingKey::SodiumOxideSymmetricKey(k))
+            }
+            Key::Symmetric(SymmetricKey::Aes256GcmSiv(k)) => {
+                Ok(EncryptingKey::Aes256GcmSivSymmetricKey(k))
+            }
+            _ => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
 impl HasByteSource for VerifyingKey {
     fn byte_source(&self) -> ByteSource {
         match self {
@@ -939,6 +1462,27 @@ impl HasByteSource for VerifyingKey {
     }
 }
 
+impl HasPublicKey for SecretAsymmetricKey {
+    type PublicKey = PublicAsymmetricKey;
+
+    fn public_key(&self) -> Result<Self::PublicKey, CryptoError> {
+        match self {
+            SecretAsymmetricKey::SodiumOxideCurve25519(k) => {
+                Ok(PublicAsymmetricKey::SodiumOxideCurve25519(k.public_key()?))
+            }
+            SecretAsymmetricKey::SodiumOxideCurve25519XChaCha20(k) => Ok(
+                PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(k.public_key()?),
+            ),
+            SecretAsymmetricKey::SodiumOxideEd25519(k) => {
+                Ok(PublicAsymmetricKey::SodiumOxideEd25519(k.public_key()?))
+            }
+            SecretAsymmetricKey::RingEd25519(k) => {
+                Ok(PublicAsymmetricKey::RingEd25519(k.public_key()?))
+            }
+        }
+    }
+}
+
 impl HasPublicKey for SigningKey {
     type PublicKey = PublicAsymmetricKey;
 
@@ -956,6 +1500,7 @@ impl HasPublicKey for SigningKey {
 #[serde(tag = "t", content = "c")]
 pub enum SecretAsymmetricKeyBuilder {
     SodiumOxideCurve25519(SodiumOxideCurve25519SecretAsymmetricKeyBuilder),
+    SodiumOxideCurve25519XChaCha20(SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder),
     SodiumOxideEd25519(SodiumOxideEd25519SecretAsymmetricKeyBuilder),
     RingEd25519(RingEd25519SecretAsymmetricKeyBuilder),
 }
@@ -987,6 +1532,9 @@ impl Builder for SecretAsymmetricKeyBuilder {
             SecretAsymmetricKeyBuilder::SodiumOxideCurve25519(sosakb) => Ok(
                 SecretAsymmetricKey::SodiumOxideCurve25519(sosakb.build(bytes)?),
             ),
+            SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sosakb) => Ok(
+                SecretAsymmetricKey::SodiumOxideCurve25519XChaCha20(sosakb.build(bytes)?),
+            ),
             SecretAsymmetricKeyBuilder::SodiumOxideEd25519(sosakb) => Ok(
                 SecretAsymmetricKey::SodiumOxideEd25519(sosakb.build(bytes)?),
             ),
@@ -996,3 +1544,272 @@ impl Builder for SecretAsymmetricKeyBuilder {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        aesgcmsiv::Aes256GcmSivSymmetricKey,
+        ring::RingEd25519SecretAsymmetricKey,
+        sodiumoxide::{
+            SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideCurve25519SecretAsymmetricKey,
+            SodiumOxideEd25519SecretAsymmetricKey,
+        },
+        verify_any, AsymmetricKey, EncryptingKey, HasFingerprint, HasPublicKey, KeyRole,
+        PublicAsymmetricKey, SecretAsymmetricKey, Signer,
+    };
+    use crate::{
+        key::sodiumoxide::{SodiumOxideSymmetricKey, SodiumOxideSymmetricKeyBuilder},
+        Algorithm, Builder, ByteSource, CryptoError, HasByteSource, Key, Path, SymmetricKey,
+        ToEntry,
+    };
+    use std::{convert::TryFrom, str::FromStr};
+
+    #[test]
+    fn test_encryptingkey_key_roundtrip() {
+        let keys = vec![
+            EncryptingKey::SodiumOxideCurve25519(SodiumOxideCurve25519SecretAsymmetricKey::new()),
+            EncryptingKey::SodiumOxideSymmetricKey(SodiumOxideSymmetricKey::new()),
+            EncryptingKey::Aes256GcmSivSymmetricKey(Aes256GcmSivSymmetricKey::new()),
+        ];
+        for encrypting_key in keys {
+            let bytes = encrypting_key.byte_source().get().unwrap().to_vec();
+            let key: Key = encrypting_key.into();
+            assert_eq!(key.byte_source().get().unwrap(), bytes.as_slice());
+            match key {
+                Key::Asymmetric(AsymmetricKey::Secret(_)) | Key::Symmetric(_) => (),
+                _ => panic!("Encrypting key should have converted to a secret key"),
+            }
+            let encrypting_key = EncryptingKey::try_from(key).unwrap();
+            assert_eq!(
+                encrypting_key.byte_source().get().unwrap(),
+                bytes.as_slice()
+            );
+        }
+
+        let (public_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let key = Key::Asymmetric(AsymmetricKey::Public(
+            PublicAsymmetricKey::SodiumOxideCurve25519(public_key),
+        ));
+        match EncryptingKey::try_from(key) {
+            Err(CryptoError::NotDowncastable) => (),
+            _ => panic!("Public key should not have converted to an encrypting key"),
+        }
+    }
+
+    #[test]
+    fn test_verify_any_returns_matching_index() {
+        let (curve_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let signer = RingEd25519SecretAsymmetricKey::new().unwrap();
+        let other = SodiumOxideEd25519SecretAsymmetricKey::new();
+        let keys = vec![
+            PublicAsymmetricKey::SodiumOxideEd25519(other.public_key().unwrap()),
+            PublicAsymmetricKey::RingEd25519(signer.public_key().unwrap()),
+            PublicAsymmetricKey::SodiumOxideCurve25519(curve_key),
+        ];
+
+        let msg: ByteSource = "hello, world!".into();
+        let signature = signer.sign(msg.clone()).unwrap();
+        assert_eq!(verify_any(&msg, &signature, &keys).unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_secret_asymmetric_algorithm_from_public_key_wrong_role() {
+        let (public_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let entry = AsymmetricKey::Public(PublicAsymmetricKey::SodiumOxideCurve25519(public_key))
+            .to_unsealed_entry(".keys.public.".to_owned())
+            .unwrap();
+        match entry.to_secret_asymmetric_byte_algorithm(None, None).await {
+            Err(CryptoError::WrongKeyRole { expected, actual }) => {
+                assert_eq!(expected, KeyRole::SecretAsymmetric);
+                assert_eq!(actual, KeyRole::PublicAsymmetric);
+            }
+            _ => panic!("Building a secret asymmetric algorithm from a public key should fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secret_asymmetric_algorithm_with_secret_key_as_public_key_wrong_role() {
+        let secret_key = AsymmetricKey::Secret(SecretAsymmetricKey::SodiumOxideCurve25519(
+            SodiumOxideCurve25519SecretAsymmetricKey::new(),
+        ));
+        let other_secret_key = AsymmetricKey::Secret(SecretAsymmetricKey::SodiumOxideCurve25519(
+            SodiumOxideCurve25519SecretAsymmetricKey::new(),
+        ))
+        .to_unsealed_entry(".keys.other.".to_owned())
+        .unwrap();
+        let entry = secret_key
+            .to_unsealed_entry(".keys.secret.".to_owned())
+            .unwrap();
+        match entry
+            .to_secret_asymmetric_byte_algorithm(Some(other_secret_key), None)
+            .await
+        {
+            Err(CryptoError::WrongKeyRole { expected, actual }) => {
+                assert_eq!(expected, KeyRole::PublicAsymmetric);
+                assert_eq!(actual, KeyRole::SecretAsymmetric);
+            }
+            _ => panic!("Passing a secret key as the public key should fail"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_secret_asymmetric_algorithm_from_asymmetric_key() {
+        let secret_key = SodiumOxideCurve25519SecretAsymmetricKey::new();
+        let public_key = AsymmetricKey::Public(PublicAsymmetricKey::SodiumOxideCurve25519(
+            secret_key.public_key().unwrap(),
+        ))
+        .to_unsealed_entry(".keys.public.".to_owned())
+        .unwrap();
+        let algorithm =
+            AsymmetricKey::Secret(SecretAsymmetricKey::SodiumOxideCurve25519(secret_key))
+                .to_unsealed_entry(".keys.secret.".to_owned())
+                .unwrap()
+                .to_secret_asymmetric_byte_algorithm(Some(public_key), None)
+                .await
+                .unwrap();
+
+        let plaintext: ByteSource = "hello, world!".into();
+        let ciphertext = algorithm.seal(&plaintext).await.unwrap();
+        assert_eq!(
+            algorithm.unseal(&ciphertext).await.unwrap().get().unwrap(),
+            plaintext.get().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_verify_any_no_match() {
+        let keys: Vec<PublicAsymmetricKey> = (0..3)
+            .map(|_| {
+                PublicAsymmetricKey::SodiumOxideEd25519(
+                    SodiumOxideEd25519SecretAsymmetricKey::new()
+                        .public_key()
+                        .unwrap(),
+                )
+            })
+            .collect();
+        let signer = SodiumOxideEd25519SecretAsymmetricKey::new();
+
+        let msg: ByteSource = "hello, world!".into();
+        let signature = signer.sign(msg.clone()).unwrap();
+        match verify_any(&msg, &signature, &keys) {
+            Err(CryptoError::BadSignature) => (),
+            _ => panic!("No key should have verified the signature"),
+        }
+    }
+
+    #[test]
+    fn test_default_path_is_derived_from_public_key() {
+        let (public_key, secret_key) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let public_bytes = public_key.byte_source().get().unwrap().to_vec();
+        let public_key = Key::Asymmetric(AsymmetricKey::Public(
+            PublicAsymmetricKey::SodiumOxideCurve25519(public_key),
+        ));
+        let secret_key = Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideCurve25519(secret_key),
+        ));
+        let first = public_key.default_path(".keys").unwrap();
+        let second = secret_key.default_path(".keys.").unwrap();
+        let (other, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let other = Key::Asymmetric(AsymmetricKey::Public(
+            PublicAsymmetricKey::SodiumOxideCurve25519(other),
+        ))
+        .default_path(".keys")
+        .unwrap();
+
+        assert_eq!(first, second);
+        assert_ne!(first, other);
+        assert_eq!(
+            first,
+            format!(".keys.{}.", super::fingerprint(&public_bytes))
+        );
+        assert!(Path::from_str(&first).is_ok());
+    }
+
+    #[test]
+    fn test_key_fingerprint_never_hashes_secret_material() {
+        let (public_key, secret_key) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let secret_bytes = secret_key.byte_source().get().unwrap().to_vec();
+        let public_key = Key::Asymmetric(AsymmetricKey::Public(
+            PublicAsymmetricKey::SodiumOxideCurve25519(public_key),
+        ));
+        let secret_key = Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideCurve25519(secret_key),
+        ));
+        assert_eq!(
+            secret_key.fingerprint().unwrap(),
+            public_key.fingerprint().unwrap()
+        );
+        assert_ne!(
+            secret_key.fingerprint().unwrap(),
+            super::fingerprint(&secret_bytes)
+        );
+
+        let symmetric_key =
+            Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        match symmetric_key.fingerprint() {
+            Err(CryptoError::UnsupportedKeyAlgorithm) => (),
+            _ => panic!("Symmetric keys should not have been fingerprinted"),
+        }
+    }
+
+    #[test]
+    fn test_default_path_of_symmetric_key_is_random() {
+        let key = SodiumOxideSymmetricKey::new();
+        let digest = super::fingerprint(key.byte_source().get().unwrap());
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(key));
+        let first = key.default_path(".keys").unwrap();
+        let second = key.default_path(".keys").unwrap();
+
+        assert_ne!(first, second);
+        assert!(!first.contains(&digest));
+        assert!(first.starts_with(".keys."));
+        assert!(first
+            .trim_start_matches(".keys.")
+            .trim_end_matches('.')
+            .chars()
+            .all(|c| c.is_ascii_hexdigit()));
+        assert!(Path::from_str(&first).is_ok());
+    }
+
+    #[test]
+    fn test_same_key_symmetric() {
+        let key = SodiumOxideSymmetricKey::new();
+        let copy = SodiumOxideSymmetricKeyBuilder {}
+            .build(Some(key.byte_source().get().unwrap()))
+            .unwrap();
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(key));
+        let copy = Key::Symmetric(SymmetricKey::SodiumOxide(copy));
+        assert!(key.same_key(&copy));
+        assert!(copy.same_key(&key));
+        assert!(key.same_key(&key));
+    }
+
+    #[test]
+    fn test_same_key_fresh_keys_differ() {
+        let first = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        let second = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        assert!(!first.same_key(&second));
+
+        let first = Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideEd25519(SodiumOxideEd25519SecretAsymmetricKey::new()),
+        ));
+        let second = Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideEd25519(SodiumOxideEd25519SecretAsymmetricKey::new()),
+        ));
+        assert!(!first.same_key(&second));
+    }
+
+    #[test]
+    fn test_same_key_public_never_equals_secret() {
+        let secret_key = SodiumOxideCurve25519SecretAsymmetricKey::new();
+        let public_key = Key::Asymmetric(AsymmetricKey::Public(
+            PublicAsymmetricKey::SodiumOxideCurve25519(secret_key.public_key().unwrap()),
+        ));
+        let secret_key = Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideCurve25519(secret_key),
+        ));
+        assert!(!public_key.same_key(&secret_key));
+        assert!(!secret_key.same_key(&public_key));
+        assert!(public_key.same_key(&public_key));
+    }
+}
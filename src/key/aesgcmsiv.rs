@@ -0,0 +1,385 @@
+//! AES-256-GCM-SIV symmetric keys. Unlike secretbox, GCM-SIV is resistant to nonce
+//! misuse: sealing twice under the same key and nonce only reveals whether the two
+//! plaintexts were equal, rather than exposing the key stream. It is a safer choice
+//! whenever nonces are supplied by the caller instead of being generated here.
+
+use crate::{
+    nonce::aesgcmsiv::Aes256GcmSivSymmetricNonce, Algorithm, Builder, ByteAlgorithm, ByteSource,
+    CryptoError, Entry, HasBuilder, HasByteSource, HasIndex, KeyBuilder, StorableType,
+    SymmetricKeyBuilder, SymmetricSealer, SymmetricUnsealer, ToSymmetricByteAlgorithm, TypeBuilder,
+    TypeBuilderContainer, VectorByteSource,
+};
+use aes_gcm_siv::{
+    aead::{Aead, NewAead, Payload},
+    Aes256GcmSiv, Key as ExternalAes256GcmSivKey, Nonce as ExternalAes256GcmSivNonce,
+};
+use async_trait::async_trait;
+use futures::Future;
+use mongodb::bson::{self, Document};
+use serde::{Deserialize, Serialize};
+use sodiumoxide::{randombytes, utils::memzero};
+use std::{
+    boxed::Box,
+    convert::{TryFrom, TryInto},
+    fmt,
+};
+
+use super::{FromSeed, RedactedBytes};
+
+const AES256GCMSIV_KEYBYTES: usize = 32;
+/// Length of the authentication tag appended to every ciphertext
+pub(crate) const AES256GCMSIV_TAGBYTES: usize = 16;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Aes256GcmSivSymmetricKeyAlgorithm {
+    pub key: Box<Entry<Aes256GcmSivSymmetricKey>>,
+    pub nonce: Aes256GcmSivSymmetricNonce,
+    /// Whether entries sealed under this algorithm authenticate their path as associated
+    /// data, see `ToEntry::to_path_bound_entry`
+    #[serde(default)]
+    pub bind_path: bool,
+}
+
+impl Aes256GcmSivSymmetricKeyAlgorithm {
+    /// Unseals the source like `Algorithm::unseal`, authenticating `aad` along with it
+    pub async fn unseal_with_aad(
+        &self,
+        source: &ByteSource,
+        aad: &[u8],
+    ) -> Result<ByteSource, CryptoError> {
+        let key = self.key.resolve().await?;
+        key.unseal_with_aad(source, &self.nonce, aad)
+    }
+
+    /// Seals the source like `Algorithm::seal`, authenticating `aad` along with it
+    pub async fn seal_with_aad(
+        &self,
+        source: &ByteSource,
+        aad: &[u8],
+    ) -> Result<ByteSource, CryptoError> {
+        let key = self.key.resolve().await?;
+        let (source, _) = key.seal_with_aad(source, Some(&self.nonce), aad)?;
+        Ok(source)
+    }
+}
+
+#[async_trait]
+impl Algorithm for Aes256GcmSivSymmetricKeyAlgorithm {
+    type Source = ByteSource;
+    type Output = ByteSource;
+
+    async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        self.unseal_with_aad(source, &[]).await
+    }
+
+    async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        self.seal_with_aad(source, &[]).await
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default)]
+pub struct Aes256GcmSivSymmetricKeyBuilder {}
+
+impl TryFrom<TypeBuilderContainer> for Aes256GcmSivSymmetricKeyBuilder {
+    type Error = CryptoError;
+
+    fn try_from(builder: TypeBuilderContainer) -> Result<Self, Self::Error> {
+        match builder.0 {
+            TypeBuilder::Key(KeyBuilder::Symmetric(SymmetricKeyBuilder::Aes256GcmSiv(agskb))) => {
+                Ok(agskb)
+            }
+            _ => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
+impl Builder for Aes256GcmSivSymmetricKeyBuilder {
+    type Output = Aes256GcmSivSymmetricKey;
+
+    fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
+        match bytes {
+            Some(bytes) => Ok(Aes256GcmSivSymmetricKey {
+                key: bytes
+                    .try_into()
+                    .map_err(|_| CryptoError::InvalidKeyLength {
+                        expected: Aes256GcmSivSymmetricKey::KEYBYTES,
+                        actual: bytes.len(),
+                    })?,
+            }),
+            None => Ok(Aes256GcmSivSymmetricKey::new()),
+        }
+    }
+}
+
+impl From<Aes256GcmSivSymmetricKeyBuilder> for TypeBuilder {
+    fn from(b: Aes256GcmSivSymmetricKeyBuilder) -> TypeBuilder {
+        TypeBuilder::Key(KeyBuilder::Symmetric(SymmetricKeyBuilder::Aes256GcmSiv(b)))
+    }
+}
+
+pub struct Aes256GcmSivSymmetricKey {
+    pub key: [u8; AES256GCMSIV_KEYBYTES],
+}
+
+impl Drop for Aes256GcmSivSymmetricKey {
+    fn drop(&mut self) {
+        memzero(&mut self.key);
+    }
+}
+
+impl fmt::Debug for Aes256GcmSivSymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Aes256GcmSivSymmetricKey")
+            .field("key", &RedactedBytes(Some(self.key.len())))
+            .finish()
+    }
+}
+
+#[async_trait]
+impl ToSymmetricByteAlgorithm for Aes256GcmSivSymmetricKey {
+    type Key = Self;
+    type Nonce = Aes256GcmSivSymmetricNonce;
+
+    async fn to_byte_algorithm<F, Fut>(
+        self,
+        nonce: Option<Self::Nonce>,
+        f: F,
+    ) -> Result<ByteAlgorithm, CryptoError>
+    where
+        F: FnOnce(Self::Key) -> Fut + Send,
+        Fut: Future<Output = Result<Entry<Self::Key>, CryptoError>> + Send,
+    {
+        let nonce = nonce.unwrap_or_else(Aes256GcmSivSymmetricNonce::new);
+        let entry = f(self).await?;
+        Ok(ByteAlgorithm::Aes256GcmSivSymmetricKey(
+            Aes256GcmSivSymmetricKeyAlgorithm {
+                key: Box::new(entry),
+                nonce,
+                bind_path: false,
+            },
+        ))
+    }
+}
+
+impl StorableType for Aes256GcmSivSymmetricKey {}
+
+impl SymmetricSealer for Aes256GcmSivSymmetricKey {
+    type SealedOutput = ByteSource;
+    type Nonce = Aes256GcmSivSymmetricNonce;
+
+    fn seal(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<(Self::SealedOutput, Self::Nonce), CryptoError> {
+        self.seal_with_aad(plaintext, nonce, &[])
+    }
+}
+
+impl SymmetricUnsealer for Aes256GcmSivSymmetricKey {
+    type UnsealedOutput = ByteSource;
+    type Nonce = Aes256GcmSivSymmetricNonce;
+
+    fn unseal(
+        &self,
+        ciphertext: &ByteSource,
+        nonce: &Self::Nonce,
+    ) -> Result<Self::UnsealedOutput, CryptoError> {
+        self.unseal_with_aad(ciphertext, nonce, &[])
+    }
+}
+
+impl HasIndex for Aes256GcmSivSymmetricKey {
+    type Index = Document;
+
+    fn get_index() -> Option<Self::Index> {
+        Some(bson::doc! {
+        "c": {
+            "builder": {
+        "t": "Key",
+        "c": {
+            "t": "Symmetric",
+        "c": {
+        "t": "Aes256GcmSiv"
+        }
+        }
+            }
+        }
+            })
+    }
+}
+
+impl HasBuilder for Aes256GcmSivSymmetricKey {
+    type Builder = Aes256GcmSivSymmetricKeyBuilder;
+
+    fn builder(&self) -> Self::Builder {
+        Aes256GcmSivSymmetricKeyBuilder {}
+    }
+}
+
+impl HasByteSource for Aes256GcmSivSymmetricKey {
+    fn byte_source(&self) -> ByteSource {
+        self.key.as_ref().into()
+    }
+}
+
+impl Aes256GcmSivSymmetricKey {
+    pub const KEYBYTES: usize = AES256GCMSIV_KEYBYTES;
+
+    pub fn new() -> Self {
+        let mut key = [0u8; Self::KEYBYTES];
+        randombytes::randombytes_into(&mut key);
+        Aes256GcmSivSymmetricKey { key }
+    }
+
+    /// Seals the plaintext like `SymmetricSealer::seal`, authenticating `aad` along with
+    /// it. The same `aad` must be given to `unseal_with_aad` for the ciphertext to verify.
+    pub fn seal_with_aad(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Aes256GcmSivSymmetricNonce>,
+        aad: &[u8],
+    ) -> Result<(ByteSource, Aes256GcmSivSymmetricNonce), CryptoError> {
+        let nonce = match nonce {
+            Some(n) => n.to_owned(),
+            None => Aes256GcmSivSymmetricNonce::new(),
+        };
+        let payload = Payload {
+            msg: plaintext.get()?,
+            aad,
+        };
+        let ciphertext = self
+            .cipher()
+            .encrypt(ExternalAes256GcmSivNonce::from_slice(&nonce.nonce), payload)
+            .map_err(|e| CryptoError::InternalError {
+                source: Box::new(e),
+            })?;
+        let cipher_byte_source =
+            VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into();
+        Ok((cipher_byte_source, nonce))
+    }
+
+    /// Unseals the ciphertext like `SymmetricUnsealer::unseal`, failing verification
+    /// unless it was sealed with the same `aad`
+    pub fn unseal_with_aad(
+        &self,
+        ciphertext: &ByteSource,
+        nonce: &Aes256GcmSivSymmetricNonce,
+        aad: &[u8],
+    ) -> Result<ByteSource, CryptoError> {
+        let payload = Payload {
+            msg: ciphertext.get()?,
+            aad,
+        };
+        let plaintext = self
+            .cipher()
+            .decrypt(ExternalAes256GcmSivNonce::from_slice(&nonce.nonce), payload)
+            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        let plaintext_byte_source =
+            VectorByteSource::new(Some(plaintext.as_slice()), *ciphertext.get_last_modified()?)
+                .into();
+        Ok(plaintext_byte_source)
+    }
+
+    fn cipher(&self) -> Aes256GcmSiv {
+        Aes256GcmSiv::new(ExternalAes256GcmSivKey::from_slice(&self.key))
+    }
+}
+
+impl Default for Aes256GcmSivSymmetricKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromSeed for Aes256GcmSivSymmetricKey {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError> {
+        Ok(Aes256GcmSivSymmetricKey { key: *seed })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Aes256GcmSivSymmetricKey, Aes256GcmSivSymmetricKeyBuilder};
+    use crate::{
+        nonce::aesgcmsiv::Aes256GcmSivSymmetricNonce, Builder, ByteSource, CryptoError, Data,
+        Entry, HasByteSource, SymmetricSealer, SymmetricUnsealer, ToEntry,
+        ToSymmetricByteAlgorithm,
+    };
+
+    #[test]
+    fn test_aes256gcmsiv_nonce_reuse_is_deterministic() {
+        let key = Aes256GcmSivSymmetricKey::new();
+        let nonce = Aes256GcmSivSymmetricNonce::new();
+        let plaintext: ByteSource = b"hello, world!".as_ref().into();
+
+        let (first, _) = key.seal(&plaintext, Some(&nonce)).unwrap();
+        let (second, _) = key.seal(&plaintext, Some(&nonce)).unwrap();
+        assert_eq!(first.get().unwrap(), second.get().unwrap());
+        assert_ne!(first.get().unwrap(), plaintext.get().unwrap());
+
+        let copy = Aes256GcmSivSymmetricKeyBuilder {}
+            .build(Some(key.byte_source().get().unwrap()))
+            .unwrap();
+        let unsealed = copy.unseal(&first, &nonce).unwrap();
+        assert_eq!(unsealed.get().unwrap(), b"hello, world!");
+
+        let (other, _) = key.seal(&plaintext, None).unwrap();
+        assert_ne!(other.get().unwrap(), first.get().unwrap());
+        match Aes256GcmSivSymmetricKey::new().unseal(&first, &nonce) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing under the wrong key should have failed verification"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_aes256gcmsiv_sealed_entry() {
+        let algorithm = Aes256GcmSivSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.siv.".to_owned())
+            })
+            .await
+            .unwrap();
+        assert_eq!(algorithm.key_id().await.unwrap(), ".keys.siv.");
+        let entry = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("hello, world!"));
+
+        let entry: Entry<Data> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_aes256gcmsiv_path_bound_entry() {
+        let algorithm = Aes256GcmSivSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.siv.".to_owned())
+            })
+            .await
+            .unwrap();
+        let entry = Data::String("hello, world!".to_owned())
+            .to_path_bound_entry(".data.a.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&entry).unwrap();
+        let entry: Entry<Data> = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+
+        // The same ciphertext and algorithm moved under another path no longer verify
+        let mut moved: Entry<Data> = serde_json::from_str(&json).unwrap();
+        moved.path = ".data.b.".to_owned();
+        match moved.resolve().await {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Resolving a moved path-bound entry should have failed"),
+        }
+    }
+}
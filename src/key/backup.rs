@@ -0,0 +1,255 @@
+//! Passphrase-protected export of keys for backup. The envelope is a base64-wrapped
+//! JSON document holding the key's builder, the argon2id parameters used to derive
+//! the key-encryption key, and the key bytes sealed under it with
+//! XChaCha20-Poly1305. Every other field of the envelope is authenticated as
+//! associated data, so it cannot be altered without failing verification.
+
+use super::{Key, KeyBuilder};
+use crate::{Builder, CryptoError, HasBuilder, HasByteSource};
+use serde::{Deserialize, Serialize};
+use sodiumoxide::crypto::{
+    aead::xchacha20poly1305_ietf::{self, Nonce},
+    pwhash::argon2id13::{
+        self, MemLimit, OpsLimit, Salt, MEMLIMIT_INTERACTIVE, MEMLIMIT_SENSITIVE,
+        OPSLIMIT_INTERACTIVE, OPSLIMIT_SENSITIVE,
+    },
+};
+
+/// Version 1 sealed with secretbox and left the header unauthenticated
+const BACKUP_VERSION: u8 = 2;
+const BACKUP_KDF: &str = "argon2id13";
+
+/// Self-describing envelope produced by `Key::export_encrypted`
+#[derive(Serialize, Deserialize, Debug)]
+struct KeyBackup {
+    version: u8,
+    algorithm: KeyBuilder,
+    kdf: String,
+    opslimit: usize,
+    memlimit: usize,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+/// Fields of a `KeyBackup` authenticated as associated data
+#[derive(Serialize)]
+struct KeyBackupHeader<'a> {
+    version: u8,
+    algorithm: &'a KeyBuilder,
+    kdf: &'a str,
+    opslimit: usize,
+    memlimit: usize,
+    salt: &'a str,
+}
+
+impl KeyBackup {
+    fn header(&self) -> Result<Vec<u8>, CryptoError> {
+        serde_json::to_vec(&KeyBackupHeader {
+            version: self.version,
+            algorithm: &self.algorithm,
+            kdf: &self.kdf,
+            opslimit: self.opslimit,
+            memlimit: self.memlimit,
+            salt: &self.salt,
+        })
+        .map_err(|e| CryptoError::InternalError {
+            source: Box::new(e),
+        })
+    }
+}
+
+impl Key {
+    /// Exports the key sealed under a key-encryption key derived from the given
+    /// passphrase. The result can be restored with `Key::import_encrypted`.
+    pub fn export_encrypted(&self, passphrase: &[u8]) -> Result<String, CryptoError> {
+        let salt = argon2id13::gen_salt();
+        let kek = derive_kek(
+            passphrase,
+            &salt,
+            OPSLIMIT_INTERACTIVE,
+            MEMLIMIT_INTERACTIVE,
+        )?;
+        let nonce = xchacha20poly1305_ietf::gen_nonce();
+
+        let mut backup = KeyBackup {
+            version: BACKUP_VERSION,
+            algorithm: self.builder(),
+            kdf: BACKUP_KDF.to_owned(),
+            opslimit: OPSLIMIT_INTERACTIVE.0,
+            memlimit: MEMLIMIT_INTERACTIVE.0,
+            salt: base64::encode(salt.as_ref()),
+            nonce: base64::encode(nonce.as_ref()),
+            ciphertext: String::new(),
+        };
+        let ciphertext = xchacha20poly1305_ietf::seal(
+            self.byte_source().get()?,
+            Some(&backup.header()?),
+            &nonce,
+            &kek,
+        );
+        backup.ciphertext = base64::encode(&ciphertext);
+        let json = serde_json::to_vec(&backup).map_err(|e| CryptoError::InternalError {
+            source: Box::new(e),
+        })?;
+        Ok(base64::encode(json))
+    }
+
+    /// Restores a key exported with `Key::export_encrypted`. Returns
+    /// `CryptoError::CiphertextFailedVerification` if the passphrase is wrong.
+    pub fn import_encrypted(blob: &str, passphrase: &[u8]) -> Result<Key, CryptoError> {
+        let json = base64::decode(blob.trim()).map_err(|_| CryptoError::InvalidKeyBackup)?;
+        let backup: KeyBackup =
+            serde_json::from_slice(&json).map_err(|_| CryptoError::InvalidKeyBackup)?;
+        // Bounding the parameters keeps a crafted envelope from demanding
+        // unreasonable amounts of work or memory, or from weakening the derivation
+        if backup.version != BACKUP_VERSION
+            || backup.kdf != BACKUP_KDF
+            || backup.opslimit < OPSLIMIT_INTERACTIVE.0
+            || backup.memlimit < MEMLIMIT_INTERACTIVE.0
+            || backup.opslimit > OPSLIMIT_SENSITIVE.0
+            || backup.memlimit > MEMLIMIT_SENSITIVE.0
+        {
+            return Err(CryptoError::InvalidKeyBackup);
+        }
+
+        let salt = base64::decode(&backup.salt)
+            .ok()
+            .and_then(|s| Salt::from_slice(&s))
+            .ok_or(CryptoError::InvalidKeyBackup)?;
+        let nonce = base64::decode(&backup.nonce)
+            .ok()
+            .and_then(|n| Nonce::from_slice(&n))
+            .ok_or(CryptoError::InvalidKeyBackup)?;
+        let ciphertext =
+            base64::decode(&backup.ciphertext).map_err(|_| CryptoError::InvalidKeyBackup)?;
+
+        let kek = derive_kek(
+            passphrase,
+            &salt,
+            OpsLimit(backup.opslimit),
+            MemLimit(backup.memlimit),
+        )?;
+        let key_bytes =
+            xchacha20poly1305_ietf::open(&ciphertext, Some(&backup.header()?), &nonce, &kek)
+                .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        backup.algorithm.build(Some(&key_bytes))
+    }
+}
+
+fn derive_kek(
+    passphrase: &[u8],
+    salt: &Salt,
+    opslimit: OpsLimit,
+    memlimit: MemLimit,
+) -> Result<xchacha20poly1305_ietf::Key, CryptoError> {
+    let mut kek = xchacha20poly1305_ietf::Key([0; xchacha20poly1305_ietf::KEYBYTES]);
+    argon2id13::derive_key(&mut kek.0, passphrase, salt, opslimit, memlimit)
+        .map_err(|_| CryptoError::InvalidKeyBackup)?;
+    Ok(kek)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        key::{
+            aesgcmsiv::Aes256GcmSivSymmetricKey,
+            ring::RingEd25519SecretAsymmetricKey,
+            sodiumoxide::{SodiumOxideEd25519SecretAsymmetricKey, SodiumOxideSymmetricKey},
+        },
+        AsymmetricKey, CryptoError, HasBuilder, HasByteSource, Key, SecretAsymmetricKey,
+        SymmetricKey,
+    };
+    use serde_json::Value;
+
+    /// Applies the given change to the JSON envelope of the backup
+    fn tamper(blob: &str, f: impl FnOnce(&mut Value)) -> String {
+        let mut envelope: Value = serde_json::from_slice(&base64::decode(blob).unwrap()).unwrap();
+        f(&mut envelope);
+        base64::encode(serde_json::to_vec(&envelope).unwrap())
+    }
+
+    fn assert_roundtrip(key: Key) {
+        let blob = key
+            .export_encrypted(b"correct horse battery staple")
+            .unwrap();
+        let imported = Key::import_encrypted(&blob, b"correct horse battery staple").unwrap();
+        assert_eq!(
+            imported.byte_source().get().unwrap(),
+            key.byte_source().get().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_export_import_symmetric_key() {
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        assert_roundtrip(key);
+    }
+
+    #[test]
+    fn test_export_import_secret_asymmetric_key() {
+        assert_roundtrip(Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::SodiumOxideEd25519(SodiumOxideEd25519SecretAsymmetricKey::new()),
+        )));
+        assert_roundtrip(Key::Asymmetric(AsymmetricKey::Secret(
+            SecretAsymmetricKey::RingEd25519(RingEd25519SecretAsymmetricKey::new().unwrap()),
+        )));
+    }
+
+    #[test]
+    fn test_import_wrong_passphrase() {
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        let blob = key
+            .export_encrypted(b"correct horse battery staple")
+            .unwrap();
+        match Key::import_encrypted(&blob, b"incorrect horse") {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Importing with the wrong passphrase should have failed verification"),
+        }
+    }
+
+    #[test]
+    fn test_import_tampered_header() {
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        let blob = key
+            .export_encrypted(b"correct horse battery staple")
+            .unwrap();
+
+        // The key bytes would build an AES key just as well, but the algorithm is
+        // authenticated along with them
+        let other = Key::Symmetric(SymmetricKey::Aes256GcmSiv(Aes256GcmSivSymmetricKey::new()));
+        let tampered = tamper(&blob, |envelope| {
+            envelope["algorithm"] = serde_json::to_value(other.builder()).unwrap()
+        });
+        match Key::import_encrypted(&tampered, b"correct horse battery staple") {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Importing a backup with a swapped algorithm should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_import_weak_kdf_params() {
+        let key = Key::Symmetric(SymmetricKey::SodiumOxide(SodiumOxideSymmetricKey::new()));
+        let blob = key
+            .export_encrypted(b"correct horse battery staple")
+            .unwrap();
+        for field in ["opslimit", "memlimit"].iter() {
+            let tampered = tamper(&blob, |envelope| envelope[*field] = 1.into());
+            match Key::import_encrypted(&tampered, b"correct horse battery staple") {
+                Err(CryptoError::InvalidKeyBackup) => (),
+                _ => panic!(
+                    "Importing a backup with a weakened {} should have failed",
+                    field
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_import_malformed_backup() {
+        match Key::import_encrypted("not a backup", b"passphrase") {
+            Err(CryptoError::InvalidKeyBackup) => (),
+            _ => panic!("Importing a malformed backup should have failed"),
+        }
+    }
+}
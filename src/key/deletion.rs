@@ -0,0 +1,95 @@
+//! Guarded deletion of keys. A key that still seals stored entries cannot be deleted
+//! without making those entries unreadable, so every entry under a prefix is checked for
+//! a reference to the key before it is removed.
+
+use super::Key;
+use crate::{CryptoError, IndexedStorer, State, Type};
+
+/// Number of entries fetched from the storer at a time
+const PAGE_SIZE: i64 = 100;
+
+/// Deletes the key stored at `key_path`, unless an entry under `search_prefix` is sealed
+/// by an algorithm referencing it, directly or through the keys sealing its own key. The
+/// first such entry found is returned in `CryptoError::KeyStillInUse` and nothing is
+/// deleted. Entries outside of `search_prefix` are not checked.
+pub async fn safe_delete_key<S: IndexedStorer>(
+    storer: &S,
+    key_path: &str,
+    search_prefix: &str,
+) -> Result<(), CryptoError> {
+    let mut skip = 0;
+    loop {
+        let entries = storer.list::<Type>(search_prefix, skip, PAGE_SIZE).await?;
+        let fetched = entries.len() as u64;
+
+        for entry in entries {
+            if let State::Sealed { ref algorithm, .. } = entry.value {
+                if algorithm.referenced_paths().iter().any(|p| p == key_path) {
+                    return Err(CryptoError::KeyStillInUse {
+                        referencing_path: entry.path,
+                    });
+                }
+            }
+        }
+
+        if fetched < PAGE_SIZE as u64 {
+            break;
+        }
+        skip += fetched;
+    }
+    storer.delete::<Key>(key_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::safe_delete_key;
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, CryptoError, Data, Entry, MemoryStorer, Storer,
+        ToEntry,
+    };
+
+    #[tokio::test]
+    async fn test_safe_delete_key_blocked_by_referencing_entry() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".keys.kek.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let algorithm =
+            Entry::<SodiumOxideSymmetricKey>::reference(".keys.kek.".to_owned(), storer.clone())
+                .to_symmetric_byte_algorithm(None)
+                .await
+                .unwrap();
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_sealed_entry(".data.greeting.".to_owned(), algorithm)
+                    .await
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        match safe_delete_key(&storer, ".keys.kek.", ".").await {
+            Err(CryptoError::KeyStillInUse { referencing_path }) => {
+                assert_eq!(referencing_path, ".data.greeting.")
+            }
+            _ => panic!("Key sealing a stored entry should not have been deleted"),
+        }
+        assert!(storer
+            .get::<SodiumOxideSymmetricKey>(".keys.kek.")
+            .await
+            .is_ok());
+
+        storer.delete::<Data>(".data.greeting.").await.unwrap();
+        safe_delete_key(&storer, ".keys.kek.", ".").await.unwrap();
+        match storer.get::<SodiumOxideSymmetricKey>(".keys.kek.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Unused key should have been deleted"),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+//! Integrity auditing of stored keys. Every key entry under a prefix is resolved, which
+//! unseals it and builds the key from its bytes, so corrupted or truncated key material
+//! is caught before it is needed. Stored keypairs can also be checked to still belong
+//! together.
+
+use super::{HasPublicKey, Key, PublicAsymmetricKey, SecretAsymmetricKey};
+use crate::{CryptoError, EntryPath, HasByteSource, IndexedStorer, Storer};
+use std::mem;
+use subtle::ConstantTimeEq;
+
+/// Number of entries fetched from the storer at a time
+const PAGE_SIZE: i64 = 100;
+
+/// Resolves every key entry under `prefix` and reports whether each one built. A key
+/// that fails to resolve is reported alongside its error and the audit moves on to the
+/// next one; only a failure to list the entries aborts it.
+pub async fn audit_keys<S: IndexedStorer>(
+    storer: &S,
+    prefix: &str,
+) -> Result<Vec<(EntryPath, Result<(), CryptoError>)>, CryptoError> {
+    let mut report = vec![];
+    let mut skip = 0;
+    loop {
+        let entries = storer.list::<Key>(prefix, skip, PAGE_SIZE).await?;
+        let fetched = entries.len() as u64;
+
+        for entry in entries {
+            let path = entry.path.clone();
+            report.push((path, entry.take_resolve().await.map(|_| ())));
+        }
+
+        if fetched < PAGE_SIZE as u64 {
+            return Ok(report);
+        }
+        skip += fetched;
+    }
+}
+
+/// Resolves the secret key at `secret_path` and the public key at `public_path` and
+/// returns whether the public key derived from the secret one matches the stored public
+/// key, comparing their bytes in constant time. Keys of different algorithms never
+/// match. Failing to fetch or resolve either key is returned as an error.
+pub async fn verify_keypair<S: Storer>(
+    storer: &S,
+    secret_path: &str,
+    public_path: &str,
+) -> Result<bool, CryptoError> {
+    let secret_key = storer
+        .get::<SecretAsymmetricKey>(secret_path)
+        .await?
+        .take_resolve()
+        .await?;
+    let public_key = storer
+        .get::<PublicAsymmetricKey>(public_path)
+        .await?
+        .take_resolve()
+        .await?;
+    let derived = secret_key.public_key()?;
+    if mem::discriminant(&derived) != mem::discriminant(&public_key) {
+        return Ok(false);
+    }
+
+    let (derived_bytes, stored_bytes) = (derived.byte_source(), public_key.byte_source());
+    Ok(derived_bytes.get()?.ct_eq(stored_bytes.get()?).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{audit_keys, verify_keypair};
+    use crate::{
+        key::sodiumoxide::{
+            SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideEd25519PublicAsymmetricKey,
+            SodiumOxideEd25519SecretAsymmetricKey, SodiumOxideSymmetricKey,
+        },
+        CryptoError, Entry, HasBuilder, MemoryStorer, State, Storer, ToEntry,
+    };
+
+    #[tokio::test]
+    async fn test_audit_keys_flags_truncated_key() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".keys.symmetric.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(
+                SodiumOxideEd25519SecretAsymmetricKey::new()
+                    .to_unsealed_entry(".keys.signing.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(Entry::<SodiumOxideSymmetricKey>::new(
+                ".keys.truncated.".to_owned(),
+                SodiumOxideSymmetricKey::new().builder().into(),
+                State::Unsealed {
+                    bytes: "too short".into(),
+                },
+            ))
+            .await
+            .unwrap();
+
+        let mut report = audit_keys(&storer, ".keys.").await.unwrap();
+        report.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(report.len(), 3);
+        assert!(report[0].1.is_ok());
+        assert!(report[1].1.is_ok());
+        assert_eq!(report[2].0, ".keys.truncated.");
+        match report[2].1 {
+            Err(CryptoError::InvalidKeyLength { actual: 9, .. }) => (),
+            _ => panic!("Truncated key should have failed to build"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verify_keypair() {
+        let storer = MemoryStorer::new();
+        let (secret, public) = SodiumOxideEd25519PublicAsymmetricKey::new_entry_pair(
+            ".keys.signing.secret.".to_owned(),
+            ".keys.signing.public.".to_owned(),
+        )
+        .unwrap();
+        storer.create(secret).await.unwrap();
+        storer.create(public).await.unwrap();
+        let (_, other_public) = SodiumOxideEd25519PublicAsymmetricKey::new_entry_pair(
+            ".keys.other.secret.".to_owned(),
+            ".keys.other.public.".to_owned(),
+        )
+        .unwrap();
+        storer.create(other_public).await.unwrap();
+        let (_, box_public) = SodiumOxideCurve25519PublicAsymmetricKey::new_entry_pair(
+            ".keys.box.secret.".to_owned(),
+            ".keys.box.public.".to_owned(),
+        )
+        .unwrap();
+        storer.create(box_public).await.unwrap();
+
+        assert!(
+            verify_keypair(&storer, ".keys.signing.secret.", ".keys.signing.public.")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !verify_keypair(&storer, ".keys.signing.secret.", ".keys.other.public.")
+                .await
+                .unwrap()
+        );
+        assert!(
+            !verify_keypair(&storer, ".keys.signing.secret.", ".keys.box.public.")
+                .await
+                .unwrap()
+        );
+    }
+}
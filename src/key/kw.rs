@@ -0,0 +1,207 @@
+//! AES key wrapping as defined in RFC 3394. Unlike the AEAD seal used elsewhere in
+//! the crate, the output is deterministic and exactly 8 bytes longer than the
+//! wrapped key, which is the format returned by most cloud KMS unwrap/wrap APIs.
+
+use crate::CryptoError;
+use aes::{
+    cipher::consts::U16, Aes128, Aes192, Aes256, Block, BlockCipher, BlockDecrypt, BlockEncrypt,
+    NewBlockCipher,
+};
+
+/// Default initial value from RFC 3394 section 2.2.3.1
+const DEFAULT_IV: [u8; 8] = [0xA6; 8];
+const SEMIBLOCK_SIZE: usize = 8;
+
+/// Wraps `key` under the AES key-encryption key `kek`. The KEK must be 16, 24 or 32
+/// bytes long, and the key being wrapped must be a multiple of 8 bytes and at least
+/// 16 bytes long.
+pub fn aes_kw_wrap(kek: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if key.len() < 2 * SEMIBLOCK_SIZE || key.len() % SEMIBLOCK_SIZE != 0 {
+        return Err(CryptoError::InvalidKeyLength {
+            expected: valid_key_length(key.len()),
+            actual: key.len(),
+        });
+    }
+    match kek.len() {
+        16 => Ok(wrap(&Aes128::new(kek.into()), key)),
+        24 => Ok(wrap(&Aes192::new(kek.into()), key)),
+        32 => Ok(wrap(&Aes256::new(kek.into()), key)),
+        _ => Err(CryptoError::InvalidKeyLength {
+            expected: valid_kek_length(kek.len()),
+            actual: kek.len(),
+        }),
+    }
+}
+
+/// Unwraps a key previously wrapped under `kek`. Returns
+/// `CryptoError::CiphertextFailedVerification` if the integrity check fails, which
+/// happens when either the wrapped key or the KEK is wrong.
+pub fn aes_kw_unwrap(kek: &[u8], wrapped: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if wrapped.len() < 3 * SEMIBLOCK_SIZE || wrapped.len() % SEMIBLOCK_SIZE != 0 {
+        return Err(CryptoError::InvalidKeyLength {
+            expected: valid_key_length(wrapped.len().saturating_sub(SEMIBLOCK_SIZE))
+                + SEMIBLOCK_SIZE,
+            actual: wrapped.len(),
+        });
+    }
+    let (iv, key) = match kek.len() {
+        16 => unwrap(&Aes128::new(kek.into()), wrapped),
+        24 => unwrap(&Aes192::new(kek.into()), wrapped),
+        32 => unwrap(&Aes256::new(kek.into()), wrapped),
+        _ => {
+            return Err(CryptoError::InvalidKeyLength {
+                expected: valid_kek_length(kek.len()),
+                actual: kek.len(),
+            })
+        }
+    };
+    ring::constant_time::verify_slices_are_equal(&iv, &DEFAULT_IV)
+        .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+    Ok(key)
+}
+
+fn wrap<C: BlockCipher<BlockSize = U16> + BlockEncrypt>(cipher: &C, key: &[u8]) -> Vec<u8> {
+    let n = key.len() / SEMIBLOCK_SIZE;
+    let mut output = Vec::with_capacity(key.len() + SEMIBLOCK_SIZE);
+    output.extend_from_slice(&DEFAULT_IV);
+    output.extend_from_slice(key);
+    let mut block = Block::default();
+
+    for j in 0..6 {
+        for i in 1..=n {
+            block[..SEMIBLOCK_SIZE].copy_from_slice(&output[..SEMIBLOCK_SIZE]);
+            block[SEMIBLOCK_SIZE..]
+                .copy_from_slice(&output[i * SEMIBLOCK_SIZE..(i + 1) * SEMIBLOCK_SIZE]);
+            cipher.encrypt_block(&mut block);
+
+            let t = ((n * j) + i) as u64;
+            for (a, t) in block[..SEMIBLOCK_SIZE]
+                .iter_mut()
+                .zip(t.to_be_bytes().iter())
+            {
+                *a ^= t;
+            }
+            output[..SEMIBLOCK_SIZE].copy_from_slice(&block[..SEMIBLOCK_SIZE]);
+            output[i * SEMIBLOCK_SIZE..(i + 1) * SEMIBLOCK_SIZE]
+                .copy_from_slice(&block[SEMIBLOCK_SIZE..]);
+        }
+    }
+
+    output
+}
+
+/// Runs the unwrap rounds and returns the recovered integrity value along with the key,
+/// leaving the integrity check to the caller
+fn unwrap<C: BlockCipher<BlockSize = U16> + BlockDecrypt>(
+    cipher: &C,
+    wrapped: &[u8],
+) -> ([u8; SEMIBLOCK_SIZE], Vec<u8>) {
+    let n = wrapped.len() / SEMIBLOCK_SIZE - 1;
+    let mut a = [0u8; SEMIBLOCK_SIZE];
+    a.copy_from_slice(&wrapped[..SEMIBLOCK_SIZE]);
+    let mut r = wrapped[SEMIBLOCK_SIZE..].to_vec();
+    let mut block = Block::default();
+
+    for j in (0..6).rev() {
+        for i in (1..=n).rev() {
+            let t = ((n * j) + i) as u64;
+            for (a, t) in a.iter_mut().zip(t.to_be_bytes().iter()) {
+                *a ^= t;
+            }
+            block[..SEMIBLOCK_SIZE].copy_from_slice(&a);
+            block[SEMIBLOCK_SIZE..]
+                .copy_from_slice(&r[(i - 1) * SEMIBLOCK_SIZE..i * SEMIBLOCK_SIZE]);
+            cipher.decrypt_block(&mut block);
+
+            a.copy_from_slice(&block[..SEMIBLOCK_SIZE]);
+            r[(i - 1) * SEMIBLOCK_SIZE..i * SEMIBLOCK_SIZE]
+                .copy_from_slice(&block[SEMIBLOCK_SIZE..]);
+        }
+    }
+
+    (a, r)
+}
+
+/// Returns the smallest valid wrappable key length that is at least `actual`
+fn valid_key_length(actual: usize) -> usize {
+    let rounded = (actual + SEMIBLOCK_SIZE - 1) / SEMIBLOCK_SIZE * SEMIBLOCK_SIZE;
+    rounded.max(2 * SEMIBLOCK_SIZE)
+}
+
+/// Returns the AES key size closest to `actual`, preferring the next size up
+fn valid_kek_length(actual: usize) -> usize {
+    match actual {
+        0..=16 => 16,
+        17..=24 => 24,
+        _ => 32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{aes_kw_unwrap, aes_kw_wrap};
+    use crate::CryptoError;
+
+    const KEK_128: &str = "000102030405060708090A0B0C0D0E0F";
+    const KEY_128: &str = "00112233445566778899AABBCCDDEEFF";
+
+    #[test]
+    fn test_aes_kw_rfc3394_128_bit_kek_128_bit_key() {
+        let kek = hex::decode(KEK_128).unwrap();
+        let key = hex::decode(KEY_128).unwrap();
+        let expected = hex::decode("1FA68B0A8112B447AEF34BD8FB5A7B829D3E862371D2CFE5").unwrap();
+
+        let wrapped = aes_kw_wrap(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(wrapped.len(), key.len() + 8);
+        assert_eq!(aes_kw_unwrap(&kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_aes_kw_rfc3394_256_bit_kek_256_bit_key() {
+        let kek = hex::decode("000102030405060708090A0B0C0D0E0F101112131415161718191A1B1C1D1E1F")
+            .unwrap();
+        let key = hex::decode("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F")
+            .unwrap();
+        let expected = hex::decode(
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21",
+        )
+        .unwrap();
+
+        let wrapped = aes_kw_wrap(&kek, &key).unwrap();
+        assert_eq!(wrapped, expected);
+        assert_eq!(aes_kw_unwrap(&kek, &wrapped).unwrap(), key);
+    }
+
+    #[test]
+    fn test_aes_kw_unwrap_tampered() {
+        let kek = hex::decode(KEK_128).unwrap();
+        let key = hex::decode(KEY_128).unwrap();
+        let mut wrapped = aes_kw_wrap(&kek, &key).unwrap();
+        wrapped[12] ^= 0x01;
+
+        match aes_kw_unwrap(&kek, &wrapped) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unwrapping a tampered key should have failed verification"),
+        }
+    }
+
+    #[test]
+    fn test_aes_kw_invalid_lengths() {
+        let kek = hex::decode(KEK_128).unwrap();
+        match aes_kw_wrap(&kek[..10], &hex::decode(KEY_128).unwrap()) {
+            Err(CryptoError::InvalidKeyLength { expected, actual }) => {
+                assert_eq!(expected, 16);
+                assert_eq!(actual, 10);
+            }
+            _ => panic!("Wrapping under a 10-byte KEK should have failed"),
+        }
+        match aes_kw_wrap(&kek, &[0u8; 12]) {
+            Err(CryptoError::InvalidKeyLength { expected, actual }) => {
+                assert_eq!(expected, 16);
+                assert_eq!(actual, 12);
+            }
+            _ => panic!("Wrapping a 12-byte key should have failed"),
+        }
+    }
+}
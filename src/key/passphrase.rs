@@ -0,0 +1,241 @@
+//! Symmetric sealing under a key derived from a passphrase with argon2id. The salt and
+//! the derivation parameters are stored with the algorithm, and every seal writes its own
+//! fresh nonce ahead of the ciphertext, so an entry sealed under it is self-contained and
+//! only needs the passphrase to be resolved with `Entry::resolve_with_passphrase`. The
+//! passphrase itself is never stored.
+
+use crate::{Algorithm, ByteSource, CryptoError, VectorByteSource};
+use async_trait::async_trait;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sodiumoxide::crypto::{
+    pwhash::argon2id13::{
+        self, MemLimit, OpsLimit, Salt, MEMLIMIT_INTERACTIVE, MEMLIMIT_SENSITIVE,
+        OPSLIMIT_INTERACTIVE, OPSLIMIT_SENSITIVE,
+    },
+    secretbox,
+};
+
+/// Weakest derivation parameters accepted, those of argon2id's interactive profile
+const MIN_PARAMS: PassphraseParams = PassphraseParams {
+    opslimit: OPSLIMIT_INTERACTIVE.0,
+    memlimit: MEMLIMIT_INTERACTIVE.0,
+};
+
+/// Cost parameters of the argon2id derivation
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct PassphraseParams {
+    pub opslimit: usize,
+    pub memlimit: usize,
+}
+
+impl Default for PassphraseParams {
+    fn default() -> Self {
+        MIN_PARAMS
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PassphraseSymmetricAlgorithm {
+    #[serde(
+        serialize_with = "salt_serialize",
+        deserialize_with = "salt_deserialize"
+    )]
+    pub salt: Salt,
+    pub params: PassphraseParams,
+    /// Key derived when the algorithm was created from a passphrase, so that it can seal
+    /// without deriving it again. It is never serialized, leaving deserialized algorithms
+    /// able to unseal only through `unseal_with_passphrase`.
+    #[serde(skip)]
+    key: Option<secretbox::Key>,
+}
+
+impl PassphraseSymmetricAlgorithm {
+    /// Creates an algorithm sealing under a key derived from the passphrase with a fresh
+    /// salt and the default interactive cost parameters
+    pub fn new(passphrase: &[u8]) -> Result<Self, CryptoError> {
+        Self::with_params(passphrase, PassphraseParams::default())
+    }
+
+    /// Creates an algorithm like `new`, using the given cost parameters. Parameters below
+    /// the interactive profile or above the sensitive one fail with
+    /// `CryptoError::InvalidPassphraseParams`.
+    pub fn with_params(passphrase: &[u8], params: PassphraseParams) -> Result<Self, CryptoError> {
+        let salt = argon2id13::gen_salt();
+        let key = derive_key(passphrase, &salt, params)?;
+        Ok(PassphraseSymmetricAlgorithm {
+            salt,
+            params,
+            key: Some(key),
+        })
+    }
+
+    /// Unseals the source under the key derived from the given passphrase. A wrong
+    /// passphrase fails with `CryptoError::CiphertextFailedVerification`.
+    pub fn unseal_with_passphrase(
+        &self,
+        source: &ByteSource,
+        passphrase: &[u8],
+    ) -> Result<ByteSource, CryptoError> {
+        let key = derive_key(passphrase, &self.salt, self.params)?;
+        self.open(source, &key)
+    }
+
+    /// Opens a sealed source, made of the nonce it was sealed under followed by the
+    /// ciphertext
+    fn open(&self, source: &ByteSource, key: &secretbox::Key) -> Result<ByteSource, CryptoError> {
+        let sealed = source.get()?;
+        if sealed.len() < secretbox::NONCEBYTES + secretbox::MACBYTES {
+            return Err(CryptoError::CiphertextFailedVerification);
+        }
+        let (nonce, ciphertext) = sealed.split_at(secretbox::NONCEBYTES);
+        let nonce =
+            secretbox::Nonce::from_slice(nonce).ok_or(CryptoError::CiphertextFailedVerification)?;
+        let plaintext = secretbox::open(ciphertext, &nonce, key)
+            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        Ok(VectorByteSource::new(Some(&plaintext), *source.get_last_modified()?).into())
+    }
+}
+
+#[async_trait]
+impl Algorithm for PassphraseSymmetricAlgorithm {
+    type Source = ByteSource;
+    type Output = ByteSource;
+
+    async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        match self.key {
+            Some(ref key) => self.open(source, key),
+            None => Err(CryptoError::PassphraseRequired),
+        }
+    }
+
+    async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
+        let key = self.key.as_ref().ok_or(CryptoError::PassphraseRequired)?;
+        // The derived key seals every value, so each one gets its own nonce
+        let nonce = secretbox::gen_nonce();
+        let mut sealed = nonce.as_ref().to_vec();
+        sealed.extend_from_slice(&secretbox::seal(source.get()?, &nonce, key));
+        Ok(VectorByteSource::new(Some(&sealed), *source.get_last_modified()?).into())
+    }
+}
+
+/// Derives the sealing key, refusing parameters above the sensitive limits so that a
+/// crafted entry cannot demand unreasonable amounts of work or memory, and below the
+/// interactive ones so that it cannot make the passphrase cheap to brute-force
+fn derive_key(
+    passphrase: &[u8],
+    salt: &Salt,
+    params: PassphraseParams,
+) -> Result<secretbox::Key, CryptoError> {
+    if params.opslimit < MIN_PARAMS.opslimit
+        || params.memlimit < MIN_PARAMS.memlimit
+        || params.opslimit > OPSLIMIT_SENSITIVE.0
+        || params.memlimit > MEMLIMIT_SENSITIVE.0
+    {
+        return Err(CryptoError::InvalidPassphraseParams);
+    }
+    let mut key = secretbox::Key([0; secretbox::KEYBYTES]);
+    argon2id13::derive_key(
+        &mut key.0,
+        passphrase,
+        salt,
+        OpsLimit(params.opslimit),
+        MemLimit(params.memlimit),
+    )
+    .map_err(|_| CryptoError::InvalidPassphraseParams)?;
+    Ok(key)
+}
+
+/// Custom serialization function base64-encodes the salt before storage
+fn salt_serialize<S>(salt: &Salt, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    s.serialize_str(&base64::encode(salt.as_ref()))
+}
+
+/// Custom deserialization function base64-decodes the salt before passing it back
+fn salt_deserialize<'de, D>(deserializer: D) -> Result<Salt, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let b64_encoded: String = de::Deserialize::deserialize(deserializer)?;
+    let decoded = base64::decode(b64_encoded).map_err(de::Error::custom)?;
+    Salt::from_slice(&decoded).ok_or_else(|| de::Error::custom("invalid salt length"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PassphraseParams, PassphraseSymmetricAlgorithm};
+    use crate::{Algorithm, ByteAlgorithm, ByteSource, CryptoError, Data, Entry, ToEntry};
+    use sodiumoxide::crypto::secretbox;
+
+    #[tokio::test]
+    async fn test_passphrase_sealed_entry() {
+        let algorithm = ByteAlgorithm::PassphraseSymmetric(
+            PassphraseSymmetricAlgorithm::new(b"correct horse battery staple").unwrap(),
+        );
+        let entry = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        let json = serde_json::to_string(&entry).unwrap();
+        assert!(!json.contains("hello, world!"));
+
+        let entry: Entry<Data> = serde_json::from_str(&json).unwrap();
+        match entry.resolve().await {
+            Err(CryptoError::PassphraseRequired) => (),
+            _ => panic!("Resolving without a passphrase should have failed"),
+        }
+        match entry.resolve_with_passphrase(b"incorrect horse").await {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Resolving with the wrong passphrase should have failed"),
+        }
+        assert_eq!(
+            entry
+                .resolve_with_passphrase(b"correct horse battery staple")
+                .await
+                .unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_passphrase_seals_under_fresh_nonces() {
+        let algorithm = PassphraseSymmetricAlgorithm::new(b"correct horse battery staple").unwrap();
+        let plaintext: ByteSource = "hello, world!".into();
+        let first = algorithm.seal(&plaintext).await.unwrap();
+        let second = algorithm.seal(&plaintext).await.unwrap();
+        assert_ne!(
+            &first.get().unwrap()[..secretbox::NONCEBYTES],
+            &second.get().unwrap()[..secretbox::NONCEBYTES]
+        );
+        for sealed in [first, second].iter() {
+            assert_eq!(
+                algorithm.unseal(sealed).await.unwrap().get().unwrap(),
+                b"hello, world!"
+            );
+        }
+    }
+
+    #[test]
+    fn test_passphrase_rejects_weak_params() {
+        let defaults = PassphraseParams::default();
+        for params in [
+            PassphraseParams {
+                opslimit: defaults.opslimit - 1,
+                ..defaults
+            },
+            PassphraseParams {
+                memlimit: defaults.memlimit / 2,
+                ..defaults
+            },
+        ]
+        .iter()
+        {
+            match PassphraseSymmetricAlgorithm::with_params(b"hunter2", *params) {
+                Err(CryptoError::InvalidPassphraseParams) => (),
+                _ => panic!("Weak derivation parameters should have been rejected"),
+            }
+        }
+    }
+}
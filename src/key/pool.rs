@@ -0,0 +1,101 @@
+//! Concurrent signing with a shared key. Signing is CPU-bound, so a `SignerPool` splits
+//! a batch of messages into chunks and signs each chunk on tokio's blocking thread pool
+//! rather than on the async executor.
+
+use super::{Signer, SigningKey};
+use crate::{ByteSource, CryptoError};
+use futures::future;
+use std::{panic, sync::Arc};
+
+/// Signs batches of messages with a shared signing key across up to `workers` blocking
+/// threads. Must be used from within a tokio runtime.
+#[derive(Debug, Clone)]
+pub struct SignerPool {
+    key: Arc<SigningKey>,
+    workers: usize,
+}
+
+impl SignerPool {
+    /// Creates a pool signing with the given key on up to `workers` threads at a time,
+    /// using at least one
+    pub fn new(key: Arc<SigningKey>, workers: usize) -> Self {
+        SignerPool {
+            key,
+            workers: workers.max(1),
+        }
+    }
+
+    /// Returns the key signatures are made with
+    pub fn key(&self) -> &Arc<SigningKey> {
+        &self.key
+    }
+
+    /// Signs every message, returning the results in the same order as the messages. A
+    /// panic while signing is propagated to the caller, and messages whose chunk was
+    /// abandoned because the runtime shut down fail with `CryptoError::Cancelled`.
+    pub async fn sign_all(
+        &self,
+        messages: Vec<ByteSource>,
+    ) -> Vec<Result<ByteSource, CryptoError>> {
+        let total = messages.len();
+        let chunk_size = ((total + self.workers - 1) / self.workers).max(1);
+        let mut messages = messages.into_iter().peekable();
+        let mut chunks = vec![];
+        while messages.peek().is_some() {
+            chunks.push(messages.by_ref().take(chunk_size).collect::<Vec<_>>());
+        }
+
+        let chunk_lengths: Vec<usize> = chunks.iter().map(Vec::len).collect();
+        let handles = chunks.into_iter().map(|chunk| {
+            let key = self.key.clone();
+            tokio::task::spawn_blocking(move || {
+                chunk
+                    .into_iter()
+                    .map(|message| key.sign(message))
+                    .collect::<Vec<_>>()
+            })
+        });
+
+        let mut results = Vec::with_capacity(total);
+        for (signed, len) in future::join_all(handles)
+            .await
+            .into_iter()
+            .zip(chunk_lengths)
+        {
+            match signed {
+                Ok(signed) => results.extend(signed),
+                Err(e) if e.is_panic() => panic::resume_unwind(e.into_panic()),
+                Err(_) => results.extend((0..len).map(|_| Err(CryptoError::Cancelled))),
+            }
+        }
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SignerPool;
+    use crate::{
+        key::{ring::RingEd25519SecretAsymmetricKey, SigningKey},
+        ByteSource, Verifier,
+    };
+    use std::sync::Arc;
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_sign_all_concurrently() {
+        let key = Arc::new(SigningKey::RingEd25519(
+            RingEd25519SecretAsymmetricKey::new().unwrap(),
+        ));
+        let pool = SignerPool::new(key.clone(), 8);
+        let messages: Vec<ByteSource> = (0..100)
+            .map(|i| format!("message {}", i).as_str().into())
+            .collect();
+
+        let signatures = pool.sign_all(messages.clone()).await;
+        assert_eq!(signatures.len(), 100);
+        for (message, signature) in messages.into_iter().zip(signatures) {
+            key.verify(message, signature.unwrap()).unwrap();
+        }
+        assert!(pool.sign_all(vec![]).await.is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+//! Sealing to a recipient known only by the path of their stored public key. The key is
+//! fetched and resolved from the storer, and the plaintext is sealed anonymously to it,
+//! so the recipient only needs their secret key to open the result.
+
+use super::{sodiumoxide::SodiumOxideCurve25519PublicAsymmetricKey, PublicAsymmetricKey};
+use crate::{ByteSource, CryptoError, Storer};
+
+/// Seals the plaintext in a sealed box to the public key stored at `recipient_path`.
+/// Curve25519 keys are sealed to directly and Ed25519 sodiumoxide keys are converted to
+/// their Curve25519 form, to be opened with the converted secret key. Ring Ed25519 keys
+/// cannot be converted and fail with `CryptoError::UnsupportedKeyAlgorithm`.
+pub async fn seal_to_path<S: Storer>(
+    storer: &S,
+    recipient_path: &str,
+    plaintext: &ByteSource,
+) -> Result<ByteSource, CryptoError> {
+    let recipient = storer
+        .get::<PublicAsymmetricKey>(recipient_path)
+        .await?
+        .take_resolve()
+        .await?;
+    let public_key = match recipient {
+        PublicAsymmetricKey::SodiumOxideCurve25519(k) => k,
+        PublicAsymmetricKey::SodiumOxideCurve25519XChaCha20(k) => {
+            SodiumOxideCurve25519PublicAsymmetricKey {
+                public_key: k.public_key,
+            }
+        }
+        PublicAsymmetricKey::SodiumOxideEd25519(k) => k.to_curve25519()?,
+        PublicAsymmetricKey::RingEd25519(_) => return Err(CryptoError::UnsupportedKeyAlgorithm),
+    };
+    public_key.seal_anonymous(plaintext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::seal_to_path;
+    use crate::{
+        key::sodiumoxide::{
+            SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideEd25519PublicAsymmetricKey,
+        },
+        ByteSource, CryptoError, MemoryStorer, Storer,
+    };
+
+    #[tokio::test]
+    async fn test_seal_to_path() {
+        let storer = MemoryStorer::new();
+        let (secret_entry, public_entry) =
+            SodiumOxideCurve25519PublicAsymmetricKey::new_entry_pair(
+                ".keys.recipient.secret.".to_owned(),
+                ".keys.recipient.public.".to_owned(),
+            )
+            .unwrap();
+        storer.create(public_entry).await.unwrap();
+
+        let plaintext: ByteSource = b"hello, world!".as_ref().into();
+        let ciphertext = seal_to_path(&storer, ".keys.recipient.public.", &plaintext)
+            .await
+            .unwrap();
+        let secret_key = secret_entry.take_resolve().await.unwrap();
+        assert_eq!(
+            secret_key
+                .open_anonymous(&ciphertext)
+                .unwrap()
+                .get()
+                .unwrap(),
+            b"hello, world!"
+        );
+
+        let (signing_entry, verifying_entry) =
+            SodiumOxideEd25519PublicAsymmetricKey::new_entry_pair(
+                ".keys.signer.secret.".to_owned(),
+                ".keys.signer.public.".to_owned(),
+            )
+            .unwrap();
+        storer.create(verifying_entry).await.unwrap();
+        let ciphertext = seal_to_path(&storer, ".keys.signer.public.", &plaintext)
+            .await
+            .unwrap();
+        let signing_key = signing_entry.take_resolve().await.unwrap();
+        assert_eq!(
+            signing_key
+                .to_curve25519()
+                .unwrap()
+                .open_anonymous(&ciphertext)
+                .unwrap()
+                .get()
+                .unwrap(),
+            b"hello, world!"
+        );
+
+        match seal_to_path(&storer, ".keys.missing.", &plaintext).await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Sealing to a missing key should have failed"),
+        }
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
     AsymmetricKeyBuilder, Builder, ByteSource, CryptoError, HasAlgorithmIdentifier, HasBuilder,
-    HasByteSource, HasIndex, HasPublicKey, KeyBuilder, PublicAsymmetricKeyBuilder,
+    HasByteSource, HasFingerprint, HasIndex, HasPublicKey, KeyBuilder, PublicAsymmetricKeyBuilder,
     SecretAsymmetricKeyBuilder, Signer, StorableType, TypeBuilder, TypeBuilderContainer, Verifier,
 };
 use mongodb::bson::{self, Document};
@@ -11,10 +11,12 @@ use ring::{
 };
 use serde::{Deserialize, Serialize};
 use spki::AlgorithmIdentifier;
-use std::convert::TryFrom;
+use std::{convert::TryFrom, fmt};
+
+use super::RedactedBytes;
 
 // SECRET SIGNING KEY \\
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct RingEd25519SecretAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for RingEd25519SecretAsymmetricKeyBuilder {
@@ -52,12 +54,24 @@ impl From<RingEd25519SecretAsymmetricKeyBuilder> for TypeBuilder {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize)]
 pub struct RingEd25519SecretAsymmetricKey {
+    #[serde(skip)]
     secret_key: OnceCell<ExternalEd25519KeyPair>,
     pkcs8_doc: ByteSource,
 }
 
+impl fmt::Debug for RingEd25519SecretAsymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RingEd25519SecretAsymmetricKey")
+            .field(
+                "pkcs8_doc",
+                &RedactedBytes(self.pkcs8_doc.get().ok().map(|bytes| bytes.len())),
+            )
+            .finish()
+    }
+}
+
 impl StorableType for RingEd25519SecretAsymmetricKey {}
 
 impl Signer for RingEd25519SecretAsymmetricKey {
@@ -132,7 +146,7 @@ impl RingEd25519SecretAsymmetricKey {
 }
 
 // PUBLIC SIGNING KEY \\
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct RingEd25519PublicAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for RingEd25519PublicAsymmetricKeyBuilder {
@@ -153,6 +167,12 @@ impl Builder for RingEd25519PublicAsymmetricKeyBuilder {
 
     fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
         match bytes {
+            Some(bytes) if bytes.len() != RingEd25519PublicAsymmetricKey::KEYBYTES => {
+                Err(CryptoError::InvalidKeyLength {
+                    expected: RingEd25519PublicAsymmetricKey::KEYBYTES,
+                    actual: bytes.len(),
+                })
+            }
             Some(bytes) => Ok(RingEd25519PublicAsymmetricKey {
                 public_key: bytes.to_vec(),
             }),
@@ -172,7 +192,7 @@ impl From<RingEd25519PublicAsymmetricKeyBuilder> for TypeBuilder {
     }
 }
 
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug)]
 pub struct RingEd25519PublicAsymmetricKey {
     pub public_key: Vec<u8>,
 }
@@ -231,6 +251,12 @@ impl HasByteSource for RingEd25519PublicAsymmetricKey {
     }
 }
 
+impl HasFingerprint for RingEd25519PublicAsymmetricKey {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        Ok(super::fingerprint(&self.public_key))
+    }
+}
+
 impl HasAlgorithmIdentifier for RingEd25519PublicAsymmetricKey {
     fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
         AlgorithmIdentifier {
@@ -241,6 +267,8 @@ impl HasAlgorithmIdentifier for RingEd25519PublicAsymmetricKey {
 }
 
 impl RingEd25519PublicAsymmetricKey {
+    pub const KEYBYTES: usize = signature::ED25519_PUBLIC_KEY_LEN;
+
     pub fn new() -> Result<(Self, RingEd25519SecretAsymmetricKey), CryptoError> {
         let secret_key = RingEd25519SecretAsymmetricKey::new()?;
         let public_key = secret_key.get_secret_key()?.public_key().as_ref().to_vec();
@@ -269,8 +297,80 @@ impl HasAlgorithmIdentifier for RingEd25519SecretAsymmetricKey {
 
 #[cfg(test)]
 mod tests {
-    use crate::key::ring::{RingEd25519PublicAsymmetricKey, RingEd25519PublicAsymmetricKeyBuilder};
-    use crate::{Builder, ByteSource, CryptoError, VectorByteSource, Verifier};
+    use crate::key::ring::{
+        RingEd25519PublicAsymmetricKey, RingEd25519PublicAsymmetricKeyBuilder,
+        RingEd25519SecretAsymmetricKey,
+    };
+    use crate::{
+        Builder, ByteSource, CryptoError, Entry, HasPublicKey, Signer, ToEntry, VectorByteSource,
+        Verifier,
+    };
+
+    #[test]
+    fn test_ringed25519secretasymmetrickey_debug_is_redacted() {
+        let secret_key = RingEd25519SecretAsymmetricKey::new().unwrap();
+        let debug = format!("{:?}", secret_key);
+        assert!(debug.contains("redacted"));
+        assert!(!debug.contains(&format!("{:?}", secret_key.pkcs8_doc.get().unwrap())));
+    }
+
+    #[tokio::test]
+    async fn test_ringed25519secretasymmetrickey_entry_serde_roundtrip() {
+        let secret_key = RingEd25519SecretAsymmetricKey::new().unwrap();
+        let signature = secret_key.sign("hello, world!".into()).unwrap();
+        let entry = secret_key
+            .to_unsealed_entry(".keys.ring.".to_owned())
+            .unwrap();
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let entry: Entry<RingEd25519SecretAsymmetricKey> = serde_json::from_str(&json).unwrap();
+        let secret_key = entry.take_resolve().await.unwrap();
+        assert_eq!(
+            secret_key
+                .sign("hello, world!".into())
+                .unwrap()
+                .get()
+                .unwrap(),
+            signature.get().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_ringed25519keys_serde_roundtrip() {
+        let secret_key = RingEd25519SecretAsymmetricKey::new().unwrap();
+        let public_key = secret_key.public_key().unwrap();
+        let signature = secret_key.sign("hello, world!".into()).unwrap();
+
+        let secret_key: RingEd25519SecretAsymmetricKey =
+            serde_json::from_str(&serde_json::to_string(&secret_key).unwrap()).unwrap();
+        let public_key: RingEd25519PublicAsymmetricKey =
+            serde_json::from_str(&serde_json::to_string(&public_key).unwrap()).unwrap();
+        assert_eq!(
+            secret_key
+                .sign("hello, world!".into())
+                .unwrap()
+                .get()
+                .unwrap(),
+            signature.get().unwrap()
+        );
+        public_key
+            .verify("hello, world!".into(), signature)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_ringed25519publicasymmetrickeybuilder_build_invalid_length() {
+        let rpakb = RingEd25519PublicAsymmetricKeyBuilder {};
+        for len in [0, RingEd25519PublicAsymmetricKey::KEYBYTES - 1, 64].iter() {
+            match rpakb.build(Some(&vec![0u8; *len])) {
+                Err(CryptoError::InvalidKeyLength { expected, actual }) => {
+                    assert_eq!(expected, RingEd25519PublicAsymmetricKey::KEYBYTES);
+                    assert_eq!(actual, *len);
+                }
+                _ => panic!("Building a {}-byte public key should have failed", len),
+            }
+        }
+    }
 
     #[test]
     fn test_ringed25519publicasymmetrickey_verify() {
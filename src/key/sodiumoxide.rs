@@ -1,16 +1,19 @@
 use crate::{
-    nonce::sodiumoxide::{SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce},
+    nonce::sodiumoxide::{
+        SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce, SodiumOxideXChaCha20AsymmetricNonce,
+    },
     Algorithm, AsymmetricKeyBuilder, Builder, ByteAlgorithm, ByteSource, CryptoError, Entry,
-    HasBuilder, HasByteSource, HasIndex, HasPublicKey, KeyBuilder, PublicAsymmetricKeyBuilder,
-    PublicAsymmetricSealer, PublicAsymmetricUnsealer, SecretAsymmetricKeyBuilder,
-    SecretAsymmetricSealer, SecretAsymmetricUnsealer, Signer, StorableType, SymmetricKeyBuilder,
-    SymmetricSealer, SymmetricUnsealer, ToPublicAsymmetricByteAlgorithm,
-    ToSecretAsymmetricByteAlgorithm, ToSymmetricByteAlgorithm, TypeBuilder, TypeBuilderContainer,
-    VectorByteSource, Verifier,
+    EntryPath, HasBuilder, HasByteSource, HasIndex, HasPublicKey, KeyBuilder,
+    PublicAsymmetricKeyBuilder, PublicAsymmetricSealer, PublicAsymmetricUnsealer,
+    SecretAsymmetricKeyBuilder, SecretAsymmetricSealer, SecretAsymmetricUnsealer, Signer,
+    StorableType, SymmetricKeyBuilder, SymmetricSealer, SymmetricUnsealer, ToEntry,
+    ToPublicAsymmetricByteAlgorithm, ToSecretAsymmetricByteAlgorithm, ToSymmetricByteAlgorithm,
+    TypeBuilder, TypeBuilderContainer, VectorByteSource, Verifier,
 };
 use async_trait::async_trait;
 use futures::Future;
 use mongodb::bson::{self, Document};
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use sodiumoxide::crypto::{
     box_::{
@@ -22,6 +25,7 @@ use sodiumoxide::crypto::{
             SECRETKEYBYTES as EXTERNALSODIUMOXIDECURVE25519SECRETASYMMETRICKEYBYTES,
         },
     },
+    sealedbox,
     secretbox::{
         self,
         xsalsa20poly1305::{
@@ -29,6 +33,7 @@ use sodiumoxide::crypto::{
             KEYBYTES as EXTERNALSODIUMOXIDESYMMETRICKEYBYTES,
         },
     },
+    secretstream,
     sign::ed25519::{
         self, PublicKey as ExternalSodiumOxideEd25519PublicAsymmetricKey,
         SecretKey as ExternalSodiumOxideEd25519SecretAsymmetricKey, Signature,
@@ -39,14 +44,22 @@ use sodiumoxide::crypto::{
     sign::{self, Seed},
 };
 use spki::AlgorithmIdentifier;
-use std::{boxed::Box, convert::TryFrom};
+use std::{
+    boxed::Box,
+    convert::TryFrom,
+    fmt,
+    io::{Read, Write},
+};
 
-use super::HasAlgorithmIdentifier;
+use super::{
+    CommittingSymmetricSealer, CommittingSymmetricUnsealer, FramedSymmetricSealer,
+    FramedSymmetricUnsealer, FromSeed, HasAlgorithmIdentifier, HasFingerprint, RedactedBytes,
+};
 use sodiumoxide::crypto::sign::Verifier as SodiumOxideVerifier;
 use std::convert::TryInto;
 
 // SYMMETRIC KEY \\
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SodiumOxideSymmetricKeyAlgorithm {
     pub key: Box<Entry<SodiumOxideSymmetricKey>>,
     pub nonce: SodiumOxideSymmetricNonce,
@@ -69,7 +82,7 @@ impl Algorithm for SodiumOxideSymmetricKeyAlgorithm {
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+#[derive(Serialize, Deserialize, Debug, Copy, Clone, Default)]
 pub struct SodiumOxideSymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for SodiumOxideSymmetricKeyBuilder {
@@ -103,17 +116,48 @@ impl Builder for SodiumOxideSymmetricKeyBuilder {
     }
 }
 
+/// Fewest bits that must be set in symmetric key material accepted by `build_checked`.
+/// A random 256-bit key has 128 bits set on average, and falls below 64 with negligible
+/// probability.
+pub const MIN_KEY_HAMMING_WEIGHT: u32 = 64;
+
+impl SodiumOxideSymmetricKeyBuilder {
+    /// Builds the key like `build`, but rejects key material that is obviously not
+    /// random with `CryptoError::WeakKeyMaterial`: bytes that are all the same value, or
+    /// that have fewer than `MIN_KEY_HAMMING_WEIGHT` bits set. Such keys almost always
+    /// come from a caller bug, like passing an uninitialized buffer.
+    pub fn build_checked(
+        &self,
+        bytes: Option<&[u8]>,
+    ) -> Result<SodiumOxideSymmetricKey, CryptoError> {
+        let key = self.build(bytes)?;
+        let key_bytes = key.key.as_ref();
+        let hamming_weight: u32 = key_bytes.iter().map(|b| b.count_ones()).sum();
+        if key_bytes.iter().all(|b| *b == key_bytes[0]) || hamming_weight < MIN_KEY_HAMMING_WEIGHT {
+            return Err(CryptoError::WeakKeyMaterial);
+        }
+        Ok(key)
+    }
+}
+
 impl From<SodiumOxideSymmetricKeyBuilder> for TypeBuilder {
     fn from(b: SodiumOxideSymmetricKeyBuilder) -> TypeBuilder {
         TypeBuilder::Key(KeyBuilder::Symmetric(SymmetricKeyBuilder::SodiumOxide(b)))
     }
 }
 
-#[derive(Debug)]
 pub struct SodiumOxideSymmetricKey {
     pub key: ExternalSodiumOxideSymmetricKey,
 }
 
+impl fmt::Debug for SodiumOxideSymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SodiumOxideSymmetricKey")
+            .field("key", &RedactedBytes(Some(self.key.as_ref().len())))
+            .finish()
+    }
+}
+
 #[async_trait]
 impl ToSymmetricByteAlgorithm for SodiumOxideSymmetricKey {
     type Key = Self;
@@ -188,6 +232,94 @@ impl SymmetricUnsealer for SodiumOxideSymmetricKey {
     }
 }
 
+/// Label authenticated under the key to produce its commitment
+const KEY_COMMITMENT_LABEL: &[u8] = b"redact-crypto symmetric key commitment";
+const KEY_COMMITMENT_BYTES: usize = 32;
+
+/// Computes a collision-resistant commitment to a symmetric key as HMAC-SHA256 of a fixed
+/// label under the key
+fn key_commitment(key: &ExternalSodiumOxideSymmetricKey) -> ::ring::hmac::Tag {
+    let hmac_key = ::ring::hmac::Key::new(::ring::hmac::HMAC_SHA256, key.as_ref());
+    ::ring::hmac::sign(&hmac_key, KEY_COMMITMENT_LABEL)
+}
+
+impl CommittingSymmetricSealer for SodiumOxideSymmetricKey {
+    fn seal_committing(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<(Self::SealedOutput, Self::Nonce), CryptoError> {
+        let (ciphertext, nonce) = self.seal(plaintext, nonce)?;
+        let mut committed = key_commitment(&self.key).as_ref().to_vec();
+        committed.extend_from_slice(ciphertext.get()?);
+        let committed_byte_source =
+            VectorByteSource::new(Some(&committed), *plaintext.get_last_modified()?).into();
+        Ok((committed_byte_source, nonce))
+    }
+}
+
+impl CommittingSymmetricUnsealer for SodiumOxideSymmetricKey {
+    fn unseal_committing(
+        &self,
+        ciphertext: &ByteSource,
+        nonce: &Self::Nonce,
+    ) -> Result<Self::UnsealedOutput, CryptoError> {
+        let committed = ciphertext.get()?;
+        if committed.len() < KEY_COMMITMENT_BYTES {
+            return Err(CryptoError::CiphertextFailedVerification);
+        }
+        let (commitment, ciphertext_bytes) = committed.split_at(KEY_COMMITMENT_BYTES);
+        let hmac_key = ::ring::hmac::Key::new(::ring::hmac::HMAC_SHA256, self.key.as_ref());
+        ::ring::hmac::verify(&hmac_key, KEY_COMMITMENT_LABEL, commitment)
+            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        self.unseal(
+            &VectorByteSource::new(Some(ciphertext_bytes), *ciphertext.get_last_modified()?).into(),
+            nonce,
+        )
+    }
+}
+
+/// Version of the frame layout written by `seal_framed`
+const FRAME_VERSION: u8 = 1;
+/// Algorithm byte identifying XSalsa20-Poly1305 secretbox frames
+const FRAME_ALGORITHM_SECRETBOX: u8 = 1;
+const FRAME_HEADER_BYTES: usize = 2;
+
+impl FramedSymmetricSealer for SodiumOxideSymmetricKey {
+    fn seal_framed(
+        &self,
+        plaintext: &ByteSource,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<Self::SealedOutput, CryptoError> {
+        let (ciphertext, nonce) = self.seal(plaintext, nonce)?;
+        let mut frame = vec![FRAME_VERSION, FRAME_ALGORITHM_SECRETBOX];
+        frame.extend_from_slice(nonce.nonce.as_ref());
+        frame.extend_from_slice(ciphertext.get()?);
+        Ok(VectorByteSource::new(Some(&frame), *plaintext.get_last_modified()?).into())
+    }
+}
+
+impl FramedSymmetricUnsealer for SodiumOxideSymmetricKey {
+    fn unseal_framed(&self, frame: &ByteSource) -> Result<Self::UnsealedOutput, CryptoError> {
+        let frame_bytes = frame.get()?;
+        if frame_bytes.len() < FRAME_HEADER_BYTES + secretbox::NONCEBYTES + secretbox::MACBYTES
+            || frame_bytes[0] != FRAME_VERSION
+            || frame_bytes[1] != FRAME_ALGORITHM_SECRETBOX
+        {
+            return Err(CryptoError::InvalidFrame);
+        }
+        let (nonce, ciphertext_bytes) =
+            frame_bytes[FRAME_HEADER_BYTES..].split_at(secretbox::NONCEBYTES);
+        let nonce = SodiumOxideSymmetricNonce {
+            nonce: secretbox::Nonce::from_slice(nonce).ok_or(CryptoError::InvalidFrame)?,
+        };
+        self.unseal(
+            &VectorByteSource::new(Some(ciphertext_bytes), *frame.get_last_modified()?).into(),
+            &nonce,
+        )
+    }
+}
+
 impl HasIndex for SodiumOxideSymmetricKey {
     type Index = Document;
 
@@ -230,10 +362,241 @@ impl SodiumOxideSymmetricKey {
             key: secretbox::gen_key(),
         }
     }
+
+    /// Deterministically derives the child key at `index` with HKDF-SHA256, using this
+    /// key as the input keying material and the big-endian index as the info. The parent
+    /// key cannot be recovered from its children.
+    pub fn derive_child(&self, index: u32) -> SodiumOxideSymmetricKey {
+        let prk = ::ring::hkdf::Salt::new(::ring::hkdf::HKDF_SHA256, KEY_DERIVATION_SALT)
+            .extract(self.key.as_ref());
+        let info = index.to_be_bytes();
+        let mut child = secretbox::Key([0; secretbox::KEYBYTES]);
+        // Expanding to a single SHA256 block of output cannot fail
+        prk.expand(&[&info[..]], DerivedKeyLength)
+            .and_then(|okm| okm.fill(&mut child.0))
+            .expect("HKDF-SHA256 output length is within bounds");
+        SodiumOxideSymmetricKey { key: child }
+    }
+
+    /// Derives a key down a multi-level path, deriving the child at each index from the
+    /// key derived at the previous level. An empty path yields a copy of this key.
+    pub fn derive_path(&self, path: &[u32]) -> SodiumOxideSymmetricKey {
+        path.iter().fold(
+            SodiumOxideSymmetricKey {
+                key: self.key.clone(),
+            },
+            |key, index| key.derive_child(*index),
+        )
+    }
+
+    /// Seals everything read from `reader` into `writer` in chunks of `chunk_size` bytes
+    /// using libsodium's secretstream construction, which authenticates the order of the
+    /// chunks and the end of the stream. Each sealed chunk is written prefixed with its
+    /// length as a big-endian u32. If given, `progress` is called after every chunk with
+    /// the number of plaintext bytes processed so far and the caller-provided `total`.
+    pub fn seal_stream<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        chunk_size: usize,
+        total: Option<u64>,
+        progress: Option<&dyn Fn(u64, Option<u64>)>,
+    ) -> Result<(), CryptoError> {
+        let key = self.stream_key()?;
+        let (mut stream, header) =
+            secretstream::Stream::init_push(&key).map_err(|_| stream_error("init_push"))?;
+        writer.write_all(header.as_ref()).map_err(io_error)?;
+
+        let mut processed = 0u64;
+        let mut current = read_chunk(&mut reader, chunk_size)?;
+        loop {
+            let next = if current.len() == chunk_size {
+                read_chunk(&mut reader, chunk_size)?
+            } else {
+                vec![]
+            };
+            let tag = if next.is_empty() {
+                secretstream::Tag::Final
+            } else {
+                secretstream::Tag::Message
+            };
+            let sealed_chunk = stream
+                .push(&current, None, tag)
+                .map_err(|_| stream_error("push"))?;
+            writer
+                .write_all(&(sealed_chunk.len() as u32).to_be_bytes())
+                .and_then(|_| writer.write_all(&sealed_chunk))
+                .map_err(io_error)?;
+
+            processed += current.len() as u64;
+            if let Some(progress) = progress {
+                progress(processed, total);
+            }
+            if tag == secretstream::Tag::Final {
+                return Ok(());
+            }
+            current = next;
+        }
+    }
+
+    /// Unseals a stream produced by `seal_stream` with the same `chunk_size`, writing the
+    /// plaintext to `writer`. Fails with `CiphertextFailedVerification` if any chunk was
+    /// modified, reordered or if the stream was truncated, and before allocating a buffer
+    /// if a chunk's length prefix exceeds what sealing `chunk_size` bytes can produce. If
+    /// given, `progress` is called after every chunk with the number of ciphertext bytes
+    /// processed so far and the caller-provided `total`.
+    pub fn unseal_stream<R: Read, W: Write>(
+        &self,
+        mut reader: R,
+        mut writer: W,
+        chunk_size: usize,
+        total: Option<u64>,
+        progress: Option<&dyn Fn(u64, Option<u64>)>,
+    ) -> Result<(), CryptoError> {
+        let key = self.stream_key()?;
+        let mut header = [0u8; secretstream::HEADERBYTES];
+        reader
+            .read_exact(&mut header)
+            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        let header = secretstream::Header::from_slice(&header)
+            .ok_or(CryptoError::CiphertextFailedVerification)?;
+        let mut stream = secretstream::Stream::init_pull(&header, &key)
+            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+
+        let max_sealed_len = chunk_size.saturating_add(secretstream::ABYTES);
+        let mut processed = secretstream::HEADERBYTES as u64;
+        loop {
+            let mut len = [0u8; 4];
+            reader
+                .read_exact(&mut len)
+                .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+            let sealed_len = u32::from_be_bytes(len) as usize;
+            if sealed_len > max_sealed_len {
+                return Err(CryptoError::CiphertextFailedVerification);
+            }
+            let mut sealed_chunk = vec![0u8; sealed_len];
+            reader
+                .read_exact(&mut sealed_chunk)
+                .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+            let (chunk, tag) = stream
+                .pull(&sealed_chunk, None)
+                .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+            writer.write_all(&chunk).map_err(io_error)?;
+
+            processed += (len.len() + sealed_chunk.len()) as u64;
+            if let Some(progress) = progress {
+                progress(processed, total);
+            }
+            if tag == secretstream::Tag::Final {
+                return Ok(());
+            }
+        }
+    }
+
+    fn stream_key(&self) -> Result<secretstream::Key, CryptoError> {
+        secretstream::Key::from_slice(self.key.as_ref()).ok_or(CryptoError::InvalidKeyLength {
+            expected: secretstream::KEYBYTES,
+            actual: self.key.as_ref().len(),
+        })
+    }
+}
+
+/// Salt separating child key derivation from any other use of HKDF with the same key
+const KEY_DERIVATION_SALT: &[u8] = b"redact-crypto symmetric key derivation";
+
+/// Output length of child key derivation, as required by ring's HKDF interface
+struct DerivedKeyLength;
+
+impl ::ring::hkdf::KeyType for DerivedKeyLength {
+    fn len(&self) -> usize {
+        secretbox::KEYBYTES
+    }
+}
+
+/// Reads up to `chunk_size` bytes, only returning fewer if the reader is exhausted
+fn read_chunk<R: Read>(reader: &mut R, chunk_size: usize) -> Result<Vec<u8>, CryptoError> {
+    let mut chunk = Vec::with_capacity(chunk_size);
+    reader
+        .take(chunk_size as u64)
+        .read_to_end(&mut chunk)
+        .map_err(io_error)?;
+    Ok(chunk)
+}
+
+fn io_error(e: std::io::Error) -> CryptoError {
+    CryptoError::InternalError {
+        source: Box::new(e),
+    }
+}
+
+fn stream_error(operation: &str) -> CryptoError {
+    io_error(std::io::Error::new(
+        std::io::ErrorKind::Other,
+        format!("secretstream {} failed", operation),
+    ))
+}
+
+impl FromSeed for SodiumOxideSymmetricKey {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError> {
+        Ok(SodiumOxideSymmetricKey {
+            key: ExternalSodiumOxideSymmetricKey::from_slice(seed).ok_or(
+                CryptoError::InvalidSeedLength {
+                    expected: SodiumOxideSymmetricKey::KEYBYTES,
+                    actual: seed.len(),
+                },
+            )?,
+        })
+    }
+}
+
+// PRECOMPUTATION HELPERS \\
+fn seal_precomputed(
+    plaintext: &ByteSource,
+    nonce: &SodiumOxideAsymmetricNonce,
+    precomputed_key: &box_::PrecomputedKey,
+) -> Result<ByteSource, CryptoError> {
+    let ciphertext = box_::seal_precomputed(plaintext.get()?, &nonce.nonce, precomputed_key);
+    Ok(VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into())
+}
+
+fn open_precomputed(
+    ciphertext: &ByteSource,
+    nonce: &SodiumOxideAsymmetricNonce,
+    precomputed_key: &box_::PrecomputedKey,
+) -> Result<ByteSource, CryptoError> {
+    let plaintext = box_::open_precomputed(ciphertext.get()?, &nonce.nonce, precomputed_key)
+        .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+    Ok(VectorByteSource::new(Some(&plaintext), *ciphertext.get_last_modified()?).into())
+}
+
+/// Seals each plaintext under its own freshly generated nonce using a single precomputed key
+fn seal_many_precomputed(
+    plaintexts: &[ByteSource],
+    precomputed_key: &box_::PrecomputedKey,
+) -> Result<Vec<(ByteSource, SodiumOxideAsymmetricNonce)>, CryptoError> {
+    plaintexts
+        .iter()
+        .map(|plaintext| {
+            let nonce = SodiumOxideAsymmetricNonce {
+                nonce: box_::gen_nonce(),
+            };
+            Ok((seal_precomputed(plaintext, &nonce, precomputed_key)?, nonce))
+        })
+        .collect()
+}
+
+fn open_many_precomputed(
+    ciphertexts: &[(ByteSource, SodiumOxideAsymmetricNonce)],
+    precomputed_key: &box_::PrecomputedKey,
+) -> Result<Vec<ByteSource>, CryptoError> {
+    ciphertexts
+        .iter()
+        .map(|(ciphertext, nonce)| open_precomputed(ciphertext, nonce, precomputed_key))
+        .collect()
 }
 
 // SECRET ASYMMETRIC KEY \\
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SodiumOxideSecretAsymmetricKeyAlgorithm {
     pub secret_key: Box<Entry<SodiumOxideCurve25519SecretAsymmetricKey>>,
     pub nonce: SodiumOxideAsymmetricNonce,
@@ -246,26 +609,40 @@ impl Algorithm for SodiumOxideSecretAsymmetricKeyAlgorithm {
     type Output = ByteSource;
 
     async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
-        let secret_key = self.secret_key.resolve().await?;
-        let public_key = match self.public_key {
-            Some(ref public_key) => Ok::<_, CryptoError>(Some(public_key.resolve().await?)),
-            None => Ok(None),
-        }?;
+        let (secret_key, public_key) = self.resolve_keys().await?;
         Ok(secret_key.unseal(&source, public_key, &self.nonce)?)
     }
 
     async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
-        let secret_key = self.secret_key.resolve().await?;
-        let public_key = match self.public_key {
-            Some(ref public_key) => Ok::<_, CryptoError>(Some(public_key.resolve().await?)),
-            None => Ok(None),
-        }?;
+        let (secret_key, public_key) = self.resolve_keys().await?;
         let (source, _) = secret_key.seal(&source, public_key, Some(&self.nonce))?;
         Ok(source)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+impl SodiumOxideSecretAsymmetricKeyAlgorithm {
+    /// Resolves the secret and public keys concurrently, since either may be a
+    /// reference requiring a round trip to its storer
+    async fn resolve_keys(
+        &self,
+    ) -> Result<
+        (
+            &SodiumOxideCurve25519SecretAsymmetricKey,
+            Option<&SodiumOxideCurve25519PublicAsymmetricKey>,
+        ),
+        CryptoError,
+    > {
+        let public_key = async {
+            match self.public_key {
+                Some(ref public_key) => public_key.resolve().await.map(Some),
+                None => Ok(None),
+            }
+        };
+        futures::try_join!(self.secret_key.resolve(), public_key)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct SodiumOxideCurve25519SecretAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for SodiumOxideCurve25519SecretAsymmetricKeyBuilder {
@@ -286,13 +663,14 @@ impl Builder for SodiumOxideCurve25519SecretAsymmetricKeyBuilder {
 
     fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
         match bytes {
-            Some(bytes) => Ok(SodiumOxideCurve25519SecretAsymmetricKey {
-                secret_key: ExternalSodiumOxideCurve25519SecretAsymmetricKey::from_slice(&bytes)
-                    .ok_or(CryptoError::InvalidKeyLength {
+            Some(bytes) => Ok(SodiumOxideCurve25519SecretAsymmetricKey::from_external(
+                ExternalSodiumOxideCurve25519SecretAsymmetricKey::from_slice(&bytes).ok_or(
+                    CryptoError::InvalidKeyLength {
                         expected: SodiumOxideCurve25519SecretAsymmetricKey::KEYBYTES,
                         actual: bytes.len(),
-                    })?,
-            }),
+                    },
+                )?,
+            )),
             None => Ok(SodiumOxideCurve25519SecretAsymmetricKey::new()),
         }
     }
@@ -306,9 +684,26 @@ impl From<SodiumOxideCurve25519SecretAsymmetricKeyBuilder> for TypeBuilder {
     }
 }
 
-#[derive(Debug)]
 pub struct SodiumOxideCurve25519SecretAsymmetricKey {
     pub secret_key: ExternalSodiumOxideCurve25519SecretAsymmetricKey,
+    /// Shared key computed with the first counterparty this key was used with, `None`
+    /// standing for this key's own public key. Computing it costs an X25519 scalar
+    /// multiplication, so repeated operations with the same counterparty reuse it.
+    precomputed: OnceCell<(
+        Option<ExternalSodiumOxideCurve25519PublicAsymmetricKey>,
+        box_::PrecomputedKey,
+    )>,
+}
+
+impl fmt::Debug for SodiumOxideCurve25519SecretAsymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SodiumOxideCurve25519SecretAsymmetricKey")
+            .field(
+                "secret_key",
+                &RedactedBytes(Some(self.secret_key.as_ref().len())),
+            )
+            .finish()
+    }
 }
 
 #[async_trait]
@@ -365,18 +760,8 @@ impl SecretAsymmetricSealer for SodiumOxideCurve25519SecretAsymmetricKey {
             Some(n) => n,
             None => &new_nonce,
         };
-        let plaintext_bytes = plaintext.get()?;
-        let self_public_key = SodiumOxideCurve25519PublicAsymmetricKey {
-            public_key: self.secret_key.public_key(),
-        };
-        let public_key = match public_key {
-            Some(sopak) => sopak,
-            None => &self_public_key,
-        };
-        let precomputed_key = box_::precompute(&public_key.public_key, &self.secret_key);
-        let ciphertext = box_::seal_precomputed(plaintext_bytes, &nonce.nonce, &precomputed_key);
-        let cipher_byte_source =
-            VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into();
+        let precomputed_key = self.precompute(public_key);
+        let cipher_byte_source = seal_precomputed(plaintext, nonce, &precomputed_key)?;
         Ok((cipher_byte_source, nonce.to_owned()))
     }
 }
@@ -392,20 +777,8 @@ impl SecretAsymmetricUnsealer for SodiumOxideCurve25519SecretAsymmetricKey {
         public_key: Option<&Self::PublicKey>,
         nonce: &Self::Nonce,
     ) -> Result<Self::UnsealedOutput, CryptoError> {
-        let ciphertext_bytes = ciphertext.get()?;
-        let self_public_key = SodiumOxideCurve25519PublicAsymmetricKey {
-            public_key: self.secret_key.public_key(),
-        };
-        let public_key = match public_key {
-            Some(sopak) => sopak,
-            None => &self_public_key,
-        };
-        let precomputed_key = box_::precompute(&public_key.public_key, &self.secret_key);
-        let plaintext = box_::open_precomputed(ciphertext_bytes, &nonce.nonce, &precomputed_key)
-            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
-        let plaintext_byte_source =
-            VectorByteSource::new(Some(&plaintext), *ciphertext.get_last_modified()?).into();
-        Ok(plaintext_byte_source)
+        let precomputed_key = self.precompute(public_key);
+        open_precomputed(ciphertext, nonce, &precomputed_key)
     }
 }
 
@@ -457,7 +830,66 @@ impl SodiumOxideCurve25519SecretAsymmetricKey {
 
     pub fn new() -> Self {
         let (_, key) = box_::gen_keypair();
-        SodiumOxideCurve25519SecretAsymmetricKey { secret_key: key }
+        Self::from_external(key)
+    }
+
+    fn from_external(secret_key: ExternalSodiumOxideCurve25519SecretAsymmetricKey) -> Self {
+        SodiumOxideCurve25519SecretAsymmetricKey {
+            secret_key,
+            precomputed: OnceCell::new(),
+        }
+    }
+
+    /// Seals each plaintext to the given public key, or to this key's own public key if none
+    /// is given, computing the shared key only once. Every plaintext gets its own nonce.
+    pub fn seal_many(
+        &self,
+        plaintexts: &[ByteSource],
+        public_key: Option<&SodiumOxideCurve25519PublicAsymmetricKey>,
+    ) -> Result<Vec<(ByteSource, SodiumOxideAsymmetricNonce)>, CryptoError> {
+        seal_many_precomputed(plaintexts, &self.precompute(public_key))
+    }
+
+    /// Unseals each ciphertext with its nonce, computing the shared key only once
+    pub fn unseal_many(
+        &self,
+        ciphertexts: &[(ByteSource, SodiumOxideAsymmetricNonce)],
+        public_key: Option<&SodiumOxideCurve25519PublicAsymmetricKey>,
+    ) -> Result<Vec<ByteSource>, CryptoError> {
+        open_many_precomputed(ciphertexts, &self.precompute(public_key))
+    }
+
+    /// Opens a ciphertext produced by `SodiumOxideCurve25519PublicAsymmetricKey::seal_anonymous`
+    /// for this key's public key
+    pub fn open_anonymous(&self, ciphertext: &ByteSource) -> Result<ByteSource, CryptoError> {
+        let plaintext = sealedbox::open(
+            ciphertext.get()?,
+            &self.secret_key.public_key(),
+            &self.secret_key,
+        )
+        .map_err(|_| CryptoError::CiphertextFailedVerification)?;
+        Ok(VectorByteSource::new(Some(&plaintext), *ciphertext.get_last_modified()?).into())
+    }
+
+    /// Returns the shared key with the given public key, or with this key's own public
+    /// key if none is given. The key computed for the first counterparty is memoized and
+    /// reused, keys for any other counterparty are computed on every call.
+    fn precompute(
+        &self,
+        public_key: Option<&SodiumOxideCurve25519PublicAsymmetricKey>,
+    ) -> box_::PrecomputedKey {
+        let counterparty = public_key.map(|sopak| sopak.public_key);
+        let compute = || match counterparty {
+            Some(ref public_key) => box_::precompute(public_key, &self.secret_key),
+            None => box_::precompute(&self.secret_key.public_key(), &self.secret_key),
+        };
+        let (memoized_counterparty, memoized_key) =
+            self.precomputed.get_or_init(|| (counterparty, compute()));
+        if *memoized_counterparty == counterparty {
+            memoized_key.clone()
+        } else {
+            compute()
+        }
     }
 
     pub fn get_signing_key(&self) -> Result<SodiumOxideEd25519SecretAsymmetricKey, CryptoError> {
@@ -473,8 +905,21 @@ impl SodiumOxideCurve25519SecretAsymmetricKey {
     }
 }
 
+impl FromSeed for SodiumOxideCurve25519SecretAsymmetricKey {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError> {
+        let seed = box_::Seed::from_slice(seed).ok_or(CryptoError::InvalidSeedLength {
+            expected: box_::SEEDBYTES,
+            actual: seed.len(),
+        })?;
+        let (_, secret_key) = box_::keypair_from_seed(&seed);
+        Ok(SodiumOxideCurve25519SecretAsymmetricKey::from_external(
+            secret_key,
+        ))
+    }
+}
+
 // PUBLIC ASYMMETRIC KEY \\
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SodiumOxidePublicAsymmetricKeyAlgorithm {
     pub public_key: Box<Entry<SodiumOxideCurve25519PublicAsymmetricKey>>,
     pub nonce: SodiumOxideAsymmetricNonce,
@@ -487,20 +932,20 @@ impl Algorithm for SodiumOxidePublicAsymmetricKeyAlgorithm {
     type Output = ByteSource;
 
     async fn unseal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
-        let secret_key = self.secret_key.resolve().await?;
-        let public_key = self.public_key.resolve().await?;
+        let (secret_key, public_key) =
+            futures::try_join!(self.secret_key.resolve(), self.public_key.resolve())?;
         Ok(public_key.unseal(source, secret_key, &self.nonce)?)
     }
 
     async fn seal(&self, source: &Self::Source) -> Result<Self::Output, CryptoError> {
-        let secret_key = self.secret_key.resolve().await?;
-        let public_key = self.public_key.resolve().await?;
+        let (secret_key, public_key) =
+            futures::try_join!(self.secret_key.resolve(), self.public_key.resolve())?;
         let (source, _) = public_key.seal(source, secret_key, Some(&self.nonce))?;
         Ok(source)
     }
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for SodiumOxideCurve25519PublicAsymmetricKeyBuilder {
@@ -603,11 +1048,8 @@ impl PublicAsymmetricSealer for SodiumOxideCurve25519PublicAsymmetricKey {
             Some(n) => n,
             None => &new_nonce,
         };
-        let plaintext_bytes = plaintext.get()?;
-        let precomputed_key = box_::precompute(&self.public_key, &secret_key.secret_key);
-        let ciphertext = box_::seal_precomputed(plaintext_bytes, &nonce.nonce, &precomputed_key);
-        let cipher_byte_source =
-            VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into();
+        let precomputed_key = secret_key.precompute(Some(self));
+        let cipher_byte_source = seal_precomputed(plaintext, nonce, &precomputed_key)?;
         Ok((cipher_byte_source, nonce.to_owned()))
     }
 }
@@ -623,13 +1065,8 @@ impl PublicAsymmetricUnsealer for SodiumOxideCurve25519PublicAsymmetricKey {
         secret_key: &Self::SecretKey,
         nonce: &Self::Nonce,
     ) -> Result<Self::UnsealedOutput, CryptoError> {
-        let ciphertext_bytes = ciphertext.get()?;
-        let precomputed_key = box_::precompute(&self.public_key, &secret_key.secret_key);
-        let plaintext = box_::open_precomputed(ciphertext_bytes, &nonce.nonce, &precomputed_key)
-            .map_err(|_| CryptoError::CiphertextFailedVerification)?;
-        let plaintext_byte_source =
-            VectorByteSource::new(Some(&plaintext), *ciphertext.get_last_modified()?).into();
-        Ok(plaintext_byte_source)
+        let precomputed_key = secret_key.precompute(Some(self));
+        open_precomputed(ciphertext, nonce, &precomputed_key)
     }
 }
 
@@ -670,6 +1107,12 @@ impl HasByteSource for SodiumOxideCurve25519PublicAsymmetricKey {
     }
 }
 
+impl HasFingerprint for SodiumOxideCurve25519PublicAsymmetricKey {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        Ok(super::fingerprint(self.public_key.as_ref()))
+    }
+}
+
 impl HasAlgorithmIdentifier for SodiumOxideCurve25519PublicAsymmetricKey {
     fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
         AlgorithmIdentifier {
@@ -686,9 +1129,54 @@ impl SodiumOxideCurve25519PublicAsymmetricKey {
         let (public_key, secret_key) = box_::gen_keypair();
         (
             SodiumOxideCurve25519PublicAsymmetricKey { public_key },
-            SodiumOxideCurve25519SecretAsymmetricKey { secret_key },
+            SodiumOxideCurve25519SecretAsymmetricKey::from_external(secret_key),
         )
     }
+
+    /// Generates a keypair and returns both halves as unsealed entries at the given paths
+    pub fn new_entry_pair(
+        secret_path: EntryPath,
+        public_path: EntryPath,
+    ) -> Result<
+        (
+            Entry<SodiumOxideCurve25519SecretAsymmetricKey>,
+            Entry<SodiumOxideCurve25519PublicAsymmetricKey>,
+        ),
+        CryptoError,
+    > {
+        let (public_key, secret_key) = Self::new();
+        Ok((
+            secret_key.to_unsealed_entry(secret_path)?,
+            public_key.to_unsealed_entry(public_path)?,
+        ))
+    }
+
+    /// Seals each plaintext from the given secret key to this public key, computing the
+    /// shared key only once. Every plaintext gets its own nonce.
+    pub fn seal_many(
+        &self,
+        plaintexts: &[ByteSource],
+        secret_key: &SodiumOxideCurve25519SecretAsymmetricKey,
+    ) -> Result<Vec<(ByteSource, SodiumOxideAsymmetricNonce)>, CryptoError> {
+        seal_many_precomputed(plaintexts, &secret_key.precompute(Some(self)))
+    }
+
+    /// Unseals each ciphertext with its nonce, computing the shared key only once
+    pub fn unseal_many(
+        &self,
+        ciphertexts: &[(ByteSource, SodiumOxideAsymmetricNonce)],
+        secret_key: &SodiumOxideCurve25519SecretAsymmetricKey,
+    ) -> Result<Vec<ByteSource>, CryptoError> {
+        open_many_precomputed(ciphertexts, &secret_key.precompute(Some(self)))
+    }
+
+    /// Seals the plaintext to this public key without a sender identity. A fresh
+    /// ephemeral keypair is generated for every call and its public key is prepended
+    /// to the ciphertext, so only the recipient's secret key is needed to open it.
+    pub fn seal_anonymous(&self, plaintext: &ByteSource) -> Result<ByteSource, CryptoError> {
+        let ciphertext = sealedbox::seal(plaintext.get()?, &self.public_key);
+        Ok(VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into())
+    }
 }
 
 impl HasPublicKey for SodiumOxideCurve25519SecretAsymmetricKey {
@@ -710,8 +1198,386 @@ impl HasAlgorithmIdentifier for SodiumOxideCurve25519SecretAsymmetricKey {
     }
 }
 
+// XCHACHA20 SECRET ASYMMETRIC KEY \\
+// crypto_box_curve25519xchacha20poly1305 is not wrapped by sodiumoxide, so it is called
+// through libsodium directly. Its keys are plain X25519 keys, the same as `box_` keys.
+
+/// Number of bytes a crypto_box_curve25519xchacha20poly1305 ciphertext adds to its plaintext
+const XCHACHA20_BOX_MACBYTES: usize =
+    libsodium_sys::crypto_box_curve25519xchacha20poly1305_MACBYTES as usize;
+
+fn xchacha20_box_seal(
+    plaintext: &ByteSource,
+    nonce: &SodiumOxideXChaCha20AsymmetricNonce,
+    public_key: &ExternalSodiumOxideCurve25519PublicAsymmetricKey,
+    secret_key: &ExternalSodiumOxideCurve25519SecretAsymmetricKey,
+) -> Result<ByteSource, CryptoError> {
+    let plaintext_bytes = plaintext.get()?;
+    let mut ciphertext = vec![0u8; plaintext_bytes.len() + XCHACHA20_BOX_MACBYTES];
+    // Safety: the ciphertext buffer is sized for the plaintext plus the MAC, and the nonce
+    // and keys are fixed-size arrays of the lengths libsodium expects
+    let ret = unsafe {
+        libsodium_sys::crypto_box_curve25519xchacha20poly1305_easy(
+            ciphertext.as_mut_ptr(),
+            plaintext_bytes.as_ptr(),
+            plaintext_bytes.len() as u64,
+            nonce.nonce.as_ptr(),
+            public_key.as_ref().as_ptr(),
+            secret_key.as_ref().as_ptr(),
+        )
+    };
+    if ret != 0 {
+        return Err(io_error(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "crypto_box_curve25519xchacha20poly1305_easy failed",
+        )));
+    }
+    Ok(VectorByteSource::new(Some(&ciphertext), *plaintext.get_last_modified()?).into())
+}
+
+fn xchacha20_box_open(
+    ciphertext: &ByteSource,
+    nonce: &SodiumOxideXChaCha20AsymmetricNonce,
+    public_key: &ExternalSodiumOxideCurve25519PublicAsymmetricKey,
+    secret_key: &ExternalSodiumOxideCurve25519SecretAsymmetricKey,
+) -> Result<ByteSource, CryptoError> {
+    let ciphertext_bytes = ciphertext.get()?;
+    if ciphertext_bytes.len() < XCHACHA20_BOX_MACBYTES {
+        return Err(CryptoError::CiphertextFailedVerification);
+    }
+    let mut plaintext = vec![0u8; ciphertext_bytes.len() - XCHACHA20_BOX_MACBYTES];
+    // Safety: the ciphertext holds at least a MAC and the plaintext buffer is sized for
+    // what remains after it, and the nonce and keys are fixed-size arrays
+    let ret = unsafe {
+        libsodium_sys::crypto_box_curve25519xchacha20poly1305_open_easy(
+            plaintext.as_mut_ptr(),
+            ciphertext_bytes.as_ptr(),
+            ciphertext_bytes.len() as u64,
+            nonce.nonce.as_ptr(),
+            public_key.as_ref().as_ptr(),
+            secret_key.as_ref().as_ptr(),
+        )
+    };
+    if ret != 0 {
+        return Err(CryptoError::CiphertextFailedVerification);
+    }
+    Ok(VectorByteSource::new(Some(&plaintext), *ciphertext.get_last_modified()?).into())
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder {}
+
+impl TryFrom<TypeBuilderContainer> for SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder {
+    type Error = CryptoError;
+
+    fn try_from(builder: TypeBuilderContainer) -> Result<Self, Self::Error> {
+        match builder.0 {
+            TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Secret(
+                SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sosakb),
+            ))) => Ok(sosakb),
+            _ => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
+impl Builder for SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder {
+    type Output = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey;
+
+    fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
+        match bytes {
+            Some(bytes) => Ok(SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+                secret_key: ExternalSodiumOxideCurve25519SecretAsymmetricKey::from_slice(bytes)
+                    .ok_or(CryptoError::InvalidKeyLength {
+                        expected: SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::KEYBYTES,
+                        actual: bytes.len(),
+                    })?,
+            }),
+            None => Ok(SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::new()),
+        }
+    }
+}
+
+impl From<SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder> for TypeBuilder {
+    fn from(b: SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder) -> TypeBuilder {
+        TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Secret(
+            SecretAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(b),
+        )))
+    }
+}
+
+/// X25519 secret key sealing with XChaCha20-Poly1305 instead of XSalsa20-Poly1305, for
+/// interoperability with libsodium's crypto_box_curve25519xchacha20poly1305
+pub struct SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    pub secret_key: ExternalSodiumOxideCurve25519SecretAsymmetricKey,
+}
+
+impl fmt::Debug for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SodiumOxideCurve25519XChaCha20SecretAsymmetricKey")
+            .field(
+                "secret_key",
+                &RedactedBytes(Some(self.secret_key.as_ref().len())),
+            )
+            .finish()
+    }
+}
+
+impl StorableType for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {}
+
+impl SecretAsymmetricSealer for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    type SealedOutput = ByteSource;
+    type Nonce = SodiumOxideXChaCha20AsymmetricNonce;
+    type PublicKey = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey;
+
+    fn seal(
+        &self,
+        plaintext: &ByteSource,
+        public_key: Option<&Self::PublicKey>,
+        nonce: Option<&Self::Nonce>,
+    ) -> Result<(Self::SealedOutput, Self::Nonce), CryptoError> {
+        let nonce = match nonce {
+            Some(n) => n.to_owned(),
+            None => SodiumOxideXChaCha20AsymmetricNonce::new(),
+        };
+        let cipher_byte_source = match public_key {
+            Some(sopak) => {
+                xchacha20_box_seal(plaintext, &nonce, &sopak.public_key, &self.secret_key)
+            }
+            None => xchacha20_box_seal(
+                plaintext,
+                &nonce,
+                &self.secret_key.public_key(),
+                &self.secret_key,
+            ),
+        }?;
+        Ok((cipher_byte_source, nonce))
+    }
+}
+
+impl SecretAsymmetricUnsealer for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    type UnsealedOutput = ByteSource;
+    type Nonce = SodiumOxideXChaCha20AsymmetricNonce;
+    type PublicKey = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey;
+
+    fn unseal(
+        &self,
+        ciphertext: &ByteSource,
+        public_key: Option<&Self::PublicKey>,
+        nonce: &Self::Nonce,
+    ) -> Result<Self::UnsealedOutput, CryptoError> {
+        match public_key {
+            Some(sopak) => {
+                xchacha20_box_open(ciphertext, nonce, &sopak.public_key, &self.secret_key)
+            }
+            None => xchacha20_box_open(
+                ciphertext,
+                nonce,
+                &self.secret_key.public_key(),
+                &self.secret_key,
+            ),
+        }
+    }
+}
+
+impl HasIndex for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    type Index = Document;
+
+    fn get_index() -> Option<Self::Index> {
+        Some(bson::doc! {
+        "c": {
+            "builder": {
+        "t": "Key",
+        "c": {
+            "t": "Asymmetric",
+        "c": {
+            "t": "Secret",
+        "c": {
+        "t": "SodiumOxideCurve25519XChaCha20"
+        }
+        }
+        }
+            }
+        }
+            })
+    }
+}
+
+impl HasBuilder for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    type Builder = SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder;
+
+    fn builder(&self) -> Self::Builder {
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder {}
+    }
+}
+
+impl HasByteSource for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    fn byte_source(&self) -> ByteSource {
+        self.secret_key.as_ref().into()
+    }
+}
+
+impl Default for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    pub const KEYBYTES: usize = EXTERNALSODIUMOXIDECURVE25519SECRETASYMMETRICKEYBYTES;
+
+    pub fn new() -> Self {
+        let (_, key) = box_::gen_keypair();
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKey { secret_key: key }
+    }
+}
+
+impl FromSeed for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError> {
+        let key = SodiumOxideCurve25519SecretAsymmetricKey::from_seed(seed)?;
+        Ok(SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+            secret_key: key.secret_key,
+        })
+    }
+}
+
+impl HasPublicKey for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    type PublicKey = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey;
+
+    fn public_key(&self) -> Result<Self::PublicKey, CryptoError> {
+        Ok(SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+            public_key: self.secret_key.public_key(),
+        })
+    }
+}
+
+impl HasAlgorithmIdentifier for SodiumOxideCurve25519XChaCha20SecretAsymmetricKey {
+    fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
+        AlgorithmIdentifier {
+            oid: spki::ObjectIdentifier::new("1.3.101.110"),
+            parameters: None,
+        }
+    }
+}
+
+// XCHACHA20 PUBLIC ASYMMETRIC KEY \\
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
+pub struct SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder {}
+
+impl TryFrom<TypeBuilderContainer> for SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder {
+    type Error = CryptoError;
+
+    fn try_from(builder: TypeBuilderContainer) -> Result<Self, Self::Error> {
+        match builder.0 {
+            TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(
+                PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(sopakb),
+            ))) => Ok(sopakb),
+            _ => Err(CryptoError::NotDowncastable),
+        }
+    }
+}
+
+impl Builder for SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder {
+    type Output = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey;
+
+    fn build(&self, bytes: Option<&[u8]>) -> Result<Self::Output, CryptoError> {
+        match bytes {
+            Some(bytes) => Ok(SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+                public_key: ExternalSodiumOxideCurve25519PublicAsymmetricKey::from_slice(bytes)
+                    .ok_or(CryptoError::InvalidKeyLength {
+                        expected: SodiumOxideCurve25519XChaCha20PublicAsymmetricKey::KEYBYTES,
+                        actual: bytes.len(),
+                    })?,
+            }),
+            None => {
+                let (pk, _) = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey::new();
+                Ok(pk)
+            }
+        }
+    }
+}
+
+impl From<SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder> for TypeBuilder {
+    fn from(b: SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder) -> TypeBuilder {
+        TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(
+            PublicAsymmetricKeyBuilder::SodiumOxideCurve25519XChaCha20(b),
+        )))
+    }
+}
+
+/// Public half of a `SodiumOxideCurve25519XChaCha20SecretAsymmetricKey`
+#[derive(Debug)]
+pub struct SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    pub public_key: ExternalSodiumOxideCurve25519PublicAsymmetricKey,
+}
+
+impl StorableType for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {}
+
+impl HasIndex for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    type Index = Document;
+
+    fn get_index() -> Option<Self::Index> {
+        Some(bson::doc! {
+        "c": {
+            "builder": {
+        "t": "Key",
+        "c": {
+            "t": "Asymmetric",
+        "c": {
+            "t": "Public",
+        "c": {
+        "t": "SodiumOxideCurve25519XChaCha20"
+        }
+        }
+        }
+            }
+        }
+            })
+    }
+}
+
+impl HasBuilder for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    type Builder = SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder;
+
+    fn builder(&self) -> Self::Builder {
+        SodiumOxideCurve25519XChaCha20PublicAsymmetricKeyBuilder {}
+    }
+}
+
+impl HasByteSource for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    fn byte_source(&self) -> ByteSource {
+        self.public_key.as_ref().into()
+    }
+}
+
+impl HasFingerprint for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        Ok(super::fingerprint(self.public_key.as_ref()))
+    }
+}
+
+impl HasAlgorithmIdentifier for SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
+        AlgorithmIdentifier {
+            oid: spki::ObjectIdentifier::new("1.3.101.110"),
+            parameters: None,
+        }
+    }
+}
+
+impl SodiumOxideCurve25519XChaCha20PublicAsymmetricKey {
+    pub const KEYBYTES: usize = EXTERNALSODIUMOXIDECURVE25519PUBLICASYMMETRICKEYBYTES;
+
+    pub fn new() -> (Self, SodiumOxideCurve25519XChaCha20SecretAsymmetricKey) {
+        let (public_key, secret_key) = box_::gen_keypair();
+        (
+            SodiumOxideCurve25519XChaCha20PublicAsymmetricKey { public_key },
+            SodiumOxideCurve25519XChaCha20SecretAsymmetricKey { secret_key },
+        )
+    }
+}
+
 // SECRET SIGNING KEY \\
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct SodiumOxideEd25519SecretAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for SodiumOxideEd25519SecretAsymmetricKeyBuilder {
@@ -769,11 +1635,21 @@ impl From<SodiumOxideEd25519SecretAsymmetricKeyBuilder> for TypeBuilder {
     }
 }
 
-#[derive(Debug)]
 pub struct SodiumOxideEd25519SecretAsymmetricKey {
     pub secret_key: ExternalSodiumOxideEd25519SecretAsymmetricKey,
 }
 
+impl fmt::Debug for SodiumOxideEd25519SecretAsymmetricKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SodiumOxideEd25519SecretAsymmetricKey")
+            .field(
+                "secret_key",
+                &RedactedBytes(Some(self.secret_key.as_ref().len())),
+            )
+            .finish()
+    }
+}
+
 impl StorableType for SodiumOxideEd25519SecretAsymmetricKey {}
 
 impl Signer for SodiumOxideEd25519SecretAsymmetricKey {
@@ -827,6 +1703,41 @@ impl SodiumOxideEd25519SecretAsymmetricKey {
         let (_, secret_key) = sign::gen_keypair();
         SodiumOxideEd25519SecretAsymmetricKey { secret_key }
     }
+
+    /// Converts the signing key to the Curve25519 key for encryption of the same
+    /// identity. Its public key is the one returned by
+    /// `SodiumOxideEd25519PublicAsymmetricKey::to_curve25519`.
+    pub fn to_curve25519(&self) -> Result<SodiumOxideCurve25519SecretAsymmetricKey, CryptoError> {
+        let mut secret_key = [0u8; EXTERNALSODIUMOXIDECURVE25519SECRETASYMMETRICKEYBYTES];
+        // Safety: the output buffer is sized for a Curve25519 secret key, and the input is
+        // a fixed-size Ed25519 secret key
+        let ret = unsafe {
+            libsodium_sys::crypto_sign_ed25519_sk_to_curve25519(
+                secret_key.as_mut_ptr(),
+                self.secret_key.as_ref().as_ptr(),
+            )
+        };
+        if ret != 0 {
+            return Err(io_error(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "crypto_sign_ed25519_sk_to_curve25519 failed",
+            )));
+        }
+        let key = SodiumOxideCurve25519SecretAsymmetricKeyBuilder {}.build(Some(&secret_key[..]));
+        sodiumoxide::utils::memzero(&mut secret_key);
+        key
+    }
+}
+
+impl FromSeed for SodiumOxideEd25519SecretAsymmetricKey {
+    fn from_seed(seed: &[u8; 32]) -> Result<Self, CryptoError> {
+        let seed = Seed::from_slice(seed).ok_or(CryptoError::InvalidSeedLength {
+            expected: EXTERNALSODIUMOXIDEED25519SEEDBYTES,
+            actual: seed.len(),
+        })?;
+        let (_, secret_key) = ed25519::keypair_from_seed(&seed);
+        Ok(SodiumOxideEd25519SecretAsymmetricKey { secret_key })
+    }
 }
 
 impl Default for SodiumOxideEd25519SecretAsymmetricKey {
@@ -836,7 +1747,7 @@ impl Default for SodiumOxideEd25519SecretAsymmetricKey {
 }
 
 // PUBLIC SIGNING KEY \\
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default)]
 pub struct SodiumOxideEd25519PublicAsymmetricKeyBuilder {}
 
 impl TryFrom<TypeBuilderContainer> for SodiumOxideEd25519PublicAsymmetricKeyBuilder {
@@ -942,6 +1853,12 @@ impl HasByteSource for SodiumOxideEd25519PublicAsymmetricKey {
     }
 }
 
+impl HasFingerprint for SodiumOxideEd25519PublicAsymmetricKey {
+    fn fingerprint(&self) -> Result<String, CryptoError> {
+        Ok(super::fingerprint(self.public_key.as_ref()))
+    }
+}
+
 impl HasAlgorithmIdentifier for SodiumOxideEd25519PublicAsymmetricKey {
     fn algorithm_identifier<'a>(&self) -> AlgorithmIdentifier<'a> {
         AlgorithmIdentifier {
@@ -961,6 +1878,46 @@ impl SodiumOxideEd25519PublicAsymmetricKey {
             SodiumOxideEd25519SecretAsymmetricKey { secret_key },
         )
     }
+
+    /// Generates a keypair and returns both halves as unsealed entries at the given paths
+    pub fn new_entry_pair(
+        secret_path: EntryPath,
+        public_path: EntryPath,
+    ) -> Result<
+        (
+            Entry<SodiumOxideEd25519SecretAsymmetricKey>,
+            Entry<SodiumOxideEd25519PublicAsymmetricKey>,
+        ),
+        CryptoError,
+    > {
+        let (public_key, secret_key) = Self::new();
+        Ok((
+            secret_key.to_unsealed_entry(secret_path)?,
+            public_key.to_unsealed_entry(public_path)?,
+        ))
+    }
+
+    /// Converts the verifying key to the Curve25519 public key for encryption to the same
+    /// identity, so that one identity key can serve both signing and encryption. Fails if
+    /// the key is not a valid point, such as one of small order.
+    pub fn to_curve25519(&self) -> Result<SodiumOxideCurve25519PublicAsymmetricKey, CryptoError> {
+        let mut public_key = [0u8; EXTERNALSODIUMOXIDECURVE25519PUBLICASYMMETRICKEYBYTES];
+        // Safety: the output buffer is sized for a Curve25519 public key, and the input is
+        // a fixed-size Ed25519 public key
+        let ret = unsafe {
+            libsodium_sys::crypto_sign_ed25519_pk_to_curve25519(
+                public_key.as_mut_ptr(),
+                self.public_key.as_ref().as_ptr(),
+            )
+        };
+        if ret != 0 {
+            return Err(io_error(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "crypto_sign_ed25519_pk_to_curve25519 failed",
+            )));
+        }
+        SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}.build(Some(&public_key[..]))
+    }
 }
 
 impl HasPublicKey for SodiumOxideEd25519SecretAsymmetricKey {
@@ -985,31 +1942,45 @@ impl HasAlgorithmIdentifier for SodiumOxideEd25519SecretAsymmetricKey {
 #[cfg(test)]
 mod tests {
     use super::{
-        SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideCurve25519PublicAsymmetricKeyBuilder,
-        SodiumOxideCurve25519SecretAsymmetricKey, SodiumOxideCurve25519SecretAsymmetricKeyBuilder,
-        SodiumOxideSymmetricKey, SodiumOxideSymmetricKeyBuilder,
+        key_commitment, SodiumOxideCurve25519PublicAsymmetricKey,
+        SodiumOxideCurve25519PublicAsymmetricKeyBuilder, SodiumOxideCurve25519SecretAsymmetricKey,
+        SodiumOxideCurve25519SecretAsymmetricKeyBuilder,
+        SodiumOxideCurve25519XChaCha20PublicAsymmetricKey,
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKey,
+        SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder,
+        SodiumOxideSecretAsymmetricKeyAlgorithm, SodiumOxideSymmetricKey,
+        SodiumOxideSymmetricKeyAlgorithm, SodiumOxideSymmetricKeyBuilder, FRAME_HEADER_BYTES,
+        FRAME_VERSION, KEY_COMMITMENT_BYTES,
     };
     use crate::key::sodiumoxide::{
         SodiumOxideEd25519PublicAsymmetricKey, SodiumOxideEd25519PublicAsymmetricKeyBuilder,
+        SodiumOxideEd25519SecretAsymmetricKey, SodiumOxideEd25519SecretAsymmetricKeyBuilder,
     };
     use crate::{
-        nonce::sodiumoxide::{SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce},
+        nonce::sodiumoxide::{
+            SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce,
+            SodiumOxideXChaCha20AsymmetricNonce,
+        },
+        storage::tests::MapStorer,
         storage::tests::MockIndexedStorer,
         storage::tests::MockStorer,
-        Algorithm, AsymmetricKeyBuilder, BoolDataBuilder, Builder, ByteSource, CryptoError, Data,
-        DataBuilder, HasBuilder, HasByteSource, HasIndex, HasPublicKey, KeyBuilder,
-        PublicAsymmetricKeyBuilder, PublicAsymmetricSealer, PublicAsymmetricUnsealer,
-        SecretAsymmetricKeyBuilder, SecretAsymmetricSealer, SecretAsymmetricUnsealer,
-        SymmetricKeyBuilder, SymmetricSealer, SymmetricUnsealer, ToEntry, ToSymmetricByteAlgorithm,
-        TypeBuilder, TypeBuilderContainer, VectorByteSource, Verifier,
+        Algorithm, AsymmetricKeyBuilder, BoolDataBuilder, Builder, ByteAlgorithm, ByteSource,
+        CommittingSymmetricSealer, CommittingSymmetricUnsealer, CryptoError, Data, DataBuilder,
+        FramedSymmetricSealer, FramedSymmetricUnsealer, FromSeed, HasBuilder, HasByteSource,
+        HasIndex, HasPublicKey, KeyBuilder, PublicAsymmetricKeyBuilder, PublicAsymmetricSealer,
+        PublicAsymmetricUnsealer, RedactStorer, SecretAsymmetricKeyBuilder, SecretAsymmetricSealer,
+        SecretAsymmetricUnsealer, Signer, SymmetricKeyBuilder, SymmetricSealer, SymmetricUnsealer,
+        ToEntry, ToSymmetricByteAlgorithm, TypeBuilder, TypeBuilderContainer, VectorByteSource,
+        Verifier,
     };
     use mongodb::bson;
     use sodiumoxide::crypto::{
         box_,
         secretbox::{self, xsalsa20poly1305::Nonce as ExternalSodiumOxideSymmetricNonce},
-        sign,
+        secretstream, sign,
     };
-    use std::convert::TryInto;
+    use std::{convert::TryInto, time::Duration};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
     //////////////////////////////////////////////
     /// PUBLIC ASYMMETRIC KEY HELPER FUNCTIONS ///
@@ -1470,6 +2441,28 @@ mod tests {
         assert_eq!(key.key.as_ref(), external_key.as_ref());
     }
 
+    #[test]
+    fn test_sodiumoxidesymmetrickeybuilder_build_checked() {
+        let soskb = SodiumOxideSymmetricKeyBuilder {};
+        for weak in [[0u8; 32], [0xffu8; 32], [0x01u8; 32]].iter() {
+            match soskb.build_checked(Some(&weak[..])) {
+                Err(CryptoError::WeakKeyMaterial) => (),
+                _ => panic!("Weak key material should have been rejected"),
+            }
+        }
+        let mut sparse = [0u8; 32];
+        sparse[..7].copy_from_slice(&[0xff; 7]);
+        assert!(soskb.build_checked(Some(&sparse[..])).is_err());
+
+        let external_key = secretbox::gen_key();
+        let key = soskb.build_checked(Some(external_key.as_ref())).unwrap();
+        assert_eq!(key.key.as_ref(), external_key.as_ref());
+        soskb.build_checked(None).unwrap();
+
+        let key = soskb.build(Some(&[0u8; 32][..])).unwrap();
+        assert_eq!(key.key.as_ref(), &[0u8; 32]);
+    }
+
     #[test]
     #[should_panic]
     fn test_sodiumoxidesymmetrickeybuilder_build_invalid() {
@@ -1581,12 +2574,113 @@ mod tests {
             .unwrap()
             .to_unsealed_entry(".bobpublickey.".to_owned())
             .unwrap();
-        let bob_key_bytes = bob_key.resolve().await.unwrap().byte_source();
-        let algorithm = alice_key
-            .to_secret_asymmetric_byte_algorithm(Some(bob_key), Some(get_soan()))
+        let bob_key_bytes = bob_key.resolve().await.unwrap().byte_source();
+        let algorithm = alice_key
+            .to_secret_asymmetric_byte_algorithm(Some(bob_key), Some(get_soan()))
+            .await
+            .unwrap();
+        let ciphertext = algorithm.seal(&data.byte_source()).await.unwrap();
+        let bob_key_copy = SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}
+            .build(Some(bob_key_bytes.get().unwrap()))
+            .unwrap();
+        assert_eq!(
+            ciphertext.get().unwrap(),
+            get_sosak_ciphertext(b"hello, world!", &Some(bob_key_copy))
+        );
+    }
+
+    /// Serves each request with the body registered for its path, and reports whether
+    /// both requests were in flight before either was answered
+    async fn serve_concurrent_gets(
+        listener: tokio::net::TcpListener,
+        bodies: Vec<(&'static str, String)>,
+    ) -> bool {
+        async fn respond(mut socket: tokio::net::TcpStream, bodies: &[(&'static str, String)]) {
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+            while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let request_line = String::from_utf8_lossy(&request)
+                .lines()
+                .next()
+                .unwrap()
+                .to_owned();
+            let body = &bodies
+                .iter()
+                .find(|(path, _)| request_line.contains(path))
+                .unwrap()
+                .1;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            socket.write_all(response.as_bytes()).await.unwrap();
+        }
+
+        let (first, _) = listener.accept().await.unwrap();
+        match tokio::time::timeout(Duration::from_secs(2), listener.accept()).await {
+            Ok(Ok((second, _))) => {
+                respond(first, &bodies).await;
+                respond(second, &bodies).await;
+                true
+            }
+            _ => {
+                respond(first, &bodies).await;
+                let (second, _) = listener.accept().await.unwrap();
+                respond(second, &bodies).await;
+                false
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_seal_secretasymmetricbytealgorithm_resolves_keys_concurrently() {
+        let data = Data::String("hello, world!".to_owned());
+        let unsealed_alice_key = get_sosak()
+            .to_unsealed_entry(".alicesecretkey.".to_owned())
+            .unwrap();
+        let bob_key = SodiumOxideCurve25519SecretAsymmetricKey::new()
+            .public_key()
+            .unwrap();
+        let bob_key_bytes = bob_key.byte_source();
+        let unsealed_bob_key = SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}
+            .build(Some(bob_key_bytes.get().unwrap()))
+            .unwrap()
+            .to_unsealed_entry(".bobpublickey.".to_owned())
+            .unwrap();
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_concurrent_gets(
+            listener,
+            vec![
+                (
+                    ".alicesecretkey.",
+                    serde_json::to_string(&unsealed_alice_key).unwrap(),
+                ),
+                (
+                    ".bobpublickey.",
+                    serde_json::to_string(&unsealed_bob_key).unwrap(),
+                ),
+            ],
+        ));
+
+        let ref_alice_key = get_sosak()
+            .to_ref_entry(".alicesecretkey.".to_owned(), RedactStorer::new(&url))
+            .unwrap();
+        let ref_bob_key = bob_key
+            .to_ref_entry(".bobpublickey.".to_owned(), RedactStorer::new(&url))
+            .unwrap();
+        let algorithm = ref_alice_key
+            .to_secret_asymmetric_byte_algorithm(Some(ref_bob_key), Some(get_soan()))
             .await
             .unwrap();
         let ciphertext = algorithm.seal(&data.byte_source()).await.unwrap();
+        assert!(server.await.unwrap());
+
         let bob_key_copy = SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}
             .build(Some(bob_key_bytes.get().unwrap()))
             .unwrap();
@@ -1608,11 +2702,7 @@ mod tests {
             .to_unsealed_entry(".bobpublickey.".to_owned())
             .unwrap();
         let bob_key_bytes = unsealed_bob_key.resolve().await.unwrap().byte_source();
-        let mut storer = MockIndexedStorer::new();
-        storer
-            .expect_private_get::<SodiumOxideCurve25519SecretAsymmetricKey>()
-            .withf(|path| path == ".alicesecretkey.")
-            .return_once(move |_| Ok(unsealed_alice_key));
+        let storer = MapStorer::from_pairs(vec![(".alicesecretkey.", unsealed_alice_key)]);
         let ref_alice_key = get_sosak()
             .to_ref_entry(".alicesecretkey.".to_owned(), storer)
             .unwrap();
@@ -1630,6 +2720,67 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_bytealgorithm_referenced_paths() {
+        let storer = MapStorer::from_pairs(vec![
+            (
+                ".keys.kek.",
+                get_sosk()
+                    .to_unsealed_entry(".keys.kek.".to_owned())
+                    .unwrap(),
+            ),
+            (
+                ".keys.alice.",
+                get_sosak()
+                    .to_unsealed_entry(".keys.alice.".to_owned())
+                    .unwrap(),
+            ),
+        ]);
+        let kek_algorithm = || {
+            ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+                key: Box::new(
+                    get_sosk()
+                        .to_ref_entry(".keys.kek.".to_owned(), storer.clone())
+                        .unwrap(),
+                ),
+                nonce: get_sosn(),
+            })
+        };
+
+        // A data key sealed by a referenced key-encryption key
+        let sealed_key = SodiumOxideSymmetricKey::new()
+            .to_sealed_entry(".keys.dek.".to_owned(), kek_algorithm())
+            .await
+            .unwrap();
+        let algorithm = ByteAlgorithm::SodiumOxideSymmetricKey(SodiumOxideSymmetricKeyAlgorithm {
+            key: Box::new(sealed_key),
+            nonce: get_sosn(),
+        });
+        assert_eq!(algorithm.referenced_paths(), vec![".keys.kek.".to_owned()]);
+
+        // A referenced secret key paired with a public key sealed by the same KEK
+        let sealed_public_key = get_sopak()
+            .0
+            .to_sealed_entry(".keys.bob.".to_owned(), kek_algorithm())
+            .await
+            .unwrap();
+        let algorithm = ByteAlgorithm::SodiumOxideSecretAsymmetricKey(
+            SodiumOxideSecretAsymmetricKeyAlgorithm {
+                secret_key: Box::new(
+                    get_sosak()
+                        .to_ref_entry(".keys.alice.".to_owned(), storer.clone())
+                        .unwrap(),
+                ),
+                nonce: get_soan(),
+                public_key: Some(Box::new(sealed_public_key)),
+            },
+        );
+        assert_eq!(
+            algorithm.referenced_paths(),
+            vec![".keys.alice.".to_owned(), ".keys.kek.".to_owned()]
+        );
+    }
+
     #[tokio::test]
     async fn test_seal_secretasymmetricbytealgorithm_with_sealed_key_with_unsealed_decryption_key()
     {
@@ -1969,6 +3120,139 @@ mod tests {
         assert!(!sosak.secret_key.as_ref().is_empty());
     }
 
+    /// XCHACHA20 SECRET ASYMMETRIC KEY ///
+    #[test]
+    fn test_xchacha20secretasymmetrickeybuilder_build_valid() {
+        let builder = SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder {};
+        let (_, sk) = box_::gen_keypair();
+        let key = builder.build(Some(sk.as_ref())).unwrap();
+        assert_eq!(key.secret_key.as_ref(), sk.as_ref());
+        assert!(builder.build(Some(b"bla")).is_err());
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickeybuilder_from_typebuildercontainer() {
+        let sosak = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::new();
+        let tbc = TypeBuilderContainer(sosak.builder().into());
+        let _: SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder = tbc.try_into().unwrap();
+        let curve_tbc = TypeBuilderContainer(
+            SodiumOxideCurve25519SecretAsymmetricKey::new()
+                .builder()
+                .into(),
+        );
+        assert!(
+            TryInto::<SodiumOxideCurve25519XChaCha20SecretAsymmetricKeyBuilder>::try_into(
+                curve_tbc
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickey_seal_unseal_roundtrip() {
+        let plaintext: ByteSource = "hello, world!".into();
+        let sosak = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::new();
+        let (ciphertext, nonce) = sosak.seal(&plaintext, None, None).unwrap();
+        assert_eq!(
+            ciphertext.get().unwrap().len(),
+            plaintext.get().unwrap().len() + 16
+        );
+        let unsealed = sosak.unseal(&ciphertext, None, &nonce).unwrap();
+        assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickey_seal_unseal_between_keys() {
+        let plaintext: ByteSource = "hello, world!".into();
+        let (alice_public, alice_secret) = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey::new();
+        let (bob_public, bob_secret) = SodiumOxideCurve25519XChaCha20PublicAsymmetricKey::new();
+        let (ciphertext, nonce) = alice_secret
+            .seal(&plaintext, Some(&bob_public), None)
+            .unwrap();
+        let unsealed = bob_secret
+            .unseal(&ciphertext, Some(&alice_public), &nonce)
+            .unwrap();
+        assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+        match bob_secret.unseal(&ciphertext, None, &nonce) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing with the wrong public key should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickey_unseal_with_invalid_nonce() {
+        let sosak = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::new();
+        let (ciphertext, _) = sosak.seal(&"hello, world!".into(), None, None).unwrap();
+        match sosak.unseal(
+            &ciphertext,
+            None,
+            &SodiumOxideXChaCha20AsymmetricNonce::new(),
+        ) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing with the wrong nonce should have failed"),
+        }
+        match sosak.unseal(
+            &"bla".into(),
+            None,
+            &SodiumOxideXChaCha20AsymmetricNonce::new(),
+        ) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing a truncated ciphertext should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickey_differs_from_xsalsa20() {
+        // The same key and nonce bytes must not produce interchangeable ciphertexts
+        let plaintext: ByteSource = "hello, world!".into();
+        let xchacha_key = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::new();
+        let xsalsa_key =
+            SodiumOxideCurve25519SecretAsymmetricKey::from_external(xchacha_key.secret_key.clone());
+        let xchacha_nonce = SodiumOxideXChaCha20AsymmetricNonce::new();
+        let xsalsa_nonce = SodiumOxideAsymmetricNonce::from_slice(&xchacha_nonce.nonce).unwrap();
+
+        let (xchacha_ciphertext, _) = xchacha_key
+            .seal(&plaintext, None, Some(&xchacha_nonce))
+            .unwrap();
+        let (xsalsa_ciphertext, _) = xsalsa_key
+            .seal(&plaintext, None, Some(&xsalsa_nonce))
+            .unwrap();
+        assert_ne!(
+            xchacha_ciphertext.get().unwrap(),
+            xsalsa_ciphertext.get().unwrap()
+        );
+        assert!(xsalsa_key
+            .unseal(&xchacha_ciphertext, None, &xsalsa_nonce)
+            .is_err());
+        assert!(xchacha_key
+            .unseal(&xsalsa_ciphertext, None, &xchacha_nonce)
+            .is_err());
+    }
+
+    #[test]
+    fn test_xchacha20secretasymmetrickey_to_index() {
+        let index = SodiumOxideCurve25519XChaCha20SecretAsymmetricKey::get_index();
+        assert_eq!(
+            index,
+            Some(bson::doc! {
+                "c": {
+                    "builder": {
+                "t": "Key",
+                "c": {
+                    "t": "Asymmetric",
+                "c": {
+            "t": "Secret",
+                "c": {
+                "t": "SodiumOxideCurve25519XChaCha20"
+                }
+                }
+                }
+                    }
+                }
+                    })
+        )
+    }
+
     ///////////////////////////////////
     /// PUBLIC ASYMMETRIC KEY TESTS ///
     ///////////////////////////////////
@@ -2274,6 +3558,64 @@ mod tests {
         public_key.verify(message, signature).unwrap();
     }
 
+    #[test]
+    fn test_sodiumoxideed25519_to_curve25519() {
+        let (alice_verifying_key, alice_signing_key) = SodiumOxideEd25519PublicAsymmetricKey::new();
+        let alice_public_key = alice_verifying_key.to_curve25519().unwrap();
+        let alice_secret_key = alice_signing_key.to_curve25519().unwrap();
+        assert_eq!(
+            alice_secret_key.public_key().unwrap().public_key.as_ref(),
+            alice_public_key.public_key.as_ref()
+        );
+
+        let bob_secret_key = SodiumOxideCurve25519SecretAsymmetricKey::new();
+        let bob_public_key = bob_secret_key.public_key().unwrap();
+        let plaintext = "hello, world!".into();
+        let (ciphertext, nonce) = alice_secret_key
+            .seal(&plaintext, Some(&bob_public_key), None)
+            .unwrap();
+        let unsealed = bob_secret_key
+            .unseal(&ciphertext, Some(&alice_public_key), &nonce)
+            .unwrap();
+        assert_eq!(unsealed.get().unwrap(), b"hello, world!");
+    }
+
+    #[tokio::test]
+    async fn test_new_entry_pair() {
+        let (secret, public) = SodiumOxideCurve25519PublicAsymmetricKey::new_entry_pair(
+            ".keys.box.secret.".to_owned(),
+            ".keys.box.public.".to_owned(),
+        )
+        .unwrap();
+        assert_eq!(secret.path, ".keys.box.secret.");
+        assert_eq!(public.path, ".keys.box.public.");
+        let secret_key = secret.take_resolve().await.unwrap();
+        let public_key = public.take_resolve().await.unwrap();
+        assert_eq!(
+            secret_key.public_key().unwrap().public_key.as_ref(),
+            public_key.public_key.as_ref()
+        );
+        let plaintext = "hello, world!".into();
+        let (ciphertext, nonce) = secret_key.seal(&plaintext, None, None).unwrap();
+        let unsealed = public_key.unseal(&ciphertext, &secret_key, &nonce).unwrap();
+        assert_eq!(unsealed.get().unwrap(), b"hello, world!");
+
+        let (secret, public) = SodiumOxideEd25519PublicAsymmetricKey::new_entry_pair(
+            ".keys.sign.secret.".to_owned(),
+            ".keys.sign.public.".to_owned(),
+        )
+        .unwrap();
+        let signing_key = secret.take_resolve().await.unwrap();
+        let verifying_key = public.take_resolve().await.unwrap();
+        assert_eq!(
+            signing_key.public_key().unwrap().public_key.as_ref(),
+            verifying_key.public_key.as_ref()
+        );
+        let message: ByteSource = "hello, world!".into();
+        let signature = signing_key.sign(message.clone()).unwrap();
+        verifying_key.verify(message, signature).unwrap();
+    }
+
     #[test]
     fn test_sodiumoxideed25519publicasymmetrickey_verify_with_different_message() {
         let sopakb = SodiumOxideEd25519PublicAsymmetricKeyBuilder {};
@@ -2442,4 +3784,418 @@ mod tests {
         let (sopak, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
         assert!(!sopak.public_key.as_ref().is_empty());
     }
+
+    #[test]
+    fn test_symmetrickey_from_seed() {
+        let first = SodiumOxideSymmetricKey::from_seed(&[1; 32]).unwrap();
+        let second = SodiumOxideSymmetricKey::from_seed(&[1; 32]).unwrap();
+        let other = SodiumOxideSymmetricKey::from_seed(&[2; 32]).unwrap();
+        assert_eq!(first.key.as_ref(), second.key.as_ref());
+        assert_ne!(first.key.as_ref(), other.key.as_ref());
+    }
+
+    #[test]
+    fn test_secretasymmetrickey_from_seed() {
+        let first = SodiumOxideCurve25519SecretAsymmetricKey::from_seed(&[1; 32]).unwrap();
+        let second = SodiumOxideCurve25519SecretAsymmetricKey::from_seed(&[1; 32]).unwrap();
+        let other = SodiumOxideCurve25519SecretAsymmetricKey::from_seed(&[2; 32]).unwrap();
+        assert_eq!(first.secret_key.as_ref(), second.secret_key.as_ref());
+        assert_ne!(first.secret_key.as_ref(), other.secret_key.as_ref());
+    }
+
+    #[test]
+    fn test_ed25519secretasymmetrickey_from_seed() {
+        let first = SodiumOxideEd25519SecretAsymmetricKey::from_seed(&[1; 32]).unwrap();
+        let second = SodiumOxideEd25519SecretAsymmetricKey::from_seed(&[1; 32]).unwrap();
+        let other = SodiumOxideEd25519SecretAsymmetricKey::from_seed(&[2; 32]).unwrap();
+        assert_eq!(first.secret_key.as_ref(), second.secret_key.as_ref());
+        assert_ne!(first.secret_key.as_ref(), other.secret_key.as_ref());
+    }
+
+    #[test]
+    fn test_precomputed_key_is_memoized() {
+        let (sopak, sosak) = get_sopak();
+        let plaintexts: Vec<ByteSource> = (0..5)
+            .map(|i| format!("message {}", i).as_str().into())
+            .collect();
+        assert!(sosak.precomputed.get().is_none());
+
+        let sealed = sosak.seal_many(&plaintexts, Some(&sopak)).unwrap();
+        let (counterparty, memoized) = sosak.precomputed.get().unwrap();
+        assert_eq!(*counterparty, Some(sopak.public_key));
+        assert_eq!(
+            *memoized,
+            box_::precompute(&sopak.public_key, &sosak.secret_key)
+        );
+
+        // Unsealing from the public key's side reuses the key memoized by the secret key
+        let unsealed = sopak.unseal_many(&sealed, &sosak).unwrap();
+        assert_eq!(sosak.precomputed.get().unwrap().0, Some(sopak.public_key));
+
+        // Another counterparty still works, without replacing the memoized key
+        let (other, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let (ciphertext, nonce) = sosak.seal(&plaintexts[0], Some(&other), None).unwrap();
+        assert_eq!(sosak.precomputed.get().unwrap().0, Some(sopak.public_key));
+        assert!(sosak.unseal(&ciphertext, Some(&other), &nonce).is_ok());
+
+        assert_ne!(sealed[0].1.nonce, sealed[1].1.nonce);
+        for (plaintext, unsealed) in plaintexts.iter().zip(unsealed.iter()) {
+            assert_eq!(plaintext.get().unwrap(), unsealed.get().unwrap());
+        }
+    }
+
+    #[test]
+    fn test_unseal_many_with_invalid_ciphertext() {
+        let (sopak, sosak) = get_sopak();
+        let plaintexts: Vec<ByteSource> = vec!["hello".into(), "world".into()];
+        let mut sealed = sopak.seal_many(&plaintexts, &sosak).unwrap();
+        sealed[1].0 = "bla".into();
+        assert!(sosak.unseal_many(&sealed, Some(&sopak)).is_err());
+    }
+
+    #[test]
+    fn test_secret_key_debug_is_redacted() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let debug = format!("{:?}", sosk);
+        assert_eq!(
+            debug,
+            "SodiumOxideSymmetricKey { key: <redacted 32 bytes> }"
+        );
+        assert!(!debug.contains(&format!("{:?}", sosk.key.as_ref())));
+
+        let sosak = get_sosak();
+        let debug = format!("{:?}", sosak);
+        assert_eq!(
+            debug,
+            "SodiumOxideCurve25519SecretAsymmetricKey { secret_key: <redacted 32 bytes> }"
+        );
+        assert!(!debug.contains(&format!("{:?}", sosak.secret_key.as_ref())));
+
+        let (_, sosesak) = SodiumOxideEd25519PublicAsymmetricKey::new();
+        let debug = format!("{:?}", sosesak);
+        assert_eq!(
+            debug,
+            "SodiumOxideEd25519SecretAsymmetricKey { secret_key: <redacted 64 bytes> }"
+        );
+        assert!(!debug.contains(&format!("{:?}", sosesak.secret_key.as_ref())));
+    }
+
+    #[test]
+    fn test_seal_anonymous_roundtrip() {
+        let (sopak, sosak) = get_sopak();
+        let plaintext: ByteSource = "hello, world!".into();
+        let first = sopak.seal_anonymous(&plaintext).unwrap();
+        let second = sopak.seal_anonymous(&plaintext).unwrap();
+        assert_ne!(first.get().unwrap(), second.get().unwrap());
+
+        assert_eq!(
+            sosak.open_anonymous(&first).unwrap().get().unwrap(),
+            plaintext.get().unwrap()
+        );
+        assert_eq!(
+            sosak.open_anonymous(&second).unwrap().get().unwrap(),
+            plaintext.get().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_open_anonymous_with_wrong_key() {
+        let (sopak, _) = get_sopak();
+        let (_, other_sosak) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let ciphertext = sopak.seal_anonymous(&"hello, world!".into()).unwrap();
+        match other_sosak.open_anonymous(&ciphertext) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Opening with the wrong secret key should have failed verification"),
+        }
+    }
+
+    /// Asserts that building from each of the given lengths fails with the expected length
+    fn assert_invalid_key_lengths<B: Builder>(builder: B, expected: usize, lengths: &[usize]) {
+        for len in lengths {
+            match builder.build(Some(&vec![0u8; *len])) {
+                Err(CryptoError::InvalidKeyLength {
+                    expected: e,
+                    actual: a,
+                }) => {
+                    assert_eq!(e, expected);
+                    assert_eq!(a, *len);
+                }
+                _ => panic!("Building a key from {} bytes should have failed", len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_symmetrickeybuilder_invalid_key_lengths() {
+        assert_eq!(SodiumOxideSymmetricKey::KEYBYTES, 32);
+        assert_invalid_key_lengths(
+            SodiumOxideSymmetricKeyBuilder {},
+            SodiumOxideSymmetricKey::KEYBYTES,
+            &[0, 31, 33, 64],
+        );
+    }
+
+    #[test]
+    fn test_curve25519secretasymmetrickeybuilder_invalid_key_lengths() {
+        assert_eq!(SodiumOxideCurve25519SecretAsymmetricKey::KEYBYTES, 32);
+        assert_invalid_key_lengths(
+            SodiumOxideCurve25519SecretAsymmetricKeyBuilder {},
+            SodiumOxideCurve25519SecretAsymmetricKey::KEYBYTES,
+            &[0, 31, 33, 64],
+        );
+    }
+
+    #[test]
+    fn test_curve25519publicasymmetrickeybuilder_invalid_key_lengths() {
+        assert_eq!(SodiumOxideCurve25519PublicAsymmetricKey::KEYBYTES, 32);
+        assert_invalid_key_lengths(
+            SodiumOxideCurve25519PublicAsymmetricKeyBuilder {},
+            SodiumOxideCurve25519PublicAsymmetricKey::KEYBYTES,
+            &[0, 31, 33, 64],
+        );
+    }
+
+    #[test]
+    fn test_ed25519secretasymmetrickeybuilder_invalid_key_lengths() {
+        // A 32-byte input is accepted as a seed, any other length must be a full secret key
+        assert_eq!(SodiumOxideEd25519SecretAsymmetricKey::KEYBYTES, 64);
+        assert_invalid_key_lengths(
+            SodiumOxideEd25519SecretAsymmetricKeyBuilder {},
+            SodiumOxideEd25519SecretAsymmetricKey::KEYBYTES,
+            &[0, 31, 33, 63, 65],
+        );
+    }
+
+    #[test]
+    fn test_ed25519publicasymmetrickeybuilder_invalid_key_lengths() {
+        assert_eq!(SodiumOxideEd25519PublicAsymmetricKey::KEYBYTES, 32);
+        assert_invalid_key_lengths(
+            SodiumOxideEd25519PublicAsymmetricKeyBuilder {},
+            SodiumOxideEd25519PublicAsymmetricKey::KEYBYTES,
+            &[0, 31, 33, 64],
+        );
+    }
+
+    #[test]
+    fn test_ed25519secretasymmetrickeybuilder_build_from_secret_key() {
+        let (_, sk) = sign::gen_keypair();
+        assert_eq!(
+            sk.as_ref().len(),
+            SodiumOxideEd25519SecretAsymmetricKey::KEYBYTES
+        );
+        let key = SodiumOxideEd25519SecretAsymmetricKeyBuilder {}
+            .build(Some(sk.as_ref()))
+            .unwrap();
+        assert_eq!(key.secret_key.as_ref(), sk.as_ref());
+    }
+
+    #[test]
+    fn test_ed25519secretasymmetrickeybuilder_build_from_seed() {
+        // 32-byte inputs are seeds rather than truncated secret keys, and expand to the
+        // same 64-byte secret key that from_seed produces
+        let key = SodiumOxideEd25519SecretAsymmetricKeyBuilder {}
+            .build(Some(&[3u8; 32][..]))
+            .unwrap();
+        let expected = SodiumOxideEd25519SecretAsymmetricKey::from_seed(&[3u8; 32]).unwrap();
+        assert_eq!(key.secret_key.as_ref(), expected.secret_key.as_ref());
+        assert_eq!(
+            key.secret_key.as_ref().len(),
+            SodiumOxideEd25519SecretAsymmetricKey::KEYBYTES
+        );
+    }
+
+    #[test]
+    fn test_symmetrickey_seal_committing_roundtrip() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let plaintext: ByteSource = "hello, world!".into();
+        let (ciphertext, nonce) = sosk.seal_committing(&plaintext, None).unwrap();
+        assert_eq!(
+            ciphertext.get().unwrap().len(),
+            KEY_COMMITMENT_BYTES + secretbox::MACBYTES + plaintext.get().unwrap().len()
+        );
+        let unsealed = sosk.unseal_committing(&ciphertext, &nonce).unwrap();
+        assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+    }
+
+    #[test]
+    fn test_symmetrickey_unseal_committing_with_other_key() {
+        let key_a = SodiumOxideSymmetricKey::new();
+        let key_b = SodiumOxideSymmetricKey::new();
+        let plaintext: ByteSource = "hello, world!".into();
+        let (ciphertext, nonce) = key_a.seal_committing(&plaintext, None).unwrap();
+        match key_b.unseal_committing(&ciphertext, &nonce) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing under a different key should have failed"),
+        }
+
+        // Simulate an AEAD tag that also verifies under key B by sealing the body under B
+        // directly; the commitment to key A must still cause the unseal to fail
+        let (body, nonce) = key_b.seal(&plaintext, Some(&nonce)).unwrap();
+        let mut forged = key_commitment(&key_a.key).as_ref().to_vec();
+        forged.extend_from_slice(body.get().unwrap());
+        match key_b.unseal_committing(&forged.as_slice().into(), &nonce) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing with a commitment to another key should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_symmetrickey_derive_child() {
+        let root = SodiumOxideSymmetricKey::new();
+        assert_eq!(root.derive_child(0).key, root.derive_child(0).key);
+        assert_ne!(root.derive_child(0).key, root.derive_child(1).key);
+        assert_ne!(root.derive_child(0).key, root.key);
+        assert_ne!(
+            root.derive_child(0).key,
+            SodiumOxideSymmetricKey::new().derive_child(0).key
+        );
+
+        assert_eq!(
+            root.derive_path(&[3, 7]).key,
+            root.derive_child(3).derive_child(7).key
+        );
+        assert_ne!(root.derive_path(&[3, 7]).key, root.derive_path(&[7, 3]).key);
+        assert_eq!(root.derive_path(&[]).key, root.key);
+    }
+
+    #[test]
+    fn test_symmetrickey_seal_framed_roundtrip() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let plaintext: ByteSource = "hello, world!".into();
+        let frame = sosk.seal_framed(&plaintext, None).unwrap();
+        assert_eq!(
+            frame.get().unwrap().len(),
+            FRAME_HEADER_BYTES
+                + secretbox::NONCEBYTES
+                + secretbox::MACBYTES
+                + plaintext.get().unwrap().len()
+        );
+
+        // The frame alone, without its nonce, is enough to unseal it
+        let frame: ByteSource = frame.get().unwrap().into();
+        let unsealed = sosk.unseal_framed(&frame).unwrap();
+        assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+    }
+
+    #[test]
+    fn test_symmetrickey_unseal_framed_invalid() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let frame = sosk.seal_framed(&"hello, world!".into(), None).unwrap();
+        let frame_bytes = frame.get().unwrap();
+
+        for len in [
+            0,
+            1,
+            FRAME_HEADER_BYTES + 10,
+            FRAME_HEADER_BYTES + secretbox::NONCEBYTES,
+        ] {
+            match sosk.unseal_framed(&frame_bytes[..len].into()) {
+                Err(CryptoError::InvalidFrame) => (),
+                _ => panic!(
+                    "Unsealing a frame truncated to {} bytes should have failed",
+                    len
+                ),
+            }
+        }
+        match sosk.unseal_framed(&frame_bytes[..frame_bytes.len() - 1].into()) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing a frame with a truncated ciphertext should have failed"),
+        }
+
+        let mut wrong_version = frame_bytes.to_vec();
+        wrong_version[0] = FRAME_VERSION + 1;
+        match sosk.unseal_framed(&wrong_version.as_slice().into()) {
+            Err(CryptoError::InvalidFrame) => (),
+            _ => panic!("Unsealing a frame with an unknown version should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_symmetrickey_seal_stream_progress() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let plaintext = vec![7u8; 1024 * 1024];
+        let chunk_size = 64 * 1024;
+        let calls = std::cell::RefCell::new(vec![]);
+        let record = |processed: u64, total: Option<u64>| {
+            assert_eq!(total, Some(plaintext.len() as u64));
+            calls.borrow_mut().push(processed);
+        };
+
+        let mut sealed = vec![];
+        sosk.seal_stream(
+            plaintext.as_slice(),
+            &mut sealed,
+            chunk_size,
+            Some(plaintext.len() as u64),
+            Some(&record),
+        )
+        .unwrap();
+        let calls = calls.into_inner();
+        assert_eq!(calls.len(), plaintext.len() / chunk_size);
+        assert!(calls.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(*calls.last().unwrap(), plaintext.len() as u64);
+
+        let mut unsealed = vec![];
+        sosk.unseal_stream(sealed.as_slice(), &mut unsealed, chunk_size, None, None)
+            .unwrap();
+        assert_eq!(unsealed, plaintext);
+    }
+
+    #[test]
+    fn test_symmetrickey_unseal_stream_truncated() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let mut sealed = vec![];
+        sosk.seal_stream(&[1u8; 100][..], &mut sealed, 16, None, None)
+            .unwrap();
+        sealed.truncate(sealed.len() - 20);
+        match sosk.unseal_stream(sealed.as_slice(), &mut vec![], 16, None, None) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing a truncated stream should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_symmetrickey_unseal_stream_oversized_chunk() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let mut sealed = vec![];
+        sosk.seal_stream(&[1u8; 16][..], &mut sealed, 16, None, None)
+            .unwrap();
+        let mut unsealed = vec![];
+        sosk.unseal_stream(sealed.as_slice(), &mut unsealed, 16, None, None)
+            .unwrap();
+        assert_eq!(unsealed, vec![1u8; 16]);
+
+        // A forged length prefix is refused rather than allocated
+        let prefix = secretstream::HEADERBYTES;
+        sealed[prefix..prefix + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+        match sosk.unseal_stream(sealed.as_slice(), &mut vec![], 16, None, None) {
+            Err(CryptoError::CiphertextFailedVerification) => (),
+            _ => panic!("Unsealing a chunk longer than the chunk size should have failed"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_symmetrickey_to_byte_algorithm_ref() {
+        let sosk = SodiumOxideSymmetricKey::new();
+        let first = sosk
+            .to_byte_algorithm_ref(None, |key| async move {
+                key.to_unsealed_entry(".keys.first.".to_owned())
+            })
+            .await
+            .unwrap();
+        let second = sosk
+            .to_byte_algorithm_ref(None, |key| async move {
+                key.to_unsealed_entry(".keys.second.".to_owned())
+            })
+            .await
+            .unwrap();
+
+        let plaintext: ByteSource = "hello, world!".into();
+        for algorithm in [first, second].iter() {
+            let ciphertext = algorithm.seal(&plaintext).await.unwrap();
+            let unsealed = algorithm.unseal(&ciphertext).await.unwrap();
+            assert_eq!(unsealed.get().unwrap(), plaintext.get().unwrap());
+        }
+        assert_eq!(sosk.key.as_ref().len(), SodiumOxideSymmetricKey::KEYBYTES);
+    }
 }
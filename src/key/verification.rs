@@ -0,0 +1,77 @@
+//! Bulk verification of entry signatures made with `Entry::sign`, reporting which entries
+//! verified and why the others did not.
+
+use super::Verifier;
+use crate::{ByteSource, CryptoError, Entry, EntryPath, StorableType};
+
+/// Outcome of a `verify_entries` run
+#[derive(Debug, Default)]
+pub struct VerificationReport {
+    /// Paths of the entries whose signature verified
+    pub verified: Vec<EntryPath>,
+    /// Entries whose signature did not verify, along with the reason
+    pub failed: Vec<(EntryPath, CryptoError)>,
+}
+
+impl VerificationReport {
+    /// Whether every entry's signature verified
+    pub fn is_success(&self) -> bool {
+        self.failed.is_empty()
+    }
+}
+
+/// Verifies each entry against the signature paired with it. A signature failing to
+/// verify is recorded in the report and verification moves on to the next entry.
+pub fn verify_entries<T: StorableType, V: Verifier + ?Sized>(
+    items: &[(Entry<T>, ByteSource)],
+    verifier: &V,
+) -> VerificationReport {
+    let mut report = VerificationReport::default();
+    for (entry, signature) in items {
+        match entry.verify_signature(verifier, signature.clone()) {
+            Ok(()) => report.verified.push(entry.path.clone()),
+            Err(e) => report.failed.push((entry.path.clone(), e)),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify_entries;
+    use crate::{
+        key::sodiumoxide::SodiumOxideEd25519SecretAsymmetricKey, ByteSource, CryptoError, Data,
+        HasPublicKey, ToEntry,
+    };
+
+    #[test]
+    fn test_verify_entries_partitions_by_path() {
+        let key = SodiumOxideEd25519SecretAsymmetricKey::new();
+        let items: Vec<(_, ByteSource)> = [".data.one.", ".data.two.", ".data.three."]
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let entry = Data::U64(i as u64)
+                    .to_unsealed_entry((*path).to_owned())
+                    .unwrap();
+                let mut signature = entry.sign(&key).unwrap();
+                if i == 1 {
+                    let mut bytes = signature.get().unwrap().to_vec();
+                    bytes[0] ^= 0x01;
+                    signature = bytes.as_slice().into();
+                }
+                (entry, signature)
+            })
+            .collect();
+
+        let report = verify_entries(&items, &key.public_key().unwrap());
+        assert!(!report.is_success());
+        assert_eq!(report.verified, vec![".data.one.", ".data.three."]);
+        assert_eq!(report.failed.len(), 1);
+        assert_eq!(report.failed[0].0, ".data.two.");
+        match report.failed[0].1 {
+            CryptoError::BadSignature => (),
+            _ => panic!("Tampered signature should have failed to verify"),
+        }
+    }
+}
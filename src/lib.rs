@@ -10,6 +10,7 @@
 //! - u64
 //! - i64
 //! - f64
+//! - decimal
 //! - Vec<u8>
 //! These should cover most use cases for now. In the case of serializing a custom data type,
 //! that type can be serialized into bytes and then those bytes turned into a Vec<u8> to
@@ -34,6 +35,9 @@
 //!
 //! File directory:
 //! - lib.rs: exports root-level public types from otherwise private submodules
+//! - audit.rs: optional audit trail of seal/unseal operations
+//! - clock.rs: injectable source of the current time
+//! - migration.rs: bulk re-encryption of stored entries under a new algorithm
 //! - error.rs: custom errors that can arise from various redact-crypto operations
 //! - sources.rs: types, traits, and implementations for sources of data
 //! - typebuilders.rs: types that build types
@@ -48,40 +52,64 @@
 //! - storage/redact.rs: storage implementation for a redact-store server
 
 mod algorithm;
+pub mod audit;
 pub mod cert;
+pub mod clock;
 mod data;
 mod entry;
 mod error;
 pub mod key;
+pub mod migration;
 pub mod nonce;
 mod source;
 pub mod storage;
+mod trace;
 pub mod x509;
 
-pub use algorithm::{Algorithm, ByteAlgorithm};
+pub use algorithm::{
+    Algorithm, AuditedAlgorithm, ByteAlgorithm, CompressedAlgorithm, Compression,
+    SizeLimitedAlgorithm, DEFAULT_MAX_DECOMPRESSED_SIZE,
+};
 pub use data::{
-    BinaryData, BinaryDataBuilder, BinaryType, BoolDataBuilder, Data, DataBuilder, F64DataBuilder,
-    I64DataBuilder, StringDataBuilder, U64DataBuilder,
+    BinaryData, BinaryDataBuilder, BinaryType, BoolDataBuilder, Data, DataBuilder,
+    DecimalDataBuilder, F64DataBuilder, I64DataBuilder, StringDataBuilder, U64DataBuilder,
 };
 pub use entry::{
-    Builder, Entry, EntryPath, HasBuilder, State, StorableType, ToEntry, Type, TypeBuilder,
-    TypeBuilderContainer,
+    Builder, DataView, Entry, EntryPatch, EntryPath, HasBuilder, Provenance, ProvenanceHop,
+    ResolvableItem, ResolveEach, SerializationFormat, State, StateKind, StorableType, ToEntry,
+    Type, TypeBuilder, TypeBuilderContainer,
 };
 pub use error::CryptoError;
 pub use key::{
-    AsymmetricKey, AsymmetricKeyBuilder, HasAlgorithmIdentifier, HasPublicKey, Key, KeyBuilder,
-    PublicAsymmetricKey, PublicAsymmetricKeyBuilder, PublicAsymmetricSealer,
-    PublicAsymmetricUnsealer, SecretAsymmetricKey, SecretAsymmetricKeyBuilder,
-    SecretAsymmetricSealer, SecretAsymmetricUnsealer, Signer, SymmetricKey, SymmetricKeyBuilder,
-    SymmetricSealer, SymmetricUnsealer, ToPublicAsymmetricByteAlgorithm,
-    ToSecretAsymmetricByteAlgorithm, ToSymmetricByteAlgorithm, Verifier,
+    audit_keys, safe_delete_key, seal_to_path, verify_any, verify_entries, verify_keypair, KeyRole,
+    SignerPool, VerificationReport,
+};
+pub use key::{
+    AsymmetricKey, AsymmetricKeyBuilder, CommittingSymmetricSealer, CommittingSymmetricUnsealer,
+    FramedSymmetricSealer, FramedSymmetricUnsealer, FromSeed, HasAlgorithmIdentifier,
+    HasFingerprint, HasPublicKey, Key, KeyBuilder, PublicAsymmetricKey, PublicAsymmetricKeyBuilder,
+    PublicAsymmetricSealer, PublicAsymmetricUnsealer, SecretAsymmetricKey,
+    SecretAsymmetricKeyBuilder, SecretAsymmetricSealer, SecretAsymmetricUnsealer, Signer,
+    SymmetricKey, SymmetricKeyBuilder, SymmetricSealer, SymmetricUnsealer,
+    ToPublicAsymmetricByteAlgorithm, ToSecretAsymmetricByteAlgorithm, ToSymmetricByteAlgorithm,
+    Verifier,
 };
+pub use migration::{migrate_algorithm, MigrationReport};
 pub use nonce::{AsymmetricNonce, Nonce, SymmetricNonce};
 pub use source::{
-    ByteSource, FsByteSource, HasByteSource, Path, Source, SourceError, VectorByteSource,
+    Base64Alphabet, ByteSource, EnvByteSource, EnvEncoding, FsByteSource, HasByteSource, Path,
+    Source, SourceError, VectorByteSource,
 };
 pub use storage::{
+    boxed::{BoxedStorer, BoxedStorerError, DynStorer},
+    dualwrite::DualWriteStorer,
+    encrypting::EncryptingStorer,
+    filesystem::{FilesystemStorer, FilesystemStorerError},
+    index_from_builder,
+    memory::{MemoryStorer, MemoryStorerError},
+    metrics::{MetricsSink, MetricsStorer, StorerOperation},
     mongodb::{MongoStorer, MongoStorerError},
     redact::{RedactStorer, RedactStorerError},
     HasIndex, IndexedStorer, IndexedTypeStorer, NonIndexedTypeStorer, Storer, TypeStorer,
+    DEFAULT_MAX_PAGE_SIZE,
 };
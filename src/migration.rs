@@ -0,0 +1,201 @@
+//! Bulk re-encryption of stored entries from one sealing algorithm to another, for when
+//! the algorithm protecting a set of entries changes rather than just its key. Entries
+//! are re-sealed from their raw plaintext bytes, so entries of any type can be migrated
+//! together without building their final types.
+
+use crate::{Algorithm, ByteAlgorithm, CryptoError, Entry, EntryPath, IndexedStorer, State, Type};
+
+/// Number of entries fetched from the storer at a time
+const PAGE_SIZE: i64 = 100;
+
+/// Outcome of a `migrate_algorithm` run
+#[derive(Debug, Default)]
+pub struct MigrationReport {
+    /// Number of entries re-sealed under the new algorithm
+    pub migrated: u64,
+    /// Number of entries left untouched because they were not sealed under a matching
+    /// algorithm, including those migrated by a previous run
+    pub skipped: u64,
+    /// Entries that matched but could not be migrated, along with the reason
+    pub errors: Vec<(EntryPath, CryptoError)>,
+}
+
+/// Re-seals every entry under `prefix` whose sealing algorithm matches `from` with the
+/// `to` algorithm, and stores it back at the same path with its metadata and any time
+/// lock intact. Each entry is sealed under a fresh nonce. Entries sealed under `to` no
+/// longer match `from`, so an interrupted migration can be resumed by running it again.
+///
+/// Failing to list entries aborts the migration, while a failure to unseal, re-seal or
+/// store a single entry is recorded in the report and the migration moves on.
+pub async fn migrate_algorithm<S: IndexedStorer>(
+    storer: &S,
+    prefix: &str,
+    from: fn(&ByteAlgorithm) -> bool,
+    mut to: ByteAlgorithm,
+) -> Result<MigrationReport, CryptoError> {
+    let mut report = MigrationReport::default();
+    let mut skip = 0;
+    loop {
+        let entries = storer.list::<Type>(prefix, skip, PAGE_SIZE).await?;
+        let fetched = entries.len() as u64;
+
+        for entry in entries {
+            let matches = match entry.value {
+                State::Sealed { ref algorithm, .. } => from(algorithm),
+                _ => false,
+            };
+            if !matches {
+                report.skipped += 1;
+                continue;
+            }
+
+            let path = entry.path.clone();
+            match migrate_entry(storer, entry, &mut to).await {
+                Ok(()) => report.migrated += 1,
+                Err(e) => report.errors.push((path, e)),
+            }
+        }
+
+        if fetched < PAGE_SIZE as u64 {
+            return Ok(report);
+        }
+        skip += fetched;
+    }
+}
+
+/// Unseals a single entry and stores it back sealed under `to`
+async fn migrate_entry<S: IndexedStorer>(
+    storer: &S,
+    entry: Entry<Type>,
+    to: &mut ByteAlgorithm,
+) -> Result<(), CryptoError> {
+    let (plaintext, unlock_at) = match entry.value {
+        State::Sealed {
+            ref ciphertext,
+            ref algorithm,
+            unlock_at,
+        } => (
+            algorithm.unseal_for_path(ciphertext, &entry.path).await?,
+            unlock_at,
+        ),
+        _ => return Ok(()),
+    };
+    to.regenerate_nonce();
+    let ciphertext = to.seal_for_path(&plaintext, &entry.path).await?;
+    let migrated = Entry::<Type>::new(
+        entry.path,
+        entry.builder,
+        State::Sealed {
+            ciphertext,
+            algorithm: to.clone(),
+            unlock_at,
+        },
+    )
+    .with_metadata(entry.metadata);
+    storer.create(migrated).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::migrate_algorithm;
+    use crate::{
+        key::sodiumoxide::{SodiumOxideCurve25519SecretAsymmetricKey, SodiumOxideSymmetricKey},
+        ByteAlgorithm, Data, MemoryStorer, State, Storer, ToEntry, ToSecretAsymmetricByteAlgorithm,
+        ToSymmetricByteAlgorithm,
+    };
+
+    fn is_secretbox(algorithm: &ByteAlgorithm) -> bool {
+        matches!(algorithm, ByteAlgorithm::SodiumOxideSymmetricKey(_))
+    }
+
+    async fn secretbox_algorithm() -> ByteAlgorithm {
+        SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".keys.old.".to_owned())
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_migrate_algorithm() {
+        let storer = MemoryStorer::new();
+        for (i, path) in [".data.one.", ".data.two.", ".data.three."]
+            .iter()
+            .enumerate()
+        {
+            let mut entry = Data::U64(i as u64)
+                .to_sealed_entry((*path).to_owned(), secretbox_algorithm().await)
+                .await
+                .unwrap();
+            entry.set_metadata("index", i.to_string());
+            storer.create(entry).await.unwrap();
+        }
+        storer
+            .create(
+                Data::Bool(true)
+                    .to_unsealed_entry(".data.plain.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let mut tampered = Data::U64(4)
+            .to_sealed_entry(".data.tampered.".to_owned(), secretbox_algorithm().await)
+            .await
+            .unwrap();
+        if let State::Sealed {
+            ref mut ciphertext, ..
+        } = tampered.value
+        {
+            let mut bytes = ciphertext.get().unwrap().to_vec();
+            bytes[0] ^= 0x01;
+            ciphertext.set(&bytes).unwrap();
+        }
+        storer.create(tampered).await.unwrap();
+
+        let to = SodiumOxideCurve25519SecretAsymmetricKey::new()
+            .to_byte_algorithm(None, None, |key| async move {
+                key.to_unsealed_entry(".keys.new.".to_owned())
+            })
+            .await
+            .unwrap();
+        let report = migrate_algorithm(&storer, ".data.", is_secretbox, to)
+            .await
+            .unwrap();
+        assert_eq!(report.migrated, 3);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.errors[0].0, ".data.tampered.");
+
+        for (i, path) in [".data.one.", ".data.two.", ".data.three."]
+            .iter()
+            .enumerate()
+        {
+            let entry = storer.get::<Data>(path).await.unwrap();
+            match entry.value {
+                State::Sealed {
+                    algorithm: ByteAlgorithm::SodiumOxideSecretAsymmetricKey(_),
+                    ..
+                } => (),
+                _ => panic!("Entry should have been sealed under the new algorithm"),
+            }
+            assert_eq!(entry.get_metadata("index"), Some(i.to_string().as_str()));
+            assert_eq!(entry.resolve().await.unwrap(), &Data::U64(i as u64));
+        }
+
+        // Migrated entries no longer match, so running again only retries the failure
+        let to = SodiumOxideCurve25519SecretAsymmetricKey::new()
+            .to_byte_algorithm(None, None, |key| async move {
+                key.to_unsealed_entry(".keys.new.".to_owned())
+            })
+            .await
+            .unwrap();
+        let report = migrate_algorithm(&storer, ".data.", is_secretbox, to)
+            .await
+            .unwrap();
+        assert_eq!(report.migrated, 0);
+        assert_eq!(report.skipped, 4);
+        assert_eq!(report.errors.len(), 1);
+    }
+}
@@ -1,10 +1,19 @@
 //! Classifies nonces used by different key types.
 
+pub mod aesgcmsiv;
 pub mod sodiumoxide;
 
-use self::sodiumoxide::{SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce};
+use self::aesgcmsiv::Aes256GcmSivSymmetricNonce;
+use self::sodiumoxide::{
+    SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce, SodiumOxideXChaCha20AsymmetricNonce,
+};
+use crate::CryptoError;
 use serde::{Deserialize, Serialize};
 
+/// Smallest nonce length, in bytes, that a nonce may be truncated to. Below 96 bits,
+/// randomly generated nonces collide too often to be safely reused by another algorithm.
+pub const MIN_TRUNCATED_NONCE_BYTES: usize = 12;
+
 /// Trait indicating a type has a nonce
 pub trait HasNonce {
     fn nonce(&self) -> Nonce;
@@ -21,10 +30,149 @@ pub enum Nonce {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum SymmetricNonce {
     SodiumOxide(SodiumOxideSymmetricNonce),
+    Aes256GcmSiv(Aes256GcmSivSymmetricNonce),
 }
 
 /// Supported nonces used for asymmetric encryption
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum AsymmetricNonce {
     SodiumOxide(SodiumOxideAsymmetricNonce),
+    SodiumOxideXChaCha20(SodiumOxideXChaCha20AsymmetricNonce),
+}
+
+impl SymmetricNonce {
+    /// Builds a sodiumoxide secretbox nonce from raw bytes
+    pub fn sodiumoxide_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Ok(SymmetricNonce::SodiumOxide(
+            SodiumOxideSymmetricNonce::try_from_slice(bs)?,
+        ))
+    }
+
+    /// Builds an AES-256-GCM-SIV nonce from raw bytes
+    pub fn aes256gcmsiv_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Ok(SymmetricNonce::Aes256GcmSiv(
+            Aes256GcmSivSymmetricNonce::try_from_slice(bs)?,
+        ))
+    }
+
+    /// Returns the raw bytes of the nonce
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            SymmetricNonce::SodiumOxide(sosn) => sosn.nonce.as_ref(),
+            SymmetricNonce::Aes256GcmSiv(agsn) => &agsn.nonce,
+        }
+    }
+
+    /// Adapts the nonce to an algorithm expecting `target_len` bytes, for migrating
+    /// ciphertexts between algorithms. Truncation keeps the leading bytes and is only
+    /// allowed down to `MIN_TRUNCATED_NONCE_BYTES`. Extension is always rejected since
+    /// it would pad the nonce with predictable bytes.
+    pub fn truncate_or_extend(&self, target_len: usize) -> Result<Vec<u8>, CryptoError> {
+        let bytes = self.as_bytes();
+        if target_len > bytes.len() || target_len < MIN_TRUNCATED_NONCE_BYTES {
+            return Err(CryptoError::UnsafeNonceConversion {
+                from: bytes.len(),
+                to: target_len,
+            });
+        }
+        Ok(bytes[..target_len].to_vec())
+    }
+}
+
+impl AsymmetricNonce {
+    /// Builds a sodiumoxide box nonce from raw bytes
+    pub fn sodiumoxide_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Ok(AsymmetricNonce::SodiumOxide(
+            SodiumOxideAsymmetricNonce::try_from_slice(bs)?,
+        ))
+    }
+
+    /// Returns the raw bytes of the nonce
+    pub fn as_bytes(&self) -> &[u8] {
+        match self {
+            AsymmetricNonce::SodiumOxide(soan) => soan.nonce.as_ref(),
+            AsymmetricNonce::SodiumOxideXChaCha20(soxan) => &soxan.nonce,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsymmetricNonce, Nonce, SymmetricNonce, MIN_TRUNCATED_NONCE_BYTES};
+    use crate::{
+        nonce::sodiumoxide::{SodiumOxideAsymmetricNonce, SodiumOxideXChaCha20AsymmetricNonce},
+        CryptoError,
+    };
+
+    fn get_nonce() -> SymmetricNonce {
+        let bytes: Vec<u8> = (0..24).collect();
+        SymmetricNonce::sodiumoxide_from_slice(&bytes).unwrap()
+    }
+
+    #[test]
+    fn test_symmetricnonce_truncate() {
+        let nonce = get_nonce();
+        let truncated = nonce.truncate_or_extend(12).unwrap();
+        assert_eq!(truncated, (0..12).collect::<Vec<u8>>());
+        assert_eq!(nonce.truncate_or_extend(24).unwrap(), nonce.as_bytes());
+    }
+
+    #[test]
+    fn test_symmetricnonce_extend_rejected() {
+        match get_nonce().truncate_or_extend(32) {
+            Err(CryptoError::UnsafeNonceConversion { from, to }) => {
+                assert_eq!(from, 24);
+                assert_eq!(to, 32);
+            }
+            _ => panic!("Extending a nonce should have been rejected"),
+        }
+    }
+
+    #[test]
+    fn test_symmetricnonce_truncate_too_short_rejected() {
+        assert!(get_nonce()
+            .truncate_or_extend(MIN_TRUNCATED_NONCE_BYTES - 1)
+            .is_err());
+    }
+
+    fn nonce_bytes(nonce: &Nonce) -> &[u8] {
+        match nonce {
+            Nonce::Symmetric(sn) => sn.as_bytes(),
+            Nonce::Asymmetric(an) => an.as_bytes(),
+        }
+    }
+
+    #[test]
+    fn test_nonce_serialization_format() {
+        let nonces = vec![
+            Nonce::Symmetric(get_nonce()),
+            Nonce::Asymmetric(AsymmetricNonce::SodiumOxide(
+                SodiumOxideAsymmetricNonce::new(),
+            )),
+            Nonce::Asymmetric(AsymmetricNonce::SodiumOxideXChaCha20(
+                SodiumOxideXChaCha20AsymmetricNonce::new(),
+            )),
+        ];
+        for nonce in nonces.iter() {
+            let bytes = nonce_bytes(nonce);
+
+            // Human-readable formats carry the nonce as a base64 string
+            let json = serde_json::to_string(nonce).unwrap();
+            assert!(json.contains(&format!("\"{}\"", base64::encode(bytes))));
+            let from_json: Nonce = serde_json::from_str(&json).unwrap();
+            assert_eq!(nonce_bytes(&from_json), bytes);
+
+            // Binary formats carry the raw bytes
+            let encoded = bincode::serialize(nonce).unwrap();
+            assert!(encoded.windows(bytes.len()).any(|w| w == bytes));
+            assert!(encoded.len() < json.len());
+            let from_bincode: Nonce = bincode::deserialize(&encoded).unwrap();
+            assert_eq!(nonce_bytes(&from_bincode), bytes);
+        }
+    }
+
+    #[test]
+    fn test_symmetricnonce_from_slice_invalid_length() {
+        assert!(SymmetricNonce::sodiumoxide_from_slice(&[0u8; 12]).is_err());
+    }
 }
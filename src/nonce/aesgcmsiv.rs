@@ -0,0 +1,75 @@
+use super::sodiumoxide::{nonce_bytes_deserialize, nonce_bytes_serialize};
+use crate::CryptoError;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use sodiumoxide::randombytes;
+use std::convert::TryInto;
+
+const AES256GCMSIV_NONCEBYTES: usize = 12;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Aes256GcmSivSymmetricNonce {
+    #[serde(
+        serialize_with = "symmetric_nonce_serialize",
+        deserialize_with = "symmetric_nonce_deserialize"
+    )]
+    pub nonce: [u8; AES256GCMSIV_NONCEBYTES],
+}
+
+/// Custom serialization function, see `nonce_bytes_serialize`
+fn symmetric_nonce_serialize<S>(
+    nonce: &[u8; AES256GCMSIV_NONCEBYTES],
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    nonce_bytes_serialize(nonce, s)
+}
+
+/// Custom deserialization function, see `nonce_bytes_deserialize`
+fn symmetric_nonce_deserialize<'de, D>(
+    deserializer: D,
+) -> Result<[u8; AES256GCMSIV_NONCEBYTES], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let decoded = nonce_bytes_deserialize(deserializer)?;
+    decoded.as_slice().try_into().map_err(|_| {
+        de::Error::custom(format!(
+            "deserialized nonce was {} bytes long, expected {} bytes",
+            decoded.len(),
+            Aes256GcmSivSymmetricNonce::NONCEBYTES
+        ))
+    })
+}
+
+impl Aes256GcmSivSymmetricNonce {
+    pub const NONCEBYTES: usize = AES256GCMSIV_NONCEBYTES;
+
+    pub fn from_slice(bs: &[u8]) -> Option<Self> {
+        Some(Aes256GcmSivSymmetricNonce {
+            nonce: bs.try_into().ok()?,
+        })
+    }
+
+    /// Builds a nonce from the given bytes, returning an `InvalidKeyLength` error
+    /// carrying the expected nonce size if the slice is the wrong length
+    pub fn try_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_slice(bs).ok_or(CryptoError::InvalidKeyLength {
+            expected: Self::NONCEBYTES,
+            actual: bs.len(),
+        })
+    }
+
+    pub fn new() -> Self {
+        let mut nonce = [0u8; Self::NONCEBYTES];
+        randombytes::randombytes_into(&mut nonce);
+        Aes256GcmSivSymmetricNonce { nonce }
+    }
+}
+
+impl Default for Aes256GcmSivSymmetricNonce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
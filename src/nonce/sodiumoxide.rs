@@ -1,8 +1,17 @@
+use crate::CryptoError;
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
-use sodiumoxide::crypto::{
-    box_::{self, Nonce as ExternalAsymmetricNonce, NONCEBYTES as EXTERNALASYMMETRICNONCEBYTES},
-    secretbox::{self, Nonce as ExternalSymmetricNonce, NONCEBYTES as EXTERNALSYMMETRICNONCEBYTES},
+use sodiumoxide::{
+    crypto::{
+        box_::{
+            self, Nonce as ExternalAsymmetricNonce, NONCEBYTES as EXTERNALASYMMETRICNONCEBYTES,
+        },
+        secretbox::{
+            self, Nonce as ExternalSymmetricNonce, NONCEBYTES as EXTERNALSYMMETRICNONCEBYTES,
+        },
+    },
+    randombytes,
 };
+use std::convert::TryInto;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SodiumOxideSymmetricNonce {
@@ -13,28 +22,54 @@ pub struct SodiumOxideSymmetricNonce {
     pub nonce: ExternalSymmetricNonce,
 }
 
-/// Custom serialization function base64-encodes the bytes before storage
+/// Serializes nonce bytes base64-encoded in human-readable formats, and raw in binary
+/// formats where base64 would only bloat them
+pub(super) fn nonce_bytes_serialize<S>(bytes: &[u8], s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    if s.is_human_readable() {
+        let b64_encoded = base64::encode(bytes);
+        s.serialize_some(&Some(b64_encoded))
+    } else {
+        s.serialize_bytes(bytes)
+    }
+}
+
+/// Deserializes nonce bytes written by `nonce_bytes_serialize`
+pub(super) fn nonce_bytes_deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    if !deserializer.is_human_readable() {
+        let bytes: serde_bytes::ByteBuf = de::Deserialize::deserialize(deserializer)?;
+        return Ok(bytes.into_vec());
+    }
+    let b64_encoded: String = de::Deserialize::deserialize(deserializer)?;
+    base64::decode(b64_encoded).map_err(de::Error::custom)
+}
+
+/// Custom serialization function, see `nonce_bytes_serialize`
 fn symmetric_nonce_serialize<S>(nonce: &ExternalSymmetricNonce, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let b64_encoded = base64::encode(nonce.as_ref());
-    s.serialize_some(&Some(b64_encoded))
+    nonce_bytes_serialize(nonce.as_ref(), s)
 }
 
-/// Custom deserialization function base64-decodes the bytes before passing them back
+/// Custom deserialization function, see `nonce_bytes_deserialize`
 fn symmetric_nonce_deserialize<'de, D>(deserializer: D) -> Result<ExternalSymmetricNonce, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let b64_encoded: String = de::Deserialize::deserialize(deserializer)?;
-    let decoded = base64::decode(b64_encoded).map_err(de::Error::custom)?;
+    let decoded = nonce_bytes_deserialize(deserializer)?;
     let nonce = ExternalSymmetricNonce::from_slice(decoded.as_ref());
     match nonce {
         Some(n) => Ok(n),
         None => Err(de::Error::custom(format!(
-            "deserialized nonce was {} bytes long, expected 24 bytes",
-            decoded.len()
+            "deserialized nonce was {} bytes long, expected {} bytes",
+            decoded.len(),
+            EXTERNALSYMMETRICNONCEBYTES
         ))),
     }
 }
@@ -48,6 +83,15 @@ impl SodiumOxideSymmetricNonce {
         })
     }
 
+    /// Builds a nonce from the given bytes, returning an `InvalidKeyLength` error
+    /// carrying the expected nonce size if the slice is the wrong length
+    pub fn try_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_slice(bs).ok_or(CryptoError::InvalidKeyLength {
+            expected: Self::NONCEBYTES,
+            actual: bs.len(),
+        })
+    }
+
     pub fn new() -> Self {
         SodiumOxideSymmetricNonce {
             nonce: secretbox::gen_nonce(),
@@ -64,30 +108,29 @@ pub struct SodiumOxideAsymmetricNonce {
     pub nonce: ExternalAsymmetricNonce,
 }
 
-/// Custom serialization function base64-encodes the bytes before storage
+/// Custom serialization function, see `nonce_bytes_serialize`
 fn asymmetric_nonce_serialize<S>(nonce: &ExternalAsymmetricNonce, s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
 {
-    let b64_encoded = base64::encode(nonce.as_ref());
-    s.serialize_some(&Some(b64_encoded))
+    nonce_bytes_serialize(nonce.as_ref(), s)
 }
 
-/// Custom deserialization function base64-decodes the bytes before passing them back
+/// Custom deserialization function, see `nonce_bytes_deserialize`
 fn asymmetric_nonce_deserialize<'de, D>(
     deserializer: D,
 ) -> Result<ExternalAsymmetricNonce, D::Error>
 where
     D: Deserializer<'de>,
 {
-    let b64_encoded: String = de::Deserialize::deserialize(deserializer)?;
-    let decoded = base64::decode(b64_encoded).map_err(de::Error::custom)?;
+    let decoded = nonce_bytes_deserialize(deserializer)?;
     let nonce = ExternalAsymmetricNonce::from_slice(decoded.as_ref());
     match nonce {
         Some(n) => Ok(n),
         None => Err(de::Error::custom(format!(
-            "deserialized nonce was {} bytes long, expected 24 bytes",
-            decoded.len()
+            "deserialized nonce was {} bytes long, expected {} bytes",
+            decoded.len(),
+            EXTERNALASYMMETRICNONCEBYTES
         ))),
     }
 }
@@ -101,9 +144,145 @@ impl SodiumOxideAsymmetricNonce {
         })
     }
 
+    /// Builds a nonce from the given bytes, returning an `InvalidKeyLength` error
+    /// carrying the expected nonce size if the slice is the wrong length
+    pub fn try_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_slice(bs).ok_or(CryptoError::InvalidKeyLength {
+            expected: Self::NONCEBYTES,
+            actual: bs.len(),
+        })
+    }
+
     pub fn new() -> Self {
         SodiumOxideAsymmetricNonce {
             nonce: box_::gen_nonce(),
         }
     }
 }
+
+/// Nonce for libsodium's crypto_box_curve25519xchacha20poly1305, which sodiumoxide does
+/// not wrap. It is the same length as a `box_` nonce, but XChaCha20 is designed for
+/// nonces to be picked at random for every message.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SodiumOxideXChaCha20AsymmetricNonce {
+    #[serde(
+        serialize_with = "xchacha20_asymmetric_nonce_serialize",
+        deserialize_with = "xchacha20_asymmetric_nonce_deserialize"
+    )]
+    pub nonce: [u8; SodiumOxideXChaCha20AsymmetricNonce::NONCEBYTES],
+}
+
+/// Custom serialization function, see `nonce_bytes_serialize`
+fn xchacha20_asymmetric_nonce_serialize<S>(
+    nonce: &[u8; SodiumOxideXChaCha20AsymmetricNonce::NONCEBYTES],
+    s: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    nonce_bytes_serialize(nonce, s)
+}
+
+/// Custom deserialization function, see `nonce_bytes_deserialize`
+fn xchacha20_asymmetric_nonce_deserialize<'de, D>(
+    deserializer: D,
+) -> Result<[u8; SodiumOxideXChaCha20AsymmetricNonce::NONCEBYTES], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let decoded = nonce_bytes_deserialize(deserializer)?;
+    decoded.as_slice().try_into().map_err(|_| {
+        de::Error::custom(format!(
+            "deserialized nonce was {} bytes long, expected {} bytes",
+            decoded.len(),
+            SodiumOxideXChaCha20AsymmetricNonce::NONCEBYTES
+        ))
+    })
+}
+
+impl SodiumOxideXChaCha20AsymmetricNonce {
+    pub const NONCEBYTES: usize =
+        libsodium_sys::crypto_box_curve25519xchacha20poly1305_NONCEBYTES as usize;
+
+    pub fn from_slice(bs: &[u8]) -> Option<Self> {
+        Some(SodiumOxideXChaCha20AsymmetricNonce {
+            nonce: bs.try_into().ok()?,
+        })
+    }
+
+    /// Builds a nonce from the given bytes, returning an `InvalidKeyLength` error
+    /// carrying the expected nonce size if the slice is the wrong length
+    pub fn try_from_slice(bs: &[u8]) -> Result<Self, CryptoError> {
+        Self::from_slice(bs).ok_or(CryptoError::InvalidKeyLength {
+            expected: Self::NONCEBYTES,
+            actual: bs.len(),
+        })
+    }
+
+    pub fn new() -> Self {
+        let mut nonce = [0u8; Self::NONCEBYTES];
+        randombytes::randombytes_into(&mut nonce);
+        SodiumOxideXChaCha20AsymmetricNonce { nonce }
+    }
+}
+
+impl Default for SodiumOxideXChaCha20AsymmetricNonce {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        SodiumOxideAsymmetricNonce, SodiumOxideSymmetricNonce, SodiumOxideXChaCha20AsymmetricNonce,
+    };
+    use crate::CryptoError;
+
+    #[test]
+    fn test_symmetric_nonce_try_from_slice() {
+        assert!(SodiumOxideSymmetricNonce::try_from_slice(&[0u8; 24]).is_ok());
+        for len in [0, 23, 25, 32].iter() {
+            match SodiumOxideSymmetricNonce::try_from_slice(&vec![0u8; *len]) {
+                Err(CryptoError::InvalidKeyLength { expected, actual }) => {
+                    assert_eq!(expected, SodiumOxideSymmetricNonce::NONCEBYTES);
+                    assert_eq!(actual, *len);
+                }
+                _ => panic!("Building a {}-byte nonce should have failed", len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_asymmetric_nonce_try_from_slice() {
+        assert!(SodiumOxideAsymmetricNonce::try_from_slice(&[0u8; 24]).is_ok());
+        for len in [0, 23, 25, 32].iter() {
+            match SodiumOxideAsymmetricNonce::try_from_slice(&vec![0u8; *len]) {
+                Err(CryptoError::InvalidKeyLength { expected, actual }) => {
+                    assert_eq!(expected, SodiumOxideAsymmetricNonce::NONCEBYTES);
+                    assert_eq!(actual, *len);
+                }
+                _ => panic!("Building a {}-byte nonce should have failed", len),
+            }
+        }
+    }
+
+    #[test]
+    fn test_xchacha20_asymmetric_nonce_serde() {
+        let nonce = SodiumOxideXChaCha20AsymmetricNonce::new();
+        assert_ne!(nonce, SodiumOxideXChaCha20AsymmetricNonce::new());
+        let json = serde_json::to_string(&nonce).unwrap();
+        let decoded: SodiumOxideXChaCha20AsymmetricNonce = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, nonce);
+        assert!(SodiumOxideXChaCha20AsymmetricNonce::try_from_slice(&[0u8; 23]).is_err());
+    }
+
+    #[test]
+    fn test_nonce_deserialize_invalid_length() {
+        let json = format!("{{\"nonce\":\"{}\"}}", base64::encode([0u8; 12]));
+        let err = serde_json::from_str::<SodiumOxideSymmetricNonce>(&json).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("deserialized nonce was 12 bytes long, expected 24 bytes"));
+    }
+}
@@ -2,25 +2,61 @@
 //! implementations available are sources of bytes. A source provides an interface
 //! for read/write operations on the set of bytes it covers.
 
-use crate::CryptoError;
+use crate::{clock, CryptoError};
 use base64::DecodeError;
 use chrono::{DateTime, Utc};
 use filetime::FileTime;
 use once_cell::sync::OnceCell;
 use serde::{
     de::{self, Deserializer},
+    ser::SerializeStruct,
     Deserialize, Serialize, Serializer,
 };
 use std::{
     convert::{Into, TryInto},
+    env::{self, VarError},
     error::Error,
     fmt::{self, Display, Formatter},
-    io::{self, ErrorKind},
-    path::PathBuf as StdPathBuf,
+    io::{self, ErrorKind, Read, Seek, SeekFrom},
+    path::{Path as StdPath, PathBuf as StdPathBuf},
     str::FromStr,
     time::SystemTime,
 };
 
+/// Alphabet used by a byte source when base64-encoding its bytes for serialization or
+/// file storage. Decoding always accepts either alphabet.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Base64Alphabet {
+    /// The standard alphabet, which includes `+` and `/`
+    Standard,
+    /// The URL and filename safe alphabet, which uses `-` and `_` instead
+    UrlSafe,
+}
+
+impl Default for Base64Alphabet {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+fn encode_base64<T: AsRef<[u8]>>(bytes: T, alphabet: Base64Alphabet) -> String {
+    match alphabet {
+        Base64Alphabet::Standard => base64::encode_config(bytes, base64::STANDARD),
+        Base64Alphabet::UrlSafe => base64::encode_config(bytes, base64::URL_SAFE),
+    }
+}
+
+/// Decodes base64 in either alphabet. The alphabets only differ in two characters,
+/// so input containing neither decodes identically under both.
+fn decode_base64<T: AsRef<[u8]>>(encoded: T) -> Result<Vec<u8>, DecodeError> {
+    let encoded = encoded.as_ref();
+    if encoded.iter().any(|&c| c == b'-' || c == b'_') {
+        base64::decode_config(encoded, base64::URL_SAFE)
+    } else {
+        base64::decode_config(encoded, base64::STANDARD)
+    }
+}
+
 #[derive(Debug)]
 pub enum NotFoundKind {
     File(String),
@@ -46,6 +82,18 @@ pub enum SourceError {
 
     /// Error happened when decoding base64 string
     Base64Decode { source: DecodeError },
+
+    /// Environment variable read by an env byte source is not set
+    EnvVarNotFound { name: String },
+
+    /// Environment variable read by an env byte source was not valid UTF-8
+    EnvVarIsInvalidUTF8 { name: String },
+
+    /// Env byte sources are read-only and cannot be set
+    EnvVarIsReadOnly { name: String },
+
+    /// Requested range of bytes cannot be addressed in a file
+    RangeOutOfBounds { offset: u64, len: usize },
 }
 
 impl Error for SourceError {
@@ -57,6 +105,10 @@ impl Error for SourceError {
             SourceError::FilePathIsInvalidUTF8 => None,
             SourceError::FileMetadataIsInvalid => None,
             SourceError::Base64Decode { ref source } => Some(source),
+            SourceError::EnvVarNotFound { .. } => None,
+            SourceError::EnvVarIsInvalidUTF8 { .. } => None,
+            SourceError::EnvVarIsReadOnly { .. } => None,
+            SourceError::RangeOutOfBounds { .. } => None,
         }
     }
 }
@@ -87,6 +139,26 @@ impl Display for SourceError {
             SourceError::Base64Decode { .. } => {
                 write!(f, "Error occurred while decoding string from base64")
             }
+            SourceError::EnvVarNotFound { ref name } => {
+                write!(f, "Environment variable \"{}\" is not set", name)
+            }
+            SourceError::EnvVarIsInvalidUTF8 { ref name } => {
+                write!(f, "Environment variable \"{}\" was not valid UTF-8", name)
+            }
+            SourceError::EnvVarIsReadOnly { ref name } => {
+                write!(
+                    f,
+                    "Environment variable \"{}\" is read-only and cannot be set",
+                    name
+                )
+            }
+            SourceError::RangeOutOfBounds { offset, len } => {
+                write!(
+                    f,
+                    "Range of {} bytes at offset {} cannot be addressed",
+                    len, offset
+                )
+            }
         }
     }
 }
@@ -94,9 +166,11 @@ impl Display for SourceError {
 impl From<SourceError> for CryptoError {
     fn from(mse: SourceError) -> Self {
         match mse {
-            SourceError::NotFound { .. } => CryptoError::NotFound {
-                source: Box::new(mse),
-            },
+            SourceError::NotFound { .. } | SourceError::EnvVarNotFound { .. } => {
+                CryptoError::NotFound {
+                    source: Box::new(mse),
+                }
+            }
             _ => CryptoError::InternalError {
                 source: Box::new(mse),
             },
@@ -121,11 +195,13 @@ pub enum Source {
 /// Currently supported:
 /// - Fs: data stored on the filesystem
 /// - Vector: data stored in a vector of bytes
+/// - Env: data stored in an environment variable
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "t", content = "c")]
 pub enum ByteSource {
     Fs(FsByteSource),
     Vector(VectorByteSource),
+    Env(EnvByteSource),
 }
 
 impl ByteSource {
@@ -134,6 +210,7 @@ impl ByteSource {
         match self {
             ByteSource::Fs(fsbks) => fsbks.set(value),
             ByteSource::Vector(vbks) => vbks.set(value),
+            ByteSource::Env(ebks) => ebks.set(value),
         }
     }
 
@@ -147,6 +224,7 @@ impl ByteSource {
         match self {
             ByteSource::Fs(fsbks) => fsbks.set_last_modified(value, last_modified),
             ByteSource::Vector(vbks) => vbks.set_last_modified(value, last_modified),
+            ByteSource::Env(ebks) => ebks.set_last_modified(value, last_modified),
         }
     }
 
@@ -155,6 +233,7 @@ impl ByteSource {
         match self {
             ByteSource::Fs(fsbks) => fsbks.get(),
             ByteSource::Vector(vbks) => vbks.get(),
+            ByteSource::Env(ebks) => ebks.get(),
         }
     }
 
@@ -163,8 +242,24 @@ impl ByteSource {
         match self {
             ByteSource::Fs(fsbks) => fsbks.get_last_modified(),
             ByteSource::Vector(vbks) => vbks.get_last_modified(),
+            ByteSource::Env(ebks) => ebks.get_last_modified(),
         }
     }
+
+    /// Returns the number of bytes stored by the source. A filesystem source that has
+    /// not been read yet computes it from the size of the file, without reading it.
+    pub fn len(&self) -> Result<usize, SourceError> {
+        match self {
+            ByteSource::Fs(fsbks) => fsbks.len(),
+            ByteSource::Vector(vbks) => Ok(vbks.get()?.len()),
+            ByteSource::Env(ebks) => Ok(ebks.get()?.len()),
+        }
+    }
+
+    /// Returns true if the source stores no bytes
+    pub fn is_empty(&self) -> Result<bool, SourceError> {
+        Ok(self.len()? == 0)
+    }
 }
 
 impl From<&[u8]> for ByteSource {
@@ -179,6 +274,12 @@ impl From<&str> for ByteSource {
     }
 }
 
+impl From<Vec<u8>> for ByteSource {
+    fn from(value: Vec<u8>) -> Self {
+        ByteSource::Vector(value.into())
+    }
+}
+
 /// Represents a valid path
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Path {
@@ -227,6 +328,8 @@ impl FromStr for Path {
 pub struct FsByteSource {
     path: Path,
     #[serde(skip)]
+    alphabet: Base64Alphabet,
+    #[serde(skip)]
     cached: OnceCell<VectorByteSource>,
 }
 
@@ -260,7 +363,24 @@ impl FsByteSource {
     /// Creates an `FsBytesSource` from a path on the filesystem
     pub fn new(path: Path) -> Self {
         let cached = OnceCell::new();
-        FsByteSource { path, cached }
+        FsByteSource {
+            path,
+            alphabet: Base64Alphabet::Standard,
+            cached,
+        }
+    }
+
+    /// Sets the alphabet the bytes are base64-encoded in when written to the file. The
+    /// setting is not serialized with the source, and files written in either alphabet
+    /// can always be read.
+    pub fn with_base64_alphabet(mut self, alphabet: Base64Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Returns the alphabet the bytes are base64-encoded in when written to the file
+    pub fn base64_alphabet(&self) -> Base64Alphabet {
+        self.alphabet
     }
 
     /// Reads a `VectorBytesSource` from a path on the filesystem
@@ -281,7 +401,7 @@ impl FsByteSource {
             _ => SourceError::FsIoError { source: e },
         })?;
         let bytes =
-            base64::decode(read_bytes).map_err(|e| SourceError::Base64Decode { source: e })?;
+            decode_base64(read_bytes).map_err(|e| SourceError::Base64Decode { source: e })?;
 
         // Get last modified time
         let metadata = std::fs::metadata(path_ref).map_err(|e| match e.kind() {
@@ -305,9 +425,57 @@ impl FsByteSource {
         self.cached.take();
     }
 
-    /// Re-writes the file at the path to the given bytes
+    /// Re-writes the file at the path to the given bytes. The write is left to the
+    /// operating system to flush, so the bytes can be lost on a crash even though this
+    /// returned successfully; use `set_durable` where that matters.
     pub fn set(&mut self, value: &[u8]) -> Result<(), SourceError> {
-        self.set_last_modified(value, Utc::now())
+        self.set_last_modified(value, clock::now())
+    }
+
+    /// Re-writes the file at the path to the given bytes like `set`, but only returns
+    /// once the file's contents and metadata have been flushed to disk. On unix the
+    /// parent directory is flushed as well, so that a newly created file's directory
+    /// entry also survives a crash.
+    pub fn set_durable(&mut self, value: &[u8]) -> Result<(), SourceError> {
+        self.write(value, clock::now(), true)
+    }
+
+    /// Re-writes the file at the path to the given bytes without ever leaving it partially
+    /// written. The bytes are written and flushed to a temporary file in the same
+    /// directory, which is then renamed over the target. Since a rename within a single
+    /// filesystem is atomic, a crash at any point leaves the file holding either its old
+    /// or its new contents. On unix the directory is flushed after the rename so that the
+    /// new contents survive a crash; a temporary file may be left behind by a crash before
+    /// the rename, but is removed on any error that is returned.
+    pub fn set_atomic(&mut self, value: &[u8]) -> Result<(), SourceError> {
+        let path_ref: &StdPathBuf = (&self.path).into();
+        let parent = match path_ref.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => StdPathBuf::from("."),
+        };
+        let file_name = path_ref
+            .file_name()
+            .ok_or_else(|| SourceError::FsIoError {
+                source: io::Error::new(ErrorKind::InvalidInput, "path has no file name"),
+            })?
+            .to_string_lossy()
+            .into_owned();
+        std::fs::create_dir_all(&parent).map_err(|source| SourceError::FsIoError { source })?;
+
+        let temp_path = parent.join(format!(".{}.{}.tmp", file_name, uuid::Uuid::new_v4()));
+        let result = write_and_replace(
+            &temp_path,
+            path_ref,
+            &encode_base64(value, self.alphabet),
+            clock::now(),
+        );
+        if result.is_err() {
+            let _ = std::fs::remove_file(&temp_path);
+        }
+        result.map_err(|source| SourceError::FsIoError { source })?;
+
+        self.reload();
+        Ok(())
     }
 
     /// Sets the bytes of the source to the given value with the given
@@ -316,6 +484,15 @@ impl FsByteSource {
         &mut self,
         value: &[u8],
         last_modified: DateTime<Utc>,
+    ) -> Result<(), SourceError> {
+        self.write(value, last_modified, false)
+    }
+
+    fn write(
+        &mut self,
+        value: &[u8],
+        last_modified: DateTime<Utc>,
+        durable: bool,
     ) -> Result<(), SourceError> {
         let path_ref: &StdPathBuf = (&self.path).into();
         let path_str = self
@@ -326,7 +503,7 @@ impl FsByteSource {
             .into_string()
             .unwrap_or_else(|_| "<Invalid UTF8>".to_owned());
         let path_parent = path_ref.parent();
-        let bytes = base64::encode(value);
+        let bytes = encode_base64(value, self.alphabet);
 
         // If the path contains parent directories, try to create the chain of
         // directories first before making the file
@@ -351,6 +528,10 @@ impl FsByteSource {
         filetime::set_file_mtime(path_ref, file_time)
             .map_err(|e| SourceError::FsIoError { source: e })?;
 
+        if durable {
+            sync_to_disk(path_ref).map_err(|source| SourceError::FsIoError { source })?;
+        }
+
         // Invalidate our cache
         self.reload();
 
@@ -364,6 +545,65 @@ impl FsByteSource {
             .get()
     }
 
+    /// Returns up to `len` decoded bytes starting at the decoded byte `offset`. Only the
+    /// base64 blocks covering the range are read from the file, unless the whole file
+    /// has already been cached. The result is shorter than `len` if the range runs past
+    /// the end of the file. A range whose end or encoded position overflows a u64 is
+    /// refused with `SourceError::RangeOutOfBounds`.
+    pub fn get_range(&self, offset: u64, len: usize) -> Result<Vec<u8>, SourceError> {
+        if let Some(cached) = self.cached.get() {
+            let bytes = cached.get()?;
+            let start = (offset.min(bytes.len() as u64)) as usize;
+            let end = start.saturating_add(len).min(bytes.len());
+            return Ok(bytes[start..end].to_vec());
+        }
+        if len == 0 {
+            return Ok(vec![]);
+        }
+
+        // Every 3 decoded bytes are encoded as a block of 4 characters, so the read
+        // has to start and end on block boundaries around the requested range
+        let out_of_bounds = || SourceError::RangeOutOfBounds { offset, len };
+        let first_block = offset / 3;
+        let last_block = offset
+            .checked_add(len as u64)
+            .and_then(|end| end.checked_add(2))
+            .ok_or_else(out_of_bounds)?
+            / 3;
+        let encoded_start = first_block.checked_mul(4).ok_or_else(out_of_bounds)?;
+        let encoded_len = (last_block - first_block)
+            .checked_mul(4)
+            .ok_or_else(out_of_bounds)?;
+        let skip = (offset % 3) as usize;
+
+        let path_ref: &StdPathBuf = (&self.path).into();
+        let path_str = self
+            .path
+            .path
+            .clone()
+            .into_os_string()
+            .into_string()
+            .unwrap_or_else(|_| "<Invalid UTF8>".to_owned());
+        let mut file = std::fs::File::open(path_ref).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => SourceError::NotFound {
+                kind: NotFoundKind::File(path_str),
+            },
+            _ => SourceError::FsIoError { source: e },
+        })?;
+        file.seek(SeekFrom::Start(encoded_start))
+            .map_err(|source| SourceError::FsIoError { source })?;
+        let mut encoded = vec![];
+        file.take(encoded_len)
+            .read_to_end(&mut encoded)
+            .map_err(|source| SourceError::FsIoError { source })?;
+
+        let decoded =
+            decode_base64(encoded).map_err(|e| SourceError::Base64Decode { source: e })?;
+        let start = skip.min(decoded.len());
+        let end = start.saturating_add(len).min(decoded.len());
+        Ok(decoded[start..end].to_vec())
+    }
+
     /// Gets the timestamp for when this ByteSource was last modified
     pub fn get_last_modified(&self) -> Result<&DateTime<Utc>, SourceError> {
         self.cached
@@ -371,6 +611,56 @@ impl FsByteSource {
             .get_last_modified()
     }
 
+    /// Returns the number of decoded bytes stored at the path. Unless the file has
+    /// already been cached, this is computed from the file's size and its trailing
+    /// padding, so only the last two characters are read.
+    pub fn len(&self) -> Result<usize, SourceError> {
+        if let Some(cached) = self.cached.get() {
+            return Ok(cached.get()?.len());
+        }
+
+        let path_ref: &StdPathBuf = (&self.path).into();
+        let path_str = self
+            .path
+            .path
+            .clone()
+            .into_os_string()
+            .into_string()
+            .unwrap_or_else(|_| "<Invalid UTF8>".to_owned());
+        let mut file = std::fs::File::open(path_ref).map_err(|e| match e.kind() {
+            ErrorKind::NotFound => SourceError::NotFound {
+                kind: NotFoundKind::File(path_str),
+            },
+            _ => SourceError::FsIoError { source: e },
+        })?;
+        let size = file
+            .metadata()
+            .map_err(|source| SourceError::FsIoError { source })?
+            .len();
+
+        let mut tail = vec![];
+        file.seek(SeekFrom::Start(size.saturating_sub(2)))
+            .map_err(|source| SourceError::FsIoError { source })?;
+        file.read_to_end(&mut tail)
+            .map_err(|source| SourceError::FsIoError { source })?;
+        let padding = tail.iter().rev().take_while(|&&c| c == b'=').count() as u64;
+
+        // Every 4 characters decode to 3 bytes, and a trailing partial block of 2 or 3
+        // characters decodes to 1 or 2 bytes
+        let chars = size - padding;
+        let partial = match chars % 4 {
+            2 => 1,
+            3 => 2,
+            _ => 0,
+        };
+        Ok((chars / 4 * 3 + partial) as usize)
+    }
+
+    /// Returns true if the file at the path stores no bytes
+    pub fn is_empty(&self) -> Result<bool, SourceError> {
+        Ok(self.len()? == 0)
+    }
+
     /// Returns the path where the key is stored
     pub fn path(&self) -> &Path {
         &self.path
@@ -383,39 +673,264 @@ impl From<FsByteSource> for ByteSource {
     }
 }
 
-/// A source that is an array of bytes in memory
+/// Flushes the file's contents and metadata to disk and, on unix, the directory holding
+/// it, since fsyncing a file does not guarantee its directory entry was persisted
+fn sync_to_disk(path: &StdPath) -> Result<(), io::Error> {
+    std::fs::OpenOptions::new()
+        .write(true)
+        .open(path)?
+        .sync_all()?;
+
+    #[cfg(unix)]
+    {
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => StdPath::new("."),
+        };
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Writes the contents to the temporary file and flushes it, then renames it over the
+/// target and, on unix, flushes the directory holding both. On unix the temporary file
+/// is created readable and writable by its owner only, whatever the umask, so the key
+/// is never exposed to other users and the target ends up with the same permissions.
+fn write_and_replace(
+    temp_path: &StdPath,
+    target: &StdPath,
+    contents: &str,
+    last_modified: DateTime<Utc>,
+) -> Result<(), io::Error> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(temp_path)?;
+    io::Write::write_all(&mut file, contents.as_bytes())?;
+    filetime::set_file_handle_times(
+        &file,
+        None,
+        Some(FileTime::from_system_time(SystemTime::from(last_modified))),
+    )?;
+    file.sync_all()?;
+    drop(file);
+    std::fs::rename(temp_path, target)?;
+
+    #[cfg(unix)]
+    {
+        let parent = match target.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => StdPath::new("."),
+        };
+        std::fs::File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+/// Encoding of the value of an environment variable read by an `EnvByteSource`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum EnvEncoding {
+    /// The value's UTF-8 bytes are used as is
+    Raw,
+    /// The value is base64-encoded in either alphabet, surrounding whitespace is ignored
+    Base64,
+}
+
+/// A source that is the value of an environment variable, such as a secret injected
+/// into a container. Only the variable's name is serialized. The variable is read and
+/// decoded on every call to get(), so a changed value is picked up right away. The
+/// source is read-only, setting it returns `SourceError::EnvVarIsReadOnly`.
 #[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EnvByteSource {
+    name: String,
+    encoding: EnvEncoding,
+    #[serde(skip)]
+    reads: EnvReads,
+}
+
+/// Decoded values of an environment variable, kept so that the bytes returned by get()
+/// remain valid while the variable is read again. A value is only appended when the
+/// variable changed since the last read, so memory only grows with the number of
+/// distinct values the variable held.
+#[derive(Debug, Clone, Default)]
+struct EnvReads {
+    value: OnceCell<(String, VectorByteSource)>,
+    next: OnceCell<Box<EnvReads>>,
+}
+
+impl EnvReads {
+    /// Returns the decoded bytes of the given raw value, decoding it only if it differs
+    /// from the last value read
+    fn latest(
+        &self,
+        raw: String,
+        decode: impl Fn(&str) -> Result<VectorByteSource, SourceError>,
+    ) -> Result<&VectorByteSource, SourceError> {
+        let mut node = self;
+        loop {
+            while let Some(next) = node.next.get() {
+                node = next;
+            }
+            match node.value.get() {
+                Some((last, bytes)) if *last == raw => return Ok(bytes),
+                Some(_) => {
+                    let bytes = decode(&raw)?;
+                    let value = OnceCell::new();
+                    let _ = value.set((raw.clone(), bytes));
+                    // Another reader may have appended first, in which case its value is
+                    // compared on the next iteration
+                    node = node.next.get_or_init(|| {
+                        Box::new(EnvReads {
+                            value,
+                            next: OnceCell::new(),
+                        })
+                    });
+                }
+                None => {
+                    node.value
+                        .get_or_try_init(|| Ok::<_, SourceError>((raw.clone(), decode(&raw)?)))?;
+                }
+            }
+        }
+    }
+}
+
+impl EnvByteSource {
+    /// Creates an `EnvByteSource` reading the environment variable with the given name
+    pub fn new<S: Into<String>>(name: S, encoding: EnvEncoding) -> Self {
+        EnvByteSource {
+            name: name.into(),
+            encoding,
+            reads: EnvReads::default(),
+        }
+    }
+
+    /// Reads the current value of the environment variable and decodes it
+    fn read(&self) -> Result<&VectorByteSource, SourceError> {
+        let raw = env::var(&self.name).map_err(|e| match e {
+            VarError::NotPresent => SourceError::EnvVarNotFound {
+                name: self.name.clone(),
+            },
+            VarError::NotUnicode(_) => SourceError::EnvVarIsInvalidUTF8 {
+                name: self.name.clone(),
+            },
+        })?;
+        self.reads.latest(raw, |raw| {
+            let bytes = match self.encoding {
+                EnvEncoding::Raw => raw.as_bytes().to_vec(),
+                EnvEncoding::Base64 => decode_base64(raw.trim())
+                    .map_err(|e| SourceError::Base64Decode { source: e })?,
+            };
+            Ok(bytes.into())
+        })
+    }
+
+    /// Env byte sources are read-only, this always returns `EnvVarIsReadOnly`
+    pub fn set(&mut self, value: &[u8]) -> Result<(), SourceError> {
+        self.set_last_modified(value, clock::now())
+    }
+
+    /// Env byte sources are read-only, this always returns `EnvVarIsReadOnly`
+    pub fn set_last_modified(
+        &mut self,
+        _value: &[u8],
+        _last_modified: DateTime<Utc>,
+    ) -> Result<(), SourceError> {
+        Err(SourceError::EnvVarIsReadOnly {
+            name: self.name.clone(),
+        })
+    }
+
+    /// Reads the variable and returns its decoded value
+    pub fn get(&self) -> Result<&[u8], SourceError> {
+        self.read()?.get()
+    }
+
+    /// Reads the variable and returns when its current value was first read
+    pub fn get_last_modified(&self) -> Result<&DateTime<Utc>, SourceError> {
+        self.read()?.get_last_modified()
+    }
+
+    /// Returns the name of the environment variable
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<EnvByteSource> for ByteSource {
+    fn from(e: EnvByteSource) -> Self {
+        Self::Env(e)
+    }
+}
+
+/// A source that is an array of bytes in memory
+#[derive(Deserialize, Debug, Clone)]
 pub struct VectorByteSource {
-    #[serde(
-        serialize_with = "byte_vector_serialize",
-        deserialize_with = "byte_vector_deserialize"
-    )]
+    #[serde(deserialize_with = "byte_vector_deserialize")]
     value: Option<Vec<u8>>,
     last_modified: DateTime<Utc>,
+    #[serde(skip)]
+    alphabet: Base64Alphabet,
 }
 
-/// Custom serialization function base64-encodes the bytes before storage
-fn byte_vector_serialize<S>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-{
-    match bytes {
-        Some(bytes) => {
-            let b64_encoded = base64::encode(bytes);
-            s.serialize_some(&Some(b64_encoded))
+impl Serialize for VectorByteSource {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = s.serialize_struct("VectorByteSource", 2)?;
+        state.serialize_field(
+            "value",
+            &EncodedBytes {
+                bytes: self.value.as_deref(),
+                alphabet: self.alphabet,
+            },
+        )?;
+        state.serialize_field("last_modified", &self.last_modified)?;
+        state.end()
+    }
+}
+
+/// Bytes of a `VectorByteSource` along with the alphabet to encode them in
+struct EncodedBytes<'a> {
+    bytes: Option<&'a [u8]>,
+    alphabet: Base64Alphabet,
+}
+
+/// Base64-encodes the bytes before storage in human-readable formats, and stores them
+/// raw in binary formats
+impl Serialize for EncodedBytes<'_> {
+    fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match self.bytes {
+            Some(bytes) if s.is_human_readable() => {
+                let b64_encoded = encode_base64(bytes, self.alphabet);
+                s.serialize_some(&Some(b64_encoded))
+            }
+            Some(bytes) => s.serialize_some(&serde_bytes::Bytes::new(bytes)),
+            None => s.serialize_none(),
         }
-        None => s.serialize_none(),
     }
 }
 
-/// Custom deserialization function base64-decodes the bytes before passing them back
+/// Custom deserialization function base64-decodes the bytes before passing them back,
+/// unless the format is binary and they were stored raw
 fn byte_vector_deserialize<'de, D>(deserializer: D) -> Result<Option<Vec<u8>>, D::Error>
 where
     D: Deserializer<'de>,
 {
+    if !deserializer.is_human_readable() {
+        let bytes: Option<serde_bytes::ByteBuf> = de::Deserialize::deserialize(deserializer)?;
+        return Ok(bytes.map(serde_bytes::ByteBuf::into_vec));
+    }
     let b64_encoded: Option<String> = de::Deserialize::deserialize(deserializer)?;
     match b64_encoded {
-        Some(bytes) => Ok(Some(base64::decode(bytes).map_err(de::Error::custom)?)),
+        Some(bytes) => Ok(Some(decode_base64(bytes).map_err(de::Error::custom)?)),
         None => Ok(None),
     }
 }
@@ -423,7 +938,7 @@ where
 impl VectorByteSource {
     /// Creates a new `VectorBytesSource` from the given byte array
     pub fn new_now(value: Option<&[u8]>) -> Self {
-        Self::new(value, Utc::now())
+        Self::new(value, clock::now())
     }
 
     /// Creates a new `VectorBytesSource` from the given byte array
@@ -432,17 +947,32 @@ impl VectorByteSource {
             Some(value) => VectorByteSource {
                 value: Some(value.to_vec()),
                 last_modified,
+                alphabet: Base64Alphabet::Standard,
             },
             None => VectorByteSource {
                 value: None,
                 last_modified,
+                alphabet: Base64Alphabet::Standard,
             },
         }
     }
 
+    /// Sets the alphabet the bytes are base64-encoded in when serialized to a
+    /// human-readable format. The setting is not itself serialized, and either
+    /// alphabet is accepted when deserializing.
+    pub fn with_base64_alphabet(mut self, alphabet: Base64Alphabet) -> Self {
+        self.alphabet = alphabet;
+        self
+    }
+
+    /// Returns the alphabet the bytes are base64-encoded in when serialized
+    pub fn base64_alphabet(&self) -> Base64Alphabet {
+        self.alphabet
+    }
+
     /// Re-writes the source to the given bytes
     pub fn set(&mut self, value: &[u8]) -> Result<(), SourceError> {
-        self.set_last_modified(value, Utc::now())
+        self.set_last_modified(value, clock::now())
     }
 
     /// Sets the bytes of the source to the given value with the given
@@ -485,8 +1015,232 @@ impl From<&str> for VectorByteSource {
     }
 }
 
+impl From<Vec<u8>> for VectorByteSource {
+    fn from(value: Vec<u8>) -> Self {
+        VectorByteSource {
+            value: Some(value),
+            last_modified: clock::now(),
+            alphabet: Base64Alphabet::Standard,
+        }
+    }
+}
+
 impl From<VectorByteSource> for ByteSource {
     fn from(v: VectorByteSource) -> Self {
         Self::Vector(v)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Base64Alphabet, ByteSource, EnvByteSource, EnvEncoding, FsByteSource, Path, SourceError,
+        VectorByteSource,
+    };
+    use crate::CryptoError;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_url_safe_base64_alphabet() {
+        // Encodes to "+/+/" in the standard alphabet
+        let bytes = [0xfb, 0xff, 0xbf];
+        let source = VectorByteSource::new_now(Some(&bytes[..]));
+        assert_eq!(source.base64_alphabet(), Base64Alphabet::Standard);
+        let standard = serde_json::to_string(&ByteSource::from(source.clone())).unwrap();
+        assert!(standard.contains("+/+/"));
+
+        // The alphabet is a setting of the source, others keep encoding as before
+        let url_safe_source = source.clone().with_base64_alphabet(Base64Alphabet::UrlSafe);
+        assert_eq!(url_safe_source.base64_alphabet(), Base64Alphabet::UrlSafe);
+        let url_safe = serde_json::to_string(&ByteSource::from(url_safe_source)).unwrap();
+        assert!(!url_safe.contains('+') && !url_safe.contains('/'));
+        assert!(url_safe.contains("-_-_"));
+        assert_eq!(
+            serde_json::to_string(&ByteSource::from(source)).unwrap(),
+            standard
+        );
+
+        // Either encoding is readable by any source
+        for json in [standard, url_safe].iter() {
+            let decoded: ByteSource = serde_json::from_str(json).unwrap();
+            assert_eq!(decoded.get().unwrap(), &bytes);
+        }
+
+        let path =
+            std::env::temp_dir().join(format!("redact-crypto-url-safe-{}", std::process::id()));
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap())
+            .with_base64_alphabet(Base64Alphabet::UrlSafe);
+        source.set(&bytes).unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "-_-_");
+        let reread = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        assert_eq!(reread.get().unwrap(), &bytes);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_fsbytesource_get_range() {
+        let bytes: Vec<u8> = (0..1000u32).map(|i| (i % 251) as u8).collect();
+        let path = std::env::temp_dir().join(format!("redact-crypto-{}", std::process::id()));
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        source.set(&bytes).unwrap();
+
+        for &(offset, len) in [(0, 10), (1, 1), (2, 5), (3, 3), (500, 137), (998, 2)].iter() {
+            assert_eq!(
+                source.get_range(offset as u64, len).unwrap(),
+                &bytes[offset..offset + len]
+            );
+        }
+        assert_eq!(source.get_range(995, 100).unwrap(), &bytes[995..]);
+        assert!(source.get_range(2000, 10).unwrap().is_empty());
+        assert!(source.get_range(10, 0).unwrap().is_empty());
+        for &(offset, len) in [(u64::MAX - 1, 10), (0, usize::MAX), (u64::MAX - 10, 1)].iter() {
+            match source.get_range(offset, len) {
+                Err(SourceError::RangeOutOfBounds { .. }) => (),
+                _ => panic!(
+                    "Range of {} bytes at {} should have been refused",
+                    len, offset
+                ),
+            }
+        }
+
+        // Ranges are served from the cache once the whole file has been read
+        assert_eq!(source.get().unwrap(), bytes.as_slice());
+        assert_eq!(source.get_range(500, 137).unwrap(), &bytes[500..637]);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_fsbytesource_len() {
+        let path = std::env::temp_dir().join(format!("redact-crypto-len-{}", std::process::id()));
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+
+        for len in 0..8 {
+            let bytes = vec![0xa5; len];
+            source.set(&bytes).unwrap();
+            assert_eq!(source.len().unwrap(), len);
+            assert!(source.cached.get().is_none());
+        }
+
+        let source: ByteSource = source.into();
+        source.get().unwrap();
+        assert_eq!(source.len().unwrap(), 7);
+        std::fs::remove_file(path).unwrap();
+        assert_eq!(source.len().unwrap(), 7);
+    }
+
+    #[test]
+    fn test_fsbytesource_set_durable() {
+        let path = std::env::temp_dir()
+            .join(format!("redact-crypto-durable-{}", std::process::id()))
+            .join("key");
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        source.set_durable(b"hello, world!").unwrap();
+        assert_eq!(source.get().unwrap(), b"hello, world!");
+
+        source.set_durable(b"goodbye").unwrap();
+        let reread = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        assert_eq!(reread.get().unwrap(), b"goodbye");
+        std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_fsbytesource_set_atomic() {
+        let dir = std::env::temp_dir().join(format!("redact-crypto-atomic-{}", std::process::id()));
+        let path = dir.join("key");
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        source.set(b"hello, world!").unwrap();
+        assert_eq!(source.get().unwrap(), b"hello, world!");
+
+        source.set_atomic(b"goodbye").unwrap();
+        assert_eq!(source.get().unwrap(), b"goodbye");
+        let reread = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        assert_eq!(reread.get().unwrap(), b"goodbye");
+
+        // Only the target remains, the temporary file having been renamed over it
+        let files = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec![std::ffi::OsString::from("key")]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_fsbytesource_set_atomic_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = std::env::temp_dir().join(format!(
+            "redact-crypto-atomic-permissions-{}",
+            std::process::id()
+        ));
+        let path = dir.join("key");
+        let mut source = FsByteSource::new(Path::from_str(path.to_str().unwrap()).unwrap());
+        source.set_atomic(b"hello, world!").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Replacing a file readable by others leaves it readable by its owner only
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o644)).unwrap();
+        source.set_atomic(b"goodbye").unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_envbytesource_decodes_base64() {
+        let name = format!("REDACT_CRYPTO_TEST_KEY_{}", std::process::id());
+        std::env::set_var(&name, "aGVsbG8sIHdvcmxkIQ==\n");
+        let source: ByteSource = EnvByteSource::new(name.as_str(), EnvEncoding::Base64).into();
+        assert_eq!(source.get().unwrap(), b"hello, world!");
+
+        // Only the variable's name is serialized, never its value
+        let json = serde_json::to_string(&source).unwrap();
+        assert!(json.contains(&name));
+        assert!(!json.contains("aGVsbG8"));
+        std::env::remove_var(&name);
+    }
+
+    #[test]
+    fn test_envbytesource_missing_var() {
+        let name = format!("REDACT_CRYPTO_TEST_MISSING_{}", std::process::id());
+        let source = EnvByteSource::new(name.as_str(), EnvEncoding::Raw);
+        match source.get() {
+            Err(SourceError::EnvVarNotFound { name: missing }) => assert_eq!(missing, name),
+            _ => panic!("Reading a missing variable should have failed"),
+        }
+        match CryptoError::from(source.get().unwrap_err()) {
+            CryptoError::NotFound { .. } => (),
+            _ => panic!("Missing variable should have been reported as not found"),
+        }
+    }
+
+    #[test]
+    fn test_envbytesource_reads_at_access_time() {
+        let name = format!("REDACT_CRYPTO_TEST_CHANGING_{}", std::process::id());
+        std::env::set_var(&name, "first");
+        let mut source = EnvByteSource::new(name.as_str(), EnvEncoding::Raw);
+        let first = source.get().unwrap();
+        assert_eq!(first, b"first");
+
+        // A changed variable is picked up, while bytes already returned remain valid
+        std::env::set_var(&name, "second");
+        assert_eq!(source.get().unwrap(), b"second");
+        assert_eq!(first, b"first");
+        std::env::set_var(&name, "first");
+        assert_eq!(source.get().unwrap(), b"first");
+
+        match source.set(b"third") {
+            Err(SourceError::EnvVarIsReadOnly { name: n }) => assert_eq!(n, name),
+            _ => panic!("Setting an env byte source should have failed"),
+        }
+        assert_eq!(std::env::var(&name).unwrap(), "first");
+
+        std::env::remove_var(&name);
+        match source.get() {
+            Err(SourceError::EnvVarNotFound { .. }) => (),
+            _ => panic!("Reading a removed variable should have failed"),
+        }
+    }
+}
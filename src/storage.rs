@@ -5,22 +5,92 @@
 //! Read operations allow for retrieval of data based on type information and the data's path.
 //!
 
+pub mod boxed;
+pub mod dualwrite;
+pub mod encrypting;
+pub mod filesystem;
 pub mod gcs;
+pub mod memory;
+pub mod metrics;
 pub mod mongodb;
 pub mod redact;
 pub mod selfstore;
 
-use crate::{CryptoError, Entry, StorableType};
+use crate::{trace::traced, CryptoError, Data, Entry, EntryPatch, StorableType, TypeBuilder};
 use ::mongodb::bson::Document;
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+/// Page size above which listing entries is refused unless the storer is configured
+/// otherwise
+pub const DEFAULT_MAX_PAGE_SIZE: i64 = 1000;
+
+pub(crate) fn default_max_page_size() -> i64 {
+    DEFAULT_MAX_PAGE_SIZE
+}
+
+/// Refuses a maximum page size that is not positive, as it would reject every page
+pub(crate) fn validate_max_page_size(max: i64) -> Result<i64, CryptoError> {
+    if max <= 0 {
+        Err(CryptoError::InvalidMaxPageSize { max })
+    } else {
+        Ok(max)
+    }
+}
+
+/// Refuses a page size that is not positive or exceeds the storer's maximum, so that
+/// a caller cannot make a backend fetch an unbounded number of entries at once
+pub(crate) fn check_page_size(page_size: i64, max: i64) -> Result<(), CryptoError> {
+    if page_size <= 0 || page_size > max {
+        Err(CryptoError::InvalidPageSize {
+            requested: page_size,
+            max,
+        })
+    } else {
+        Ok(())
+    }
+}
+
 pub trait HasIndex {
     type Index;
 
     fn get_index() -> Option<Self::Index>;
 }
 
+/// Generates the index document matching entries built by the given builder. The
+/// document is derived from the builder's serialized form, keeping only the nested
+/// `t` tags, so it cannot drift from how entries are actually stored. This produces
+/// the index of the concrete type; indexes of enclosing types such as `Key` are
+/// prefixes of it.
+pub fn index_from_builder<B: Into<TypeBuilder>>(builder: B) -> Result<Document, CryptoError> {
+    let builder: TypeBuilder = builder.into();
+    let serialized =
+        ::mongodb::bson::to_document(&builder).map_err(|e| CryptoError::InternalError {
+            source: Box::new(e),
+        })?;
+    Ok(::mongodb::bson::doc! {
+        "c": {
+            "builder": builder_tags(&serialized),
+        }
+    })
+}
+
+/// Reduces a serialized builder to its chain of `t` tags, dropping the contents of
+/// the innermost builder
+fn builder_tags(serialized: &Document) -> Document {
+    let mut tags = Document::new();
+    if let Some(t) = serialized.get("t") {
+        tags.insert("t", t.clone());
+    }
+    if let Ok(content) = serialized.get_document("c") {
+        if content.contains_key("t") {
+            tags.insert("c", builder_tags(content));
+        }
+    }
+    tags
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum TypeStorer {
     Indexed(IndexedTypeStorer),
@@ -30,23 +100,64 @@ pub enum TypeStorer {
 #[async_trait]
 impl Storer for TypeStorer {
     async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        traced!("delete", path, async {
+            match self {
+                TypeStorer::NonIndexed(ts) => ts.delete::<T>(path).await,
+                TypeStorer::Indexed(ts) => ts.delete::<T>(path).await,
+            }
+        })
+        .await
+    }
+
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        traced!("get", path, async {
+            match self {
+                TypeStorer::NonIndexed(ts) => ts.get(path).await,
+                TypeStorer::Indexed(ts) => ts.get(path).await,
+            }
+        })
+        .await
+    }
+
+    async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        traced!("create", &value.path, async move {
+            match self {
+                TypeStorer::NonIndexed(ts) => ts.create(value).await,
+                TypeStorer::Indexed(ts) => ts.create(value).await,
+            }
+        })
+        .await
+    }
+
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
         match self {
-            TypeStorer::NonIndexed(ts) => ts.delete::<T>(path).await,
-            TypeStorer::Indexed(ts) => ts.delete::<T>(path).await,
+            TypeStorer::NonIndexed(ts) => ts.apply_patch::<T>(path, patch).await,
+            TypeStorer::Indexed(ts) => ts.apply_patch::<T>(path, patch).await,
         }
     }
 
-    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
         match self {
-            TypeStorer::NonIndexed(ts) => ts.get(path).await,
-            TypeStorer::Indexed(ts) => ts.get(path).await,
+            TypeStorer::NonIndexed(ts) => ts.rename::<T>(from, to).await,
+            TypeStorer::Indexed(ts) => ts.rename::<T>(from, to).await,
         }
     }
 
-    async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+    async fn health_check(&self) -> Result<(), CryptoError> {
         match self {
-            TypeStorer::NonIndexed(ts) => ts.create(value).await,
-            TypeStorer::Indexed(ts) => ts.create(value).await,
+            TypeStorer::NonIndexed(ts) => ts.health_check().await,
+            TypeStorer::Indexed(ts) => ts.health_check().await,
+        }
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        match self {
+            TypeStorer::NonIndexed(ts) => ts.flush().await,
+            TypeStorer::Indexed(ts) => ts.flush().await,
         }
     }
 }
@@ -55,6 +166,7 @@ impl Storer for TypeStorer {
 pub enum IndexedTypeStorer {
     Redact(redact::RedactStorer),
     Mongo(mongodb::MongoStorer),
+    Memory(memory::MemoryStorer),
     Mock(tests::MockIndexedStorer),
 }
 
@@ -62,7 +174,12 @@ pub enum IndexedTypeStorer {
 pub enum NonIndexedTypeStorer {
     SelfStore(selfstore::SelfStorer),
     GoogleCloud(gcs::GoogleCloudStorer),
+    Filesystem(filesystem::FilesystemStorer),
+    Encrypting(Box<encrypting::EncryptingStorer<TypeStorer>>),
+    DualWrite(Box<dualwrite::DualWriteStorer>),
     Mock(tests::MockStorer),
+    #[serde(skip)]
+    Boxed(boxed::BoxedStorer),
 }
 
 impl From<IndexedTypeStorer> for TypeStorer {
@@ -77,6 +194,45 @@ impl From<NonIndexedTypeStorer> for TypeStorer {
     }
 }
 
+impl TypeStorer {
+    /// Returns a stable identifier of the backend this storer wraps, suitable for use
+    /// in logs and metric labels
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            TypeStorer::Indexed(its) => its.backend_name(),
+            TypeStorer::NonIndexed(nits) => nits.backend_name(),
+        }
+    }
+}
+
+impl IndexedTypeStorer {
+    /// Returns a stable identifier of the backend this storer wraps
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            IndexedTypeStorer::Redact(_) => "redact",
+            IndexedTypeStorer::Mongo(_) => "mongodb",
+            IndexedTypeStorer::Memory(_) => "memory",
+            IndexedTypeStorer::Mock(_) => "mock",
+        }
+    }
+}
+
+impl NonIndexedTypeStorer {
+    /// Returns a stable identifier of the backend this storer wraps. Encrypting storers
+    /// report themselves rather than the storer they wrap.
+    pub fn backend_name(&self) -> &'static str {
+        match self {
+            NonIndexedTypeStorer::SelfStore(_) => "selfstore",
+            NonIndexedTypeStorer::GoogleCloud(_) => "gcs",
+            NonIndexedTypeStorer::Filesystem(_) => "filesystem",
+            NonIndexedTypeStorer::Encrypting(_) => "encrypting",
+            NonIndexedTypeStorer::DualWrite(_) => "dualwrite",
+            NonIndexedTypeStorer::Mock(_) => "mock",
+            NonIndexedTypeStorer::Boxed(_) => "boxed",
+        }
+    }
+}
+
 #[async_trait]
 impl IndexedStorer for IndexedTypeStorer {
     async fn get_indexed<T: StorableType>(
@@ -87,6 +243,7 @@ impl IndexedStorer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.get_indexed(path, index).await,
             IndexedTypeStorer::Mongo(ms) => ms.get_indexed(path, index).await,
+            IndexedTypeStorer::Memory(ms) => ms.get_indexed(path, index).await,
             IndexedTypeStorer::Mock(ms) => ms.get_indexed(path, index).await,
         }
     }
@@ -100,6 +257,7 @@ impl IndexedStorer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.list(path, skip, page_size).await,
             IndexedTypeStorer::Mongo(ms) => ms.list(path, skip, page_size).await,
+            IndexedTypeStorer::Memory(ms) => ms.list(path, skip, page_size).await,
             IndexedTypeStorer::Mock(ms) => ms.list(path, skip, page_size).await,
         }
     }
@@ -114,9 +272,121 @@ impl IndexedStorer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.list_indexed(path, skip, page_size, index).await,
             IndexedTypeStorer::Mongo(ms) => ms.list_indexed(path, skip, page_size, index).await,
+            IndexedTypeStorer::Memory(ms) => ms.list_indexed(path, skip, page_size, index).await,
             IndexedTypeStorer::Mock(ms) => ms.list_indexed(path, skip, page_size, index).await,
         }
     }
+
+    async fn get_by_fingerprint<T: StorableType>(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Entry<T>, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.get_by_fingerprint(fingerprint).await,
+            IndexedTypeStorer::Mongo(ms) => ms.get_by_fingerprint(fingerprint).await,
+            IndexedTypeStorer::Memory(ms) => ms.get_by_fingerprint(fingerprint).await,
+            IndexedTypeStorer::Mock(ms) => ms.get_by_fingerprint(fingerprint).await,
+        }
+    }
+
+    async fn list_by_algorithm<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        oid: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => {
+                rs.list_by_algorithm(path_prefix, oid, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Mongo(ms) => {
+                ms.list_by_algorithm(path_prefix, oid, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Memory(ms) => {
+                ms.list_by_algorithm(path_prefix, oid, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Mock(ms) => {
+                ms.list_by_algorithm(path_prefix, oid, skip, page_size)
+                    .await
+            }
+        }
+    }
+
+    async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.count::<T>(path_prefix).await,
+            IndexedTypeStorer::Mongo(ms) => ms.count::<T>(path_prefix).await,
+            IndexedTypeStorer::Memory(ms) => ms.count::<T>(path_prefix).await,
+            IndexedTypeStorer::Mock(ms) => ms.count::<T>(path_prefix).await,
+        }
+    }
+
+    async fn list_modified_since<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => {
+                rs.list_modified_since(path_prefix, since, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Mongo(ms) => {
+                ms.list_modified_since(path_prefix, since, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Memory(ms) => {
+                ms.list_modified_since(path_prefix, since, skip, page_size)
+                    .await
+            }
+            IndexedTypeStorer::Mock(ms) => {
+                ms.list_modified_since(path_prefix, since, skip, page_size)
+                    .await
+            }
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.delete_prefix(prefix).await,
+            IndexedTypeStorer::Mongo(ms) => ms.delete_prefix(prefix).await,
+            IndexedTypeStorer::Memory(ms) => ms.delete_prefix(prefix).await,
+            IndexedTypeStorer::Mock(ms) => ms.delete_prefix(prefix).await,
+        }
+    }
+
+    async fn append<T: StorableType>(
+        &self,
+        collection_path: &str,
+        entry: Entry<T>,
+    ) -> Result<u64, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.append(collection_path, entry).await,
+            IndexedTypeStorer::Mongo(ms) => ms.append(collection_path, entry).await,
+            IndexedTypeStorer::Memory(ms) => ms.append(collection_path, entry).await,
+            IndexedTypeStorer::Mock(ms) => ms.append(collection_path, entry).await,
+        }
+    }
+
+    async fn read_range<T: StorableType>(
+        &self,
+        collection_path: &str,
+        from_seq: u64,
+        to_seq: u64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.read_range(collection_path, from_seq, to_seq).await,
+            IndexedTypeStorer::Mongo(ms) => ms.read_range(collection_path, from_seq, to_seq).await,
+            IndexedTypeStorer::Memory(ms) => ms.read_range(collection_path, from_seq, to_seq).await,
+            IndexedTypeStorer::Mock(ms) => ms.read_range(collection_path, from_seq, to_seq).await,
+        }
+    }
 }
 
 #[async_trait]
@@ -125,6 +395,7 @@ impl Storer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.delete::<T>(path).await,
             IndexedTypeStorer::Mongo(ms) => ms.delete::<T>(path).await,
+            IndexedTypeStorer::Memory(ms) => ms.delete::<T>(path).await,
             IndexedTypeStorer::Mock(ms) => ms.delete::<T>(path).await,
         }
     }
@@ -133,6 +404,7 @@ impl Storer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.get(path).await,
             IndexedTypeStorer::Mongo(ms) => ms.get(path).await,
+            IndexedTypeStorer::Memory(ms) => ms.get(path).await,
             IndexedTypeStorer::Mock(ms) => ms.get(path).await,
         }
     }
@@ -141,9 +413,50 @@ impl Storer for IndexedTypeStorer {
         match self {
             IndexedTypeStorer::Redact(rs) => rs.create(value).await,
             IndexedTypeStorer::Mongo(ms) => ms.create(value).await,
+            IndexedTypeStorer::Memory(ms) => ms.create(value).await,
             IndexedTypeStorer::Mock(ms) => ms.create(value).await,
         }
     }
+
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.apply_patch::<T>(path, patch).await,
+            IndexedTypeStorer::Mongo(ms) => ms.apply_patch::<T>(path, patch).await,
+            IndexedTypeStorer::Memory(ms) => ms.apply_patch::<T>(path, patch).await,
+            IndexedTypeStorer::Mock(ms) => ms.apply_patch::<T>(path, patch).await,
+        }
+    }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.rename::<T>(from, to).await,
+            IndexedTypeStorer::Mongo(ms) => ms.rename::<T>(from, to).await,
+            IndexedTypeStorer::Memory(ms) => ms.rename::<T>(from, to).await,
+            IndexedTypeStorer::Mock(ms) => ms.rename::<T>(from, to).await,
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.health_check().await,
+            IndexedTypeStorer::Mongo(ms) => ms.health_check().await,
+            IndexedTypeStorer::Memory(ms) => ms.health_check().await,
+            IndexedTypeStorer::Mock(ms) => ms.health_check().await,
+        }
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        match self {
+            IndexedTypeStorer::Redact(rs) => rs.flush().await,
+            IndexedTypeStorer::Mongo(ms) => ms.flush().await,
+            IndexedTypeStorer::Memory(ms) => ms.flush().await,
+            IndexedTypeStorer::Mock(ms) => ms.flush().await,
+        }
+    }
 }
 
 #[async_trait]
@@ -151,24 +464,88 @@ impl Storer for NonIndexedTypeStorer {
     async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
         match self {
             NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.delete::<T>(path).await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.delete::<T>(path).await,
+            NonIndexedTypeStorer::Encrypting(es) => es.delete::<T>(path).await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.delete::<T>(path).await,
             NonIndexedTypeStorer::Mock(ms) => ms.delete::<T>(path).await,
             NonIndexedTypeStorer::SelfStore(ss) => ss.delete::<T>(path).await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.delete::<T>(path).await,
         }
     }
 
     async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
         match self {
             NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.get(path).await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.get(path).await,
+            NonIndexedTypeStorer::Encrypting(es) => es.get(path).await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.get(path).await,
             NonIndexedTypeStorer::Mock(ms) => ms.get(path).await,
             NonIndexedTypeStorer::SelfStore(ss) => ss.get(path).await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.get(path).await,
         }
     }
 
     async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
         match self {
             NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.create(value).await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.create(value).await,
+            NonIndexedTypeStorer::Encrypting(es) => es.create(value).await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.create(value).await,
             NonIndexedTypeStorer::Mock(ms) => ms.create(value).await,
             NonIndexedTypeStorer::SelfStore(ss) => ss.create(value).await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.create(value).await,
+        }
+    }
+
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        match self {
+            NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::Encrypting(es) => es.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::Mock(ms) => ms.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::SelfStore(ss) => ss.apply_patch::<T>(path, patch).await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.apply_patch::<T>(path, patch).await,
+        }
+    }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        match self {
+            NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::Encrypting(es) => es.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::Mock(ms) => ms.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::SelfStore(ss) => ss.rename::<T>(from, to).await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.rename::<T>(from, to).await,
+        }
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        match self {
+            NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.health_check().await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.health_check().await,
+            NonIndexedTypeStorer::Encrypting(es) => es.health_check().await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.health_check().await,
+            NonIndexedTypeStorer::Mock(ms) => ms.health_check().await,
+            NonIndexedTypeStorer::SelfStore(ss) => ss.health_check().await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.health_check().await,
+        }
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        match self {
+            NonIndexedTypeStorer::GoogleCloud(gcs) => gcs.flush().await,
+            NonIndexedTypeStorer::Filesystem(fs) => fs.flush().await,
+            NonIndexedTypeStorer::Encrypting(es) => es.flush().await,
+            NonIndexedTypeStorer::DualWrite(dws) => dws.flush().await,
+            NonIndexedTypeStorer::Mock(ms) => ms.flush().await,
+            NonIndexedTypeStorer::SelfStore(ss) => ss.flush().await,
+            NonIndexedTypeStorer::Boxed(bs) => bs.flush().await,
         }
     }
 }
@@ -183,7 +560,9 @@ pub trait IndexedStorer: Send + Sync + Storer {
         index: &Option<Document>,
     ) -> Result<Entry<T>, CryptoError>;
 
-    /// Fetches a list of all the stored keys.
+    /// Fetches a list of all the stored keys. The page size must be positive and at most
+    /// the storer's maximum page size, `DEFAULT_MAX_PAGE_SIZE` unless configured
+    /// otherwise, or `CryptoError::InvalidPageSize` is returned.
     async fn list<T: StorableType>(
         &self,
         path: &str,
@@ -194,7 +573,8 @@ pub trait IndexedStorer: Send + Sync + Storer {
             .await
     }
 
-    /// Like list, but doesn't enforce IntoIndex and allows providing a custom index doc
+    /// Like list, but doesn't enforce IntoIndex and allows providing a custom index doc.
+    /// Page sizes are validated the same way.
     async fn list_indexed<T: StorableType>(
         &self,
         path: &str,
@@ -202,6 +582,86 @@ pub trait IndexedStorer: Send + Sync + Storer {
         page_size: i64,
         index: &Option<Document>,
     ) -> Result<Vec<Entry<T>>, CryptoError>;
+
+    /// Fetches the entry of the given type whose key material has the given fingerprint.
+    /// Fingerprints are only recorded for entries that were unsealed at creation time.
+    async fn get_by_fingerprint<T: StorableType>(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Entry<T>, CryptoError>;
+
+    /// Lists the stored entries of the given type under the given path prefix whose key
+    /// algorithm has the given OID, see `Entry::algorithm_oid`. The OID is recorded when
+    /// an entry is created. Page sizes are validated like in `list`.
+    async fn list_by_algorithm<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        oid: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError>;
+
+    /// Counts the stored entries of the given type whose path begins with the given prefix.
+    async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError>;
+
+    /// Lists the stored entries of the given type under the given path prefix whose bytes
+    /// were last modified strictly after `since`. Referenced entries have no modification
+    /// time and are never returned. Page sizes are validated like in `list`.
+    async fn list_modified_since<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError>;
+
+    /// Deletes every stored entry, regardless of type, under the given path prefix and
+    /// returns how many were removed. The prefix always ends on a path segment boundary,
+    /// so `.tenant12` never matches `.tenant123.`. A prefix matching every entry, such as
+    /// `""` or `"."`, is refused with `CryptoError::InvalidPathPrefix`.
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError>;
+
+    /// Appends the entry to the ordered log under the given collection path and returns
+    /// its sequence number. Sequence numbers start at 0 and are assigned atomically and
+    /// without gaps. Logs are kept apart from the entries stored with `create`, so an
+    /// appended entry is never returned by `get` or `list`.
+    async fn append<T: StorableType>(
+        &self,
+        collection_path: &str,
+        entry: Entry<T>,
+    ) -> Result<u64, CryptoError>;
+
+    /// Reads the entries of the given type appended to the log under the given collection
+    /// path with sequence numbers from `from_seq` up to, but excluding, `to_seq`, in
+    /// sequence order. Entries of another type are skipped.
+    async fn read_range<T: StorableType>(
+        &self,
+        collection_path: &str,
+        from_seq: u64,
+        to_seq: u64,
+    ) -> Result<Vec<Entry<T>>, CryptoError>;
+}
+
+/// Terminates a path prefix on a segment boundary by appending the `.` separator if
+/// it is missing. The empty prefix is left as is and matches every path.
+pub(crate) fn segment_prefix(prefix: &str) -> String {
+    if prefix.is_empty() || prefix.ends_with('.') {
+        prefix.to_owned()
+    } else {
+        format!("{}.", prefix)
+    }
+}
+
+/// Terminates a prefix of entries to delete on a segment boundary like `segment_prefix`,
+/// refusing one made only of separators, such as `""` or `"."`, since it would match
+/// every stored entry
+pub(crate) fn deletion_prefix(prefix: &str) -> Result<String, CryptoError> {
+    if prefix.trim_matches('.').is_empty() {
+        return Err(CryptoError::InvalidPathPrefix {
+            prefix: prefix.to_owned(),
+        });
+    }
+    Ok(segment_prefix(prefix))
 }
 
 /// The operations a storer of `Key` structs must be able to fulfill.
@@ -215,14 +675,66 @@ pub trait Storer: Send + Sync + Into<TypeStorer> + Clone {
 
     /// Adds the given `Key` struct to the backing store.
     async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError>;
+
+    /// Applies the given patch to the entry stored at the given path. By default this
+    /// fetches the whole entry and stores it again; backends able to update individual
+    /// fields in place should override it.
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        let entry = self.get::<T>(path).await?;
+        self.create(patch.apply(entry)).await.map(|_| ())
+    }
+
+    /// Moves the entry stored at `from` to `to`, replacing any entry already stored at
+    /// `to`, and returns it under its new path. Entries referencing the old path are not
+    /// updated. By default this fetches the entry, stores it at the new path and deletes
+    /// the old one, which is not atomic; backends able to move entries in place should
+    /// override it.
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        let entry = self.get::<T>(from).await?;
+        if from == to {
+            return Ok(entry);
+        }
+        let entry = self.create(entry.rename(to.to_owned())).await?;
+        self.delete::<T>(from).await?;
+        Ok(entry)
+    }
+
+    /// Checks that the backend is reachable, for use in readiness probes. By default this
+    /// fetches a reserved path that is never written, so a `NotFound` means the backend
+    /// answered. Any other failure is reported as `CryptoError::StorerUnavailable`.
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        match self.get::<Data>(HEALTH_CHECK_PATH).await {
+            Ok(_) | Err(CryptoError::NotFound { .. }) => Ok(()),
+            Err(e) => Err(CryptoError::StorerUnavailable {
+                source: Box::new(e),
+            }),
+        }
+    }
+
+    /// Writes out any writes the storer is still holding, such as creates buffered or
+    /// batched in memory. Storers which write through, which is all of the storers in
+    /// this crate, have nothing to do. Rust has no async `Drop`, so a storer cannot flush
+    /// itself when dropped: callers of a buffering storer must call this before dropping
+    /// it, or the buffered writes are lost.
+    async fn flush(&self) -> Result<(), CryptoError> {
+        Ok(())
+    }
 }
 
+/// Path fetched by the default `Storer::health_check`
+pub const HEALTH_CHECK_PATH: &str = ".redact-crypto.health-check.";
+
 pub mod tests {
     use super::IndexedStorer as IndexedStorerTrait;
     use super::Storer as StorerTrait;
-    use crate::storage::NonIndexedTypeStorer;
+    use crate::storage::{memory::MemoryStorer, NonIndexedTypeStorer};
     use crate::{CryptoError, Entry, IndexedTypeStorer, StorableType, TypeStorer};
     use async_trait::async_trait;
+    use chrono::{DateTime, Utc};
     use mockall::predicate::*;
     use mockall::*;
     use mongodb::bson::Document;
@@ -234,6 +746,13 @@ pub mod tests {
         pub fn private_serialize(&self) -> MockIndexedStorer;
     pub fn private_get_indexed<T: StorableType>(&self, path: &str, index: &Option<Document>) -> Result<Entry<T>, CryptoError>;
     pub fn private_list_indexed<T: StorableType>(&self, path: &str, skip: u64, page_size: i64, index: &Option<Document>) -> Result<Vec<Entry<T>>, CryptoError>;
+    pub fn private_get_by_fingerprint<T: StorableType>(&self, fingerprint: &str) -> Result<Entry<T>, CryptoError>;
+    pub fn private_count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError>;
+    pub fn private_list_by_algorithm<T: StorableType>(&self, path_prefix: &str, oid: &str, skip: u64, page_size: i64) -> Result<Vec<Entry<T>>, CryptoError>;
+    pub fn private_list_modified_since<T: StorableType>(&self, path_prefix: &str, since: DateTime<Utc>, skip: u64, page_size: i64) -> Result<Vec<Entry<T>>, CryptoError>;
+    pub fn private_delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError>;
+    pub fn private_append<T: StorableType>(&self, collection_path: &str, entry: Entry<T>) -> Result<u64, CryptoError>;
+    pub fn private_read_range<T: StorableType>(&self, collection_path: &str, from_seq: u64, to_seq: u64) -> Result<Vec<Entry<T>>, CryptoError>;
     pub fn private_delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError>;
     pub fn private_get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError>;
     pub fn private_list<T: StorableType>(&self, path: &str, skip: u64, page_size: i64) -> Result<Vec<Entry<T>>, CryptoError>;
@@ -301,6 +820,51 @@ pub mod tests {
         ) -> Result<Vec<Entry<T>>, CryptoError> {
             self.private_list_indexed(path, skip, page_size, index)
         }
+        async fn get_by_fingerprint<T: StorableType>(
+            &self,
+            fingerprint: &str,
+        ) -> Result<Entry<T>, CryptoError> {
+            self.private_get_by_fingerprint(fingerprint)
+        }
+        async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError> {
+            self.private_count::<T>(path_prefix)
+        }
+        async fn list_modified_since<T: StorableType>(
+            &self,
+            path_prefix: &str,
+            since: DateTime<Utc>,
+            skip: u64,
+            page_size: i64,
+        ) -> Result<Vec<Entry<T>>, CryptoError> {
+            self.private_list_modified_since(path_prefix, since, skip, page_size)
+        }
+        async fn list_by_algorithm<T: StorableType>(
+            &self,
+            path_prefix: &str,
+            oid: &str,
+            skip: u64,
+            page_size: i64,
+        ) -> Result<Vec<Entry<T>>, CryptoError> {
+            self.private_list_by_algorithm(path_prefix, oid, skip, page_size)
+        }
+        async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError> {
+            self.private_delete_prefix(prefix)
+        }
+        async fn append<T: StorableType>(
+            &self,
+            collection_path: &str,
+            entry: Entry<T>,
+        ) -> Result<u64, CryptoError> {
+            self.private_append(collection_path, entry)
+        }
+        async fn read_range<T: StorableType>(
+            &self,
+            collection_path: &str,
+            from_seq: u64,
+            to_seq: u64,
+        ) -> Result<Vec<Entry<T>>, CryptoError> {
+            self.private_read_range(collection_path, from_seq, to_seq)
+        }
     }
 
     #[async_trait]
@@ -376,4 +940,131 @@ pub mod tests {
             TypeStorer::NonIndexed(NonIndexedTypeStorer::Mock(mis))
         }
     }
+
+    /// Test storer serving a fixed set of entries by path, for tests that only need
+    /// lookups to succeed and would otherwise set up an expectation per call. It is
+    /// backed by a `MemoryStorer`, which is what it becomes when converted into a
+    /// `TypeStorer`.
+    #[derive(Debug, Clone, Default)]
+    pub struct MapStorer {
+        inner: MemoryStorer,
+    }
+
+    impl MapStorer {
+        /// Creates a storer serving each entry at the path it is paired with
+        pub fn from_pairs<T: StorableType>(pairs: Vec<(&str, Entry<T>)>) -> Self {
+            pairs
+                .into_iter()
+                .fold(MapStorer::default(), |storer, (path, entry)| {
+                    storer.with(path, entry)
+                })
+        }
+
+        /// Adds an entry served at the given path, allowing entries of different types
+        pub fn with<T: StorableType>(self, path: &str, mut entry: Entry<T>) -> Self {
+            entry.path = path.to_owned();
+            self.inner.insert(&entry).unwrap();
+            self
+        }
+    }
+
+    impl From<MapStorer> for TypeStorer {
+        fn from(ms: MapStorer) -> Self {
+            ms.inner.into()
+        }
+    }
+
+    #[async_trait]
+    impl StorerTrait for MapStorer {
+        async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+            self.inner.delete::<T>(path).await
+        }
+        async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+            self.inner.get(path).await
+        }
+        async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+            self.inner.create(value).await
+        }
+    }
+
+    #[tokio::test]
+    async fn test_typestorer_backend_name() {
+        use crate::{
+            key::sodiumoxide::SodiumOxideSymmetricKey,
+            storage::{
+                dualwrite::DualWriteStorer, encrypting::EncryptingStorer, gcs::GoogleCloudStorer,
+                memory::MemoryStorer, mongodb::MongoStorer, redact::RedactStorer,
+                selfstore::SelfStorer,
+            },
+            ToEntry,
+        };
+
+        let keys = MemoryStorer::new();
+        keys.create(
+            SodiumOxideSymmetricKey::new()
+                .to_unsealed_entry(".keys.default.".to_owned())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        let algorithm =
+            Entry::<SodiumOxideSymmetricKey>::reference(".keys.default.".to_owned(), keys)
+                .to_symmetric_byte_algorithm(None)
+                .await
+                .unwrap();
+        let storers: Vec<(TypeStorer, &str)> = vec![
+            (RedactStorer::new("http://localhost:8080").into(), "redact"),
+            (
+                MongoStorer::new("mongodb://localhost", "db", "entries").into(),
+                "mongodb",
+            ),
+            (MemoryStorer::new().into(), "memory"),
+            (MockIndexedStorer::new().into(), "mock"),
+            (SelfStorer::default().into(), "selfstore"),
+            (GoogleCloudStorer::new("bucket".to_owned()).into(), "gcs"),
+            (
+                EncryptingStorer::new(MemoryStorer::new(), algorithm)
+                    .unwrap()
+                    .into(),
+                "encrypting",
+            ),
+            (
+                DualWriteStorer::new(MemoryStorer::new(), MemoryStorer::new()).into(),
+                "dualwrite",
+            ),
+            (MockStorer::new().into(), "mock"),
+        ];
+        for (storer, expected) in storers.iter() {
+            assert_eq!(storer.backend_name(), *expected);
+        }
+    }
+
+    #[test]
+    fn test_index_from_builder_matches_hand_written_indexes() {
+        use crate::{
+            key::{
+                ring::{RingEd25519SecretAsymmetricKey, RingEd25519SecretAsymmetricKeyBuilder},
+                sodiumoxide::{
+                    SodiumOxideCurve25519PublicAsymmetricKey,
+                    SodiumOxideCurve25519PublicAsymmetricKeyBuilder, SodiumOxideSymmetricKey,
+                    SodiumOxideSymmetricKeyBuilder,
+                },
+            },
+            storage::index_from_builder,
+            HasIndex,
+        };
+
+        assert_eq!(
+            Some(index_from_builder(SodiumOxideSymmetricKeyBuilder {}).unwrap()),
+            SodiumOxideSymmetricKey::get_index()
+        );
+        assert_eq!(
+            Some(index_from_builder(RingEd25519SecretAsymmetricKeyBuilder {}).unwrap()),
+            RingEd25519SecretAsymmetricKey::get_index()
+        );
+        assert_eq!(
+            Some(index_from_builder(SodiumOxideCurve25519PublicAsymmetricKeyBuilder {}).unwrap()),
+            SodiumOxideCurve25519PublicAsymmetricKey::get_index()
+        );
+    }
 }
@@ -0,0 +1,292 @@
+//! Type-erased storers. `Storer` is generic over the type of the entries it stores and
+//! must convert into the `TypeStorer` enum, so a backend defined outside of this crate
+//! cannot be used as one directly. Implementing the object-safe `DynStorer` instead, and
+//! wrapping it in a `BoxedStorer`, makes any backend usable wherever a `Storer` is.
+
+use crate::{
+    storage::HEALTH_CHECK_PATH, CryptoError, Entry, HasBuilder, NonIndexedTypeStorer, StorableType,
+    Storer, TypeBuilderContainer, TypeStorer,
+};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    sync::Arc,
+};
+
+#[derive(Debug)]
+pub enum BoxedStorerError {
+    /// Represents an error which occurred in some internal system
+    InternalError {
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Requested entry was not found
+    NotFound,
+}
+
+impl Error for BoxedStorerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            BoxedStorerError::InternalError { ref source } => Some(source.as_ref()),
+            BoxedStorerError::NotFound => None,
+        }
+    }
+}
+
+impl Display for BoxedStorerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            BoxedStorerError::InternalError { .. } => {
+                write!(f, "Internal error occurred")
+            }
+            BoxedStorerError::NotFound => {
+                write!(f, "Requested entry not found")
+            }
+        }
+    }
+}
+
+impl From<BoxedStorerError> for CryptoError {
+    fn from(bse: BoxedStorerError) -> Self {
+        match bse {
+            BoxedStorerError::InternalError { .. } => CryptoError::InternalError {
+                source: Box::new(bse),
+            },
+            BoxedStorerError::NotFound => CryptoError::NotFound {
+                source: Box::new(bse),
+            },
+        }
+    }
+}
+
+/// Object-safe version of the `Storer` operations. Entries are exchanged in their
+/// serialized JSON form, so implementations do not need to know the type of the entries
+/// they store; `BoxedStorer` takes care of checking it.
+#[async_trait]
+pub trait DynStorer: Send + Sync {
+    /// Fetches the serialized entry stored at the given path, whatever its type
+    async fn get_value(&self, path: &str) -> Result<Value, CryptoError>;
+
+    /// Stores the serialized entry at its path, replacing any entry already stored there
+    async fn create_value(&self, path: &str, value: Value) -> Result<(), CryptoError>;
+
+    /// Deletes the entry stored at the given path, whatever its type
+    async fn delete_value(&self, path: &str) -> Result<(), CryptoError>;
+
+    /// Checks that the backend is reachable, see `Storer::health_check`
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        match self.get_value(HEALTH_CHECK_PATH).await {
+            Ok(_) | Err(CryptoError::NotFound { .. }) => Ok(()),
+            Err(e) => Err(CryptoError::StorerUnavailable {
+                source: Box::new(e),
+            }),
+        }
+    }
+
+    /// Writes out any buffered writes, see `Storer::flush`
+    async fn flush(&self) -> Result<(), CryptoError> {
+        Ok(())
+    }
+}
+
+/// Storer backed by any `DynStorer` implementation. Clones share the same backend.
+///
+/// A trait object cannot be serialized, so an entry referencing a `BoxedStorer` can be
+/// resolved but fails to serialize.
+#[derive(Clone)]
+pub struct BoxedStorer {
+    inner: Arc<dyn DynStorer>,
+}
+
+impl fmt::Debug for BoxedStorer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BoxedStorer").finish()
+    }
+}
+
+impl From<BoxedStorer> for NonIndexedTypeStorer {
+    fn from(bs: BoxedStorer) -> Self {
+        NonIndexedTypeStorer::Boxed(bs)
+    }
+}
+
+impl From<BoxedStorer> for TypeStorer {
+    fn from(bs: BoxedStorer) -> Self {
+        TypeStorer::NonIndexed(NonIndexedTypeStorer::Boxed(bs))
+    }
+}
+
+impl From<Arc<dyn DynStorer>> for BoxedStorer {
+    fn from(inner: Arc<dyn DynStorer>) -> Self {
+        BoxedStorer { inner }
+    }
+}
+
+impl BoxedStorer {
+    pub fn new<S: DynStorer + 'static>(storer: S) -> Self {
+        BoxedStorer {
+            inner: Arc::new(storer),
+        }
+    }
+}
+
+/// Returns true if the entry's builder can be downcast into the builder of T
+fn is_type<T: StorableType>(entry: &Entry<T>) -> bool {
+    <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(entry.builder)).is_ok()
+}
+
+#[async_trait]
+impl Storer for BoxedStorer {
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        let value = self.inner.get_value(path).await?;
+        let entry: Entry<T> =
+            serde_json::from_value(value).map_err(|e| BoxedStorerError::InternalError {
+                source: Box::new(e),
+            })?;
+        if is_type::<T>(&entry) {
+            Ok(entry)
+        } else {
+            Err(BoxedStorerError::NotFound.into())
+        }
+    }
+
+    async fn create<T: StorableType>(&self, entry: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        let value = serde_json::to_value(&entry).map_err(|e| BoxedStorerError::InternalError {
+            source: Box::new(e),
+        })?;
+        self.inner.create_value(&entry.path, value).await?;
+        Ok(entry)
+    }
+
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        self.get::<T>(path).await?;
+        self.inner.delete_value(path).await
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        self.inner.health_check().await
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BoxedStorer, DynStorer};
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, CryptoError, Data, Entry, MemoryStorer, Storer,
+        ToEntry,
+    };
+    use async_trait::async_trait;
+    use serde_json::Value;
+    use std::sync::{Arc, Mutex};
+
+    /// Holds created entries in memory until flushed to the inner storer
+    struct BufferingStorer {
+        inner: MemoryStorer,
+        pending: Arc<Mutex<Vec<(String, Value)>>>,
+    }
+
+    #[async_trait]
+    impl DynStorer for BufferingStorer {
+        async fn get_value(&self, path: &str) -> Result<Value, CryptoError> {
+            self.inner.get_value(path).await
+        }
+
+        async fn create_value(&self, path: &str, value: Value) -> Result<(), CryptoError> {
+            self.pending.lock().unwrap().push((path.to_owned(), value));
+            Ok(())
+        }
+
+        async fn delete_value(&self, path: &str) -> Result<(), CryptoError> {
+            self.inner.delete_value(path).await
+        }
+
+        async fn flush(&self) -> Result<(), CryptoError> {
+            let pending = std::mem::take(&mut *self.pending.lock().unwrap());
+            for (path, value) in pending {
+                self.inner.create_value(&path, value).await?;
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_boxedstorer_resolves_referenced_entry() {
+        let memory = MemoryStorer::new();
+        let storer = BoxedStorer::new(memory.clone());
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".keys.kek.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(memory
+            .get::<SodiumOxideSymmetricKey>(".keys.kek.")
+            .await
+            .is_ok());
+
+        let algorithm =
+            Entry::<SodiumOxideSymmetricKey>::reference(".keys.kek.".to_owned(), storer.clone())
+                .to_symmetric_byte_algorithm(None)
+                .await
+                .unwrap();
+        let entry = Data::String("hello, world!".to_owned())
+            .to_sealed_entry(".data.".to_owned(), algorithm)
+            .await
+            .unwrap();
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+        assert!(serde_json::to_string(&entry).is_err());
+
+        match storer.get::<Data>(".keys.kek.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Entry of another type should not have been found"),
+        }
+        storer
+            .delete::<SodiumOxideSymmetricKey>(".keys.kek.")
+            .await
+            .unwrap();
+        match entry.verify_chain_integrity().await {
+            Err(CryptoError::BrokenChainLink { .. }) => (),
+            _ => panic!("Deleted key should have broken the chain"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_boxedstorer_flush_writes_buffered_entries() {
+        let memory = MemoryStorer::new();
+        let storer = BoxedStorer::new(BufferingStorer {
+            inner: memory.clone(),
+            pending: Arc::new(Mutex::new(vec![])),
+        });
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        match memory.get::<Data>(".data.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Entry should not have been written before flushing"),
+        }
+
+        storer.flush().await.unwrap();
+        let entry = memory.get::<Data>(".data.").await.unwrap();
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+}
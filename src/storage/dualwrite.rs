@@ -0,0 +1,165 @@
+//! Dual-writing storer for migrating between backends. Every write goes to both the
+//! primary and the secondary storer while reads are served by the primary, so the
+//! secondary can be filled in the background and switched to once it has caught up.
+
+use crate::{CryptoError, Entry, NonIndexedTypeStorer, StorableType, Storer, TypeStorer};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+/// Writes every entry to both a primary and a secondary storer. A write fails if it
+/// fails on the primary; a failure on the secondary alone is only reported as a
+/// `tracing` warning when the `tracing` feature is enabled, since the secondary is not
+/// yet authoritative. Reads are served by the primary and, if read fallback is enabled,
+/// by the secondary for entries the primary does not have.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct DualWriteStorer {
+    primary: TypeStorer,
+    secondary: TypeStorer,
+    #[serde(default)]
+    read_fallback: bool,
+}
+
+impl From<DualWriteStorer> for NonIndexedTypeStorer {
+    fn from(dws: DualWriteStorer) -> Self {
+        NonIndexedTypeStorer::DualWrite(Box::new(dws))
+    }
+}
+
+impl From<DualWriteStorer> for TypeStorer {
+    fn from(dws: DualWriteStorer) -> Self {
+        TypeStorer::NonIndexed(dws.into())
+    }
+}
+
+impl DualWriteStorer {
+    /// Instantiates a storer writing to both storers and reading from the primary only
+    pub fn new<P: Storer, S: Storer>(primary: P, secondary: S) -> Self {
+        DualWriteStorer {
+            primary: primary.into(),
+            secondary: secondary.into(),
+            read_fallback: false,
+        }
+    }
+
+    /// Falls back to reading from the secondary when an entry is not found in the primary
+    pub fn with_read_fallback(mut self) -> Self {
+        self.read_fallback = true;
+        self
+    }
+
+    /// Returns a reference to the primary storer
+    pub fn primary(&self) -> &TypeStorer {
+        &self.primary
+    }
+
+    /// Returns a reference to the secondary storer
+    pub fn secondary(&self) -> &TypeStorer {
+        &self.secondary
+    }
+}
+
+/// Reports a write which succeeded on the primary but failed on the secondary
+#[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+fn secondary_failed(operation: &'static str, path: &str, error: &CryptoError) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        operation,
+        path,
+        error = %error,
+        "write to the secondary storer failed"
+    );
+}
+
+#[async_trait]
+impl Storer for DualWriteStorer {
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        match self.primary.get::<T>(path).await {
+            Err(CryptoError::NotFound { .. }) if self.read_fallback => {
+                self.secondary.get::<T>(path).await
+            }
+            result => result,
+        }
+    }
+
+    async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        let entry = self.primary.create(value.clone()).await?;
+        if let Err(e) = self.secondary.create(value).await {
+            secondary_failed("create", &entry.path, &e);
+        }
+        Ok(entry)
+    }
+
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        self.primary.delete::<T>(path).await?;
+        if let Err(e) = self.secondary.delete::<T>(path).await {
+            secondary_failed("delete", path, &e);
+        }
+        Ok(())
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        self.primary.health_check().await
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        self.primary.flush().await?;
+        if let Err(e) = self.secondary.flush().await {
+            secondary_failed("flush", "", &e);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DualWriteStorer;
+    use crate::{CryptoError, Data, MemoryStorer, Storer, ToEntry};
+
+    #[tokio::test]
+    async fn test_dualwritestorer_create_writes_both() {
+        let primary = MemoryStorer::new();
+        let secondary = MemoryStorer::new();
+        let storer = DualWriteStorer::new(primary.clone(), secondary.clone());
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(primary.get::<Data>(".data.").await.is_ok());
+        assert!(secondary.get::<Data>(".data.").await.is_ok());
+
+        storer.delete::<Data>(".data.").await.unwrap();
+        assert!(primary.get::<Data>(".data.").await.is_err());
+        assert!(secondary.get::<Data>(".data.").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dualwritestorer_get_falls_back_to_secondary() {
+        let primary = MemoryStorer::new();
+        let secondary = MemoryStorer::new();
+        secondary
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let storer = DualWriteStorer::new(primary.clone(), secondary.clone());
+        match storer.get::<Data>(".data.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Entry should only have been read from the primary"),
+        }
+
+        let storer = storer.with_read_fallback();
+        let entry = storer.get::<Data>(".data.").await.unwrap();
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use crate::{
+    ByteAlgorithm, CryptoError, Entry, NonIndexedTypeStorer, State, StorableType, Storer,
+    TypeStorer,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// Wraps another storer and seals every unsealed entry value under a master algorithm
+/// before it reaches the inner storer, unsealing it again on retrieval. The entry's
+/// path and builder are left untouched so the inner storer can still query on them.
+/// Sealed and referenced entries contain no plaintext and are passed through as-is.
+///
+/// Each value is sealed under a fresh nonce, stored with the ciphertext in the sealed
+/// entry along with the algorithm. The master key must therefore be referenced rather
+/// than embedded in the algorithm, or it would be written out next to every entry, and
+/// next to any serialized reference to this storer.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct EncryptingStorer<S> {
+    inner: S,
+    algorithm: Arc<ByteAlgorithm>,
+}
+
+impl<S: Storer> From<EncryptingStorer<S>> for NonIndexedTypeStorer {
+    fn from(es: EncryptingStorer<S>) -> Self {
+        NonIndexedTypeStorer::Encrypting(Box::new(EncryptingStorer {
+            inner: es.inner.into(),
+            algorithm: es.algorithm,
+        }))
+    }
+}
+
+impl<S: Storer> From<EncryptingStorer<S>> for TypeStorer {
+    fn from(es: EncryptingStorer<S>) -> Self {
+        TypeStorer::NonIndexed(es.into())
+    }
+}
+
+impl<S: Storer> EncryptingStorer<S> {
+    /// Instantiates an encrypting storer that seals values under the given algorithm
+    /// before handing them to the inner storer. Fails with
+    /// `CryptoError::EmbeddedSecretKey` if the algorithm carries its secret key in the
+    /// clear instead of referencing it.
+    pub fn new(inner: S, algorithm: ByteAlgorithm) -> Result<Self, CryptoError> {
+        if algorithm.embeds_secret_key() {
+            return Err(CryptoError::EmbeddedSecretKey);
+        }
+        Ok(EncryptingStorer {
+            inner,
+            algorithm: Arc::new(algorithm),
+        })
+    }
+
+    /// Returns a reference to the wrapped storer
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+}
+
+#[async_trait]
+impl<S: Storer> Storer for EncryptingStorer<S> {
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        let entry = self.inner.get::<T>(path).await?;
+        match entry.value {
+            // Only entries sealed under the master key are unsealed, whatever their nonce.
+            // Only the nonce is taken from the stored algorithm: the key always comes from
+            // the master algorithm, so a stored key reference pointing at another storer
+            // cannot get forged ciphertext accepted.
+            State::Sealed {
+                ref ciphertext,
+                ref algorithm,
+                unlock_at: None,
+            } if algorithm.referenced_paths() == self.algorithm.referenced_paths() => {
+                let plaintext = self
+                    .algorithm
+                    .with_nonce_of(algorithm)?
+                    .unseal_for_path(ciphertext, &entry.path)
+                    .await?;
+                Ok(Entry::new(
+                    entry.path,
+                    entry.builder,
+                    State::Unsealed { bytes: plaintext },
+                )
+                .with_metadata(entry.metadata))
+            }
+            _ => Ok(entry),
+        }
+    }
+
+    async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        match value.value {
+            State::Unsealed { ref bytes } => {
+                let mut algorithm = (*self.algorithm).clone();
+                algorithm.regenerate_nonce();
+                let ciphertext = algorithm.seal_for_path(bytes, &value.path).await?;
+                self.inner
+                    .create::<T>(
+                        Entry::new(
+                            value.path.clone(),
+                            value.builder,
+                            State::Sealed {
+                                ciphertext,
+                                algorithm,
+                                unlock_at: None,
+                            },
+                        )
+                        .with_metadata(value.metadata.clone()),
+                    )
+                    .await?;
+                Ok(value)
+            }
+            _ => self.inner.create(value).await,
+        }
+    }
+
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        self.inner.delete::<T>(path).await
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        self.inner.health_check().await
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EncryptingStorer;
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, ByteAlgorithm, CryptoError, Data, Entry,
+        MemoryStorer, State, Storer, ToEntry, ToSymmetricByteAlgorithm,
+    };
+
+    /// Returns an algorithm referencing a master key stored in its own storer
+    async fn master_algorithm() -> ByteAlgorithm {
+        let keys = MemoryStorer::new();
+        keys.create(
+            SodiumOxideSymmetricKey::new()
+                .to_unsealed_entry(".masterkey.".to_owned())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+        Entry::<SodiumOxideSymmetricKey>::reference(".masterkey.".to_owned(), keys)
+            .to_symmetric_byte_algorithm(None)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_encryptingstorer_stores_ciphertext() {
+        let inner = MemoryStorer::new();
+        let storer = EncryptingStorer::new(inner.clone(), master_algorithm().await).unwrap();
+        let plaintext = "hello, world!";
+
+        storer
+            .create(
+                Data::String(plaintext.to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        storer
+            .create(
+                Data::String(plaintext.to_owned())
+                    .to_unsealed_entry(".data2.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let raw_entry = inner.get::<Data>(".data.").await.unwrap();
+        let raw_json = serde_json::to_string(&raw_entry).unwrap();
+        assert!(!raw_json.contains(plaintext));
+        assert!(!raw_json.contains(&base64::encode(plaintext)));
+        let raw_nonces = vec![raw_entry, inner.get::<Data>(".data2.").await.unwrap()]
+            .into_iter()
+            .map(|entry| match entry.value {
+                State::Sealed {
+                    algorithm: ByteAlgorithm::SodiumOxideSymmetricKey(sosku),
+                    ..
+                } => sosku.nonce.nonce,
+                _ => panic!("Stored entry should have been sealed"),
+            })
+            .collect::<Vec<_>>();
+        assert_ne!(raw_nonces[0], raw_nonces[1]);
+
+        let entry = storer.get::<Data>(".data.").await.unwrap();
+        match entry.resolve().await.unwrap() {
+            Data::String(s) => assert_eq!(s, plaintext),
+            _ => panic!("Resolved data should have been a string"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_encryptingstorer_rejects_swapped_key_reference() {
+        let inner = MemoryStorer::new();
+        let storer = EncryptingStorer::new(inner.clone(), master_algorithm().await).unwrap();
+
+        // Same key path, but referencing a key in a storer the writer controls
+        let forged = Data::String("forged".to_owned())
+            .to_sealed_entry(".data.".to_owned(), master_algorithm().await)
+            .await
+            .unwrap();
+        inner.create(forged).await.unwrap();
+
+        assert!(storer.get::<Data>(".data.").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_encryptingstorer_rejects_embedded_key() {
+        let algorithm = SodiumOxideSymmetricKey::new()
+            .to_byte_algorithm(None, |key| async move {
+                key.to_unsealed_entry(".masterkey.".to_owned())
+            })
+            .await
+            .unwrap();
+        match EncryptingStorer::new(MemoryStorer::new(), algorithm) {
+            Err(CryptoError::EmbeddedSecretKey) => (),
+            _ => panic!("Algorithm embedding its key should have been rejected"),
+        }
+    }
+}
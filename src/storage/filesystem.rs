@@ -0,0 +1,319 @@
+use crate::{
+    CryptoError, Entry, HasBuilder, NonIndexedTypeStorer, StorableType, Storer,
+    TypeBuilderContainer, TypeStorer,
+};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::{
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+const ENTRY_EXTENSION: &str = "json";
+
+#[derive(Debug)]
+pub enum FilesystemStorerError {
+    /// Represents an error which occurred in some internal system
+    InternalError {
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Requested document was not found
+    NotFound,
+
+    /// Entry path cannot be mapped to a file under the storer's root
+    InvalidPath { path: String },
+}
+
+impl Error for FilesystemStorerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            FilesystemStorerError::InternalError { ref source } => Some(source.as_ref()),
+            FilesystemStorerError::NotFound => None,
+            FilesystemStorerError::InvalidPath { .. } => None,
+        }
+    }
+}
+
+impl Display for FilesystemStorerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            FilesystemStorerError::InternalError { .. } => {
+                write!(f, "Internal error occurred")
+            }
+            FilesystemStorerError::NotFound => {
+                write!(f, "Requested document not found")
+            }
+            FilesystemStorerError::InvalidPath { ref path } => {
+                write!(f, "Entry path {} cannot be stored as a file", path)
+            }
+        }
+    }
+}
+
+impl From<FilesystemStorerError> for CryptoError {
+    fn from(fse: FilesystemStorerError) -> Self {
+        match fse {
+            FilesystemStorerError::NotFound => CryptoError::NotFound {
+                source: Box::new(fse),
+            },
+            _ => CryptoError::InternalError {
+                source: Box::new(fse),
+            },
+        }
+    }
+}
+
+impl From<io::Error> for FilesystemStorerError {
+    fn from(e: io::Error) -> Self {
+        match e.kind() {
+            io::ErrorKind::NotFound => FilesystemStorerError::NotFound,
+            _ => FilesystemStorerError::InternalError {
+                source: Box::new(e),
+            },
+        }
+    }
+}
+
+/// Stores each entry as a JSON file under a root directory. Every segment of an entry's
+/// path becomes a directory, with the last segment naming the file, so `.keys.alice.`
+/// is stored at `{root}/keys/alice.json`. File operations are blocking, which makes this
+/// storer best suited to local tooling and tests.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FilesystemStorer {
+    root: PathBuf,
+}
+
+impl From<FilesystemStorer> for NonIndexedTypeStorer {
+    fn from(fs: FilesystemStorer) -> Self {
+        NonIndexedTypeStorer::Filesystem(fs)
+    }
+}
+
+impl From<FilesystemStorer> for TypeStorer {
+    fn from(fs: FilesystemStorer) -> Self {
+        TypeStorer::NonIndexed(NonIndexedTypeStorer::Filesystem(fs))
+    }
+}
+
+impl FilesystemStorer {
+    pub fn new<P: Into<PathBuf>>(root: P) -> Self {
+        FilesystemStorer { root: root.into() }
+    }
+
+    /// Returns the directory entries are stored under
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Lists the entries of type T stored under the given path prefix by walking the
+    /// directory tree. Entries of other types are skipped.
+    pub async fn list<T: StorableType>(
+        &self,
+        path_prefix: &str,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let segments = path_segments(path_prefix)?;
+        let dir = segments
+            .iter()
+            .fold(self.root.clone(), |dir, segment| dir.join(segment));
+        let mut files = vec![];
+        match collect_files(&dir, &mut files) {
+            Err(FilesystemStorerError::NotFound) => return Ok(vec![]),
+            result => result?,
+        }
+        files.sort();
+
+        let mut entries = vec![];
+        for file in files {
+            let entry: Entry<T> = read_entry(&file)?;
+            if is_type::<T>(&entry) {
+                entries.push(entry);
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Maps an entry path to the file it is stored in
+    fn file_path(&self, path: &str) -> Result<PathBuf, FilesystemStorerError> {
+        let segments = path_segments(path)?;
+        let (file_name, dirs) =
+            segments
+                .split_last()
+                .ok_or_else(|| FilesystemStorerError::InvalidPath {
+                    path: path.to_owned(),
+                })?;
+        let dir = dirs
+            .iter()
+            .fold(self.root.clone(), |dir, segment| dir.join(segment));
+        Ok(dir.join(format!("{}.{}", file_name, ENTRY_EXTENSION)))
+    }
+}
+
+/// Splits an entry path into its segments, rejecting any that could name a file
+/// outside of the storer's root
+fn path_segments(path: &str) -> Result<Vec<&str>, FilesystemStorerError> {
+    let segments: Vec<&str> = path.split('.').filter(|s| !s.is_empty()).collect();
+    if segments
+        .iter()
+        .any(|s| s.contains(|c| c == '/' || c == '\\') || s.contains('\0'))
+    {
+        return Err(FilesystemStorerError::InvalidPath {
+            path: path.to_owned(),
+        });
+    }
+    Ok(segments)
+}
+
+/// Recursively collects every entry file under the given directory
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), FilesystemStorerError> {
+    for dir_entry in fs::read_dir(dir)? {
+        let path = dir_entry?.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else if path.extension().map_or(false, |ext| ext == ENTRY_EXTENSION) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn read_entry<T: StorableType>(file: &Path) -> Result<Entry<T>, FilesystemStorerError> {
+    let bytes = fs::read(file)?;
+    serde_json::from_slice(&bytes).map_err(|e| FilesystemStorerError::InternalError {
+        source: Box::new(e),
+    })
+}
+
+/// Returns true if the entry's builder can be downcast into the builder of T
+fn is_type<T: StorableType>(entry: &Entry<T>) -> bool {
+    <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(entry.builder)).is_ok()
+}
+
+#[async_trait]
+impl Storer for FilesystemStorer {
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        let file = self.file_path(path)?;
+        fs::remove_file(file).map_err(FilesystemStorerError::from)?;
+        Ok(())
+    }
+
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        let entry = read_entry(&self.file_path(path)?)?;
+        if is_type::<T>(&entry) {
+            Ok(entry)
+        } else {
+            Err(FilesystemStorerError::NotFound.into())
+        }
+    }
+
+    async fn create<T: StorableType>(&self, entry: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        let file = self.file_path(&entry.path)?;
+        let json =
+            serde_json::to_vec(&entry).map_err(|e| FilesystemStorerError::InternalError {
+                source: Box::new(e),
+            })?;
+        if let Some(dir) = file.parent() {
+            fs::create_dir_all(dir).map_err(FilesystemStorerError::from)?;
+        }
+        fs::write(file, json).map_err(FilesystemStorerError::from)?;
+        Ok(entry)
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        fs::metadata(&self.root)
+            .and_then(|metadata| {
+                if metadata.is_dir() {
+                    Ok(())
+                } else {
+                    Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        "storer root is not a directory",
+                    ))
+                }
+            })
+            .map_err(|source| CryptoError::StorerUnavailable {
+                source: Box::new(source),
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FilesystemStorer;
+    use crate::{CryptoError, Data, Storer, ToEntry};
+    use std::path::PathBuf;
+
+    fn temp_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("redact-crypto-fs-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_create_get_delete() {
+        let root = temp_root("crud");
+        let storer = FilesystemStorer::new(&root);
+        let entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.greeting.".to_owned())
+            .unwrap();
+        storer.create(entry).await.unwrap();
+        assert!(root.join("data").join("greeting.json").is_file());
+
+        match storer
+            .get::<Data>(".data.greeting.")
+            .await
+            .unwrap()
+            .resolve()
+            .await
+            .unwrap()
+        {
+            Data::String(s) => assert_eq!(s, "hello, world!"),
+            _ => panic!("Resolved data should have been a string"),
+        }
+
+        storer.delete::<Data>(".data.greeting.").await.unwrap();
+        match storer.get::<Data>(".data.greeting.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Deleted entry should not have been found"),
+        }
+        match storer.delete::<Data>(".data.greeting.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Deleting a missing entry should have been not found"),
+        }
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_list_walks_directories() {
+        let root = temp_root("list");
+        let storer = FilesystemStorer::new(&root);
+        for (path, n) in [
+            (".numbers.one.", 1),
+            (".numbers.deep.two.", 2),
+            (".other.", 3),
+        ]
+        .iter()
+        {
+            storer
+                .create(Data::U64(*n).to_unsealed_entry((*path).to_owned()).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let entries = storer.list::<Data>(".numbers.").await.unwrap();
+        let mut paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        paths.sort_unstable();
+        assert_eq!(paths, vec![".numbers.deep.two.", ".numbers.one."]);
+        assert!(storer.list::<Data>(".missing.").await.unwrap().is_empty());
+        assert!(storer.health_check().await.is_ok());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_path_outside_root_rejected() {
+        let storer = FilesystemStorer::new(temp_root("invalid"));
+        assert!(storer.get::<Data>(".data/../../etc.").await.is_err());
+        assert!(storer.get::<Data>("...").await.is_err());
+    }
+}
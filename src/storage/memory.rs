@@ -0,0 +1,891 @@
+use crate::{
+    storage::{
+        boxed::DynStorer, check_page_size, default_max_page_size, deletion_prefix,
+        validate_max_page_size,
+    },
+    CryptoError, Entry, HasBuilder, IndexedStorer, IndexedTypeStorer, StorableType, Storer, Type,
+    TypeBuilder, TypeBuilderContainer, TypeStorer,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use mongodb::bson::Document;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{
+    collections::{BTreeMap, HashMap},
+    convert::TryFrom,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    sync::{Arc, RwLock},
+};
+
+#[derive(Debug)]
+pub enum MemoryStorerError {
+    /// Represents an error which occurred in some internal system
+    InternalError {
+        source: Box<dyn Error + Send + Sync>,
+    },
+
+    /// Requested entry was not found
+    NotFound,
+}
+
+impl Error for MemoryStorerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match *self {
+            MemoryStorerError::InternalError { ref source } => Some(source.as_ref()),
+            MemoryStorerError::NotFound => None,
+        }
+    }
+}
+
+impl Display for MemoryStorerError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            MemoryStorerError::InternalError { .. } => {
+                write!(f, "Internal error occurred")
+            }
+            MemoryStorerError::NotFound => {
+                write!(f, "Requested entry not found")
+            }
+        }
+    }
+}
+
+impl From<MemoryStorerError> for CryptoError {
+    fn from(mse: MemoryStorerError) -> Self {
+        match mse {
+            MemoryStorerError::InternalError { .. } => CryptoError::InternalError {
+                source: Box::new(mse),
+            },
+            MemoryStorerError::NotFound => CryptoError::NotFound {
+                source: Box::new(mse),
+            },
+        }
+    }
+}
+
+/// A single stored entry along with the index fields written at creation time
+#[derive(Debug, Clone)]
+struct MemoryRecord {
+    entry: serde_json::Value,
+    fingerprint: Option<String>,
+    algorithm_oid: Option<String>,
+    last_modified: Option<DateTime<Utc>>,
+}
+
+impl MemoryRecord {
+    fn from_entry<T: StorableType>(entry: &Entry<T>) -> Result<Self, MemoryStorerError> {
+        let value = serde_json::to_value(entry).map_err(|e| MemoryStorerError::InternalError {
+            source: Box::new(e),
+        })?;
+        Ok(MemoryRecord {
+            entry: value,
+            fingerprint: entry.fingerprint(),
+            algorithm_oid: entry.algorithm_oid(),
+            last_modified: entry.last_modified(),
+        })
+    }
+
+    /// Returns true if the stored entry's builder can be downcast into the builder of T,
+    /// which is the in-memory equivalent of filtering on `T::get_index()`
+    fn is_type<T: StorableType>(&self) -> bool {
+        match self
+            .entry
+            .get("builder")
+            .map(|b| serde_json::from_value::<TypeBuilder>(b.clone()))
+        {
+            Some(Ok(builder)) => {
+                <T as HasBuilder>::Builder::try_from(TypeBuilderContainer(builder)).is_ok()
+            }
+            _ => false,
+        }
+    }
+
+    fn to_entry<T: StorableType>(&self) -> Result<Entry<T>, MemoryStorerError> {
+        serde_json::from_value(self.entry.clone()).map_err(|e| MemoryStorerError::InternalError {
+            source: Box::new(e),
+        })
+    }
+}
+
+/// Stores entries in process memory. Clones of a `MemoryStorer` share the same
+/// underlying entries, while a deserialized `MemoryStorer` always starts empty.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MemoryStorer {
+    #[serde(skip)]
+    entries: Arc<RwLock<BTreeMap<String, MemoryRecord>>>,
+    /// Appended logs by collection path, each entry's sequence number being its index
+    #[serde(skip)]
+    logs: Arc<RwLock<HashMap<String, Vec<MemoryRecord>>>>,
+    #[serde(default = "default_max_page_size")]
+    max_page_size: i64,
+}
+
+impl Default for MemoryStorer {
+    fn default() -> Self {
+        MemoryStorer {
+            entries: Default::default(),
+            logs: Default::default(),
+            max_page_size: default_max_page_size(),
+        }
+    }
+}
+
+impl From<MemoryStorer> for IndexedTypeStorer {
+    fn from(ms: MemoryStorer) -> Self {
+        IndexedTypeStorer::Memory(ms)
+    }
+}
+
+impl From<MemoryStorer> for TypeStorer {
+    fn from(ms: MemoryStorer) -> Self {
+        TypeStorer::Indexed(IndexedTypeStorer::Memory(ms))
+    }
+}
+
+impl MemoryStorer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the largest page size accepted when listing entries, which must be positive
+    pub fn with_max_page_size(mut self, max: i64) -> Result<Self, CryptoError> {
+        self.max_page_size = validate_max_page_size(max)?;
+        Ok(self)
+    }
+
+    /// Returns the largest page size accepted when listing entries
+    pub fn max_page_size(&self) -> i64 {
+        self.max_page_size
+    }
+
+    /// Stores the entry at its path, replacing any existing entry there
+    pub(crate) fn insert<T: StorableType>(&self, entry: &Entry<T>) -> Result<(), CryptoError> {
+        let record = MemoryRecord::from_entry(entry)?;
+        self.entries
+            .write()
+            .unwrap()
+            .insert(entry.path.clone(), record);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl IndexedStorer for MemoryStorer {
+    async fn get_indexed<T: StorableType>(
+        &self,
+        path: &str,
+        _index: &Option<Document>,
+    ) -> Result<Entry<T>, CryptoError> {
+        let entries = self.entries.read().unwrap();
+        match entries.get(path) {
+            Some(record) if record.is_type::<T>() => Ok(record.to_entry()?),
+            _ => Err(MemoryStorerError::NotFound.into()),
+        }
+    }
+
+    async fn list_indexed<T: StorableType>(
+        &self,
+        path: &str,
+        skip: u64,
+        page_size: i64,
+        _index: &Option<Document>,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let entries = self.entries.read().unwrap();
+        Ok(entries
+            .iter()
+            .filter(|(entry_path, record)| entry_path.starts_with(path) && record.is_type::<T>())
+            .skip(skip as usize)
+            .take(if page_size < 0 { 0 } else { page_size as usize })
+            .filter_map(|(_, record)| record.to_entry().ok())
+            .collect())
+    }
+
+    async fn get_by_fingerprint<T: StorableType>(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Entry<T>, CryptoError> {
+        let entries = self.entries.read().unwrap();
+        match entries.values().find(|record| {
+            record.fingerprint.as_deref() == Some(fingerprint) && record.is_type::<T>()
+        }) {
+            Some(record) => Ok(record.to_entry()?),
+            None => Err(MemoryStorerError::NotFound.into()),
+        }
+    }
+
+    async fn list_by_algorithm<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        oid: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let entries = self.entries.read().unwrap();
+        Ok(entries
+            .iter()
+            .filter(|(path, record)| {
+                path.starts_with(path_prefix)
+                    && record.algorithm_oid.as_deref() == Some(oid)
+                    && record.is_type::<T>()
+            })
+            .skip(skip as usize)
+            .take(if page_size < 0 { 0 } else { page_size as usize })
+            .filter_map(|(_, record)| record.to_entry().ok())
+            .collect())
+    }
+
+    async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError> {
+        let entries = self.entries.read().unwrap();
+        Ok(entries
+            .iter()
+            .filter(|(path, record)| path.starts_with(path_prefix) && record.is_type::<T>())
+            .count() as u64)
+    }
+
+    async fn list_modified_since<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let entries = self.entries.read().unwrap();
+        Ok(entries
+            .iter()
+            .filter(|(path, record)| {
+                path.starts_with(path_prefix)
+                    && record.last_modified.map_or(false, |lm| lm > since)
+                    && record.is_type::<T>()
+            })
+            .skip(skip as usize)
+            .take(if page_size < 0 { 0 } else { page_size as usize })
+            .filter_map(|(_, record)| record.to_entry().ok())
+            .collect())
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError> {
+        let prefix = deletion_prefix(prefix)?;
+        let mut entries = self.entries.write().unwrap();
+        let before = entries.len();
+        entries.retain(|path, _| !path.starts_with(&prefix));
+        Ok((before - entries.len()) as u64)
+    }
+
+    async fn append<T: StorableType>(
+        &self,
+        collection_path: &str,
+        entry: Entry<T>,
+    ) -> Result<u64, CryptoError> {
+        let record = MemoryRecord::from_entry(&entry)?;
+        let mut logs = self.logs.write().unwrap();
+        let log = logs.entry(collection_path.to_owned()).or_default();
+        log.push(record);
+        Ok((log.len() - 1) as u64)
+    }
+
+    async fn read_range<T: StorableType>(
+        &self,
+        collection_path: &str,
+        from_seq: u64,
+        to_seq: u64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let logs = self.logs.read().unwrap();
+        Ok(logs
+            .get(collection_path)
+            .map(|log| {
+                log.iter()
+                    .skip(from_seq as usize)
+                    .take(to_seq.saturating_sub(from_seq) as usize)
+                    .filter(|record| record.is_type::<T>())
+                    .filter_map(|record| record.to_entry().ok())
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+}
+
+#[async_trait]
+impl Storer for MemoryStorer {
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        let mut entries = self.entries.write().unwrap();
+        match entries.get(path) {
+            Some(record) if record.is_type::<T>() => {
+                entries.remove(path);
+                Ok(())
+            }
+            _ => Err(MemoryStorerError::NotFound.into()),
+        }
+    }
+
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        self.get_indexed::<T>(path, &T::get_index()).await
+    }
+
+    async fn create<T: StorableType>(&self, entry: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        self.insert(&entry)?;
+        Ok(entry)
+    }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        let mut entries = self.entries.write().unwrap();
+        let mut record = match entries.get(from) {
+            Some(record) if record.is_type::<T>() => record.clone(),
+            _ => return Err(MemoryStorerError::NotFound.into()),
+        };
+        if let Some(entry) = record.entry.as_object_mut() {
+            entry.insert("path".to_owned(), to.into());
+        }
+        let entry = record.to_entry()?;
+        entries.remove(from);
+        entries.insert(to.to_owned(), record);
+        Ok(entry)
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl DynStorer for MemoryStorer {
+    async fn get_value(&self, path: &str) -> Result<Value, CryptoError> {
+        let entries = self.entries.read().unwrap();
+        match entries.get(path) {
+            Some(record) => Ok(record.entry.clone()),
+            None => Err(MemoryStorerError::NotFound.into()),
+        }
+    }
+
+    async fn create_value(&self, path: &str, value: Value) -> Result<(), CryptoError> {
+        // Parsed as an entry of any type so that its index fields can be recorded
+        let entry: Entry<Type> =
+            serde_json::from_value(value).map_err(|e| MemoryStorerError::InternalError {
+                source: Box::new(e),
+            })?;
+        self.insert(&entry.rename(path.to_owned()))
+    }
+
+    async fn delete_value(&self, path: &str) -> Result<(), CryptoError> {
+        match self.entries.write().unwrap().remove(path) {
+            Some(_) => Ok(()),
+            None => Err(MemoryStorerError::NotFound.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MemoryStorer;
+    use crate::{
+        key::{
+            ring::RingEd25519SecretAsymmetricKey,
+            sodiumoxide::{
+                SodiumOxideCurve25519PublicAsymmetricKey, SodiumOxideCurve25519SecretAsymmetricKey,
+                SodiumOxideEd25519PublicAsymmetricKey, SodiumOxideEd25519SecretAsymmetricKey,
+                SodiumOxideSymmetricKey,
+            },
+        },
+        CryptoError, Data, Entry, HasByteSource, HasFingerprint, HasPublicKey, IndexedStorer, Key,
+        PublicAsymmetricKey, State, Storer, StringDataBuilder, SymmetricKey, ToEntry,
+        VectorByteSource, DEFAULT_MAX_PAGE_SIZE,
+    };
+    use chrono::{Duration, TimeZone, Utc};
+
+    #[tokio::test]
+    async fn test_apply_patch() {
+        let storer = MemoryStorer::new();
+        let original = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        let updated = Data::String("goodbye, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        let patch = original.diff(&updated).unwrap();
+        assert!(patch.builder.is_none());
+        assert!(patch.value.is_some());
+
+        storer.create(original).await.unwrap();
+        storer.apply_patch::<Data>(".data.", patch).await.unwrap();
+        match storer
+            .get::<Data>(".data.")
+            .await
+            .unwrap()
+            .resolve()
+            .await
+            .unwrap()
+        {
+            Data::String(s) => assert_eq!(s, "goodbye, world!"),
+            _ => panic!("Resolved data should have been a string"),
+        }
+        // Only public keys are fingerprinted
+        assert!(storer
+            .get::<Data>(".data.")
+            .await
+            .unwrap()
+            .fingerprint()
+            .is_none());
+        assert!(updated.fingerprint().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_metadata_roundtrip() {
+        let storer = MemoryStorer::new();
+        let mut entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        assert_eq!(entry.set_metadata("owner", "alice"), None);
+        entry.set_metadata("rotation", "90d");
+        storer.create(entry).await.unwrap();
+
+        let fetched = storer.get::<Data>(".data.").await.unwrap();
+        assert_eq!(fetched.get_metadata("owner"), Some("alice"));
+        assert_eq!(fetched.get_metadata("rotation"), Some("90d"));
+        assert_eq!(fetched.get_metadata("missing"), None);
+        assert_eq!(fetched.metadata.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_metadata() {
+        let storer = MemoryStorer::new();
+        let mut original = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.".to_owned())
+            .unwrap();
+        original.set_metadata("owner", "alice");
+        original.set_metadata("rotation", "90d");
+        let mut updated = original.clone();
+        updated.set_metadata("owner", "bob");
+        updated.metadata.remove("rotation");
+
+        let patch = original.diff(&updated).unwrap();
+        assert!(patch.builder.is_none());
+        assert!(patch.value.is_none());
+        assert!(!patch.is_empty());
+
+        storer.create(original).await.unwrap();
+        storer.apply_patch::<Data>(".data.", patch).await.unwrap();
+        let fetched = storer.get::<Data>(".data.").await.unwrap();
+        assert_eq!(fetched.get_metadata("owner"), Some("bob"));
+        assert_eq!(fetched.get_metadata("rotation"), None);
+        assert!(fetched.diff(&updated).unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_apply_patch_not_found() {
+        let storer = MemoryStorer::new();
+        let patch = Default::default();
+        assert!(storer.apply_patch::<Data>(".data.", patch).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_by_fingerprint() {
+        let storer = MemoryStorer::new();
+        let (alice_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let (bob_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let alice_fingerprint = alice_key.fingerprint().unwrap();
+        let bob_fingerprint = bob_key.fingerprint().unwrap();
+        assert_ne!(alice_fingerprint, bob_fingerprint);
+
+        storer
+            .create(alice_key.to_unsealed_entry(".alice.".to_owned()).unwrap())
+            .await
+            .unwrap();
+        storer
+            .create(bob_key.to_unsealed_entry(".bob.".to_owned()).unwrap())
+            .await
+            .unwrap();
+
+        let alice_entry = storer
+            .get_by_fingerprint::<SodiumOxideCurve25519PublicAsymmetricKey>(&alice_fingerprint)
+            .await
+            .unwrap();
+        let bob_entry = storer
+            .get_by_fingerprint::<SodiumOxideCurve25519PublicAsymmetricKey>(&bob_fingerprint)
+            .await
+            .unwrap();
+        assert_eq!(alice_entry.path, ".alice.");
+        assert_eq!(bob_entry.path, ".bob.");
+        assert_eq!(
+            alice_entry.resolve().await.unwrap().fingerprint().unwrap(),
+            alice_fingerprint
+        );
+        assert_eq!(
+            bob_entry
+                .resolve()
+                .await
+                .unwrap()
+                .byte_source()
+                .get()
+                .unwrap()
+                .len(),
+            SodiumOxideCurve25519PublicAsymmetricKey::KEYBYTES
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_by_fingerprint_skips_secret_keys() {
+        let storer = MemoryStorer::new();
+        let (_, secret_key) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        let secret_fingerprint = crate::key::fingerprint(secret_key.byte_source().get().unwrap());
+        let entry = secret_key.to_unsealed_entry(".secret.".to_owned()).unwrap();
+        assert!(entry.fingerprint().is_none());
+        storer.create(entry).await.unwrap();
+        assert!(storer
+            .get_by_fingerprint::<SodiumOxideCurve25519SecretAsymmetricKey>(&secret_fingerprint)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_by_fingerprint_not_found() {
+        let storer = MemoryStorer::new();
+        assert!(storer
+            .get_by_fingerprint::<SodiumOxideCurve25519PublicAsymmetricKey>("abcd")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_by_algorithm() {
+        let storer = MemoryStorer::new();
+        let (sodiumoxide_ed25519, _) = SodiumOxideEd25519PublicAsymmetricKey::new();
+        let ring_ed25519 = RingEd25519SecretAsymmetricKey::new()
+            .unwrap()
+            .public_key()
+            .unwrap();
+        let (x25519, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        storer
+            .create(
+                sodiumoxide_ed25519
+                    .to_unsealed_entry(".keys.a.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(
+                ring_ed25519
+                    .to_unsealed_entry(".keys.b.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        storer
+            .create(x25519.to_unsealed_entry(".keys.c.".to_owned()).unwrap())
+            .await
+            .unwrap();
+        storer
+            .create(
+                SodiumOxideEd25519SecretAsymmetricKey::new()
+                    .to_unsealed_entry(".keys.d.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let ed25519 = storer
+            .list_by_algorithm::<PublicAsymmetricKey>(".keys.", "1.3.101.112", 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            ed25519.iter().map(|e| e.path.as_str()).collect::<Vec<_>>(),
+            vec![".keys.a.", ".keys.b."]
+        );
+        let x25519 = storer
+            .list_by_algorithm::<PublicAsymmetricKey>(".keys.", "1.3.101.110", 0, 10)
+            .await
+            .unwrap();
+        assert_eq!(x25519.len(), 1);
+        assert_eq!(x25519[0].path, ".keys.c.");
+    }
+
+    #[tokio::test]
+    async fn test_count() {
+        let storer = MemoryStorer::new();
+        for i in 0..3 {
+            storer
+                .create(
+                    Data::U64(i)
+                        .to_unsealed_entry(format!(".data.{}.", i))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        for i in 0..2 {
+            storer
+                .create(
+                    SodiumOxideSymmetricKey::new()
+                        .to_unsealed_entry(format!(".keys.{}.", i))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+        let (public_key, _) = SodiumOxideCurve25519PublicAsymmetricKey::new();
+        storer
+            .create(
+                public_key
+                    .to_unsealed_entry(".keys.public.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(storer.count::<Data>("").await.unwrap(), 3);
+        assert_eq!(storer.count::<SymmetricKey>("").await.unwrap(), 2);
+        assert_eq!(storer.count::<Key>("").await.unwrap(), 3);
+        assert_eq!(storer.count::<Key>(".keys.").await.unwrap(), 3);
+        assert_eq!(storer.count::<Data>(".keys.").await.unwrap(), 0);
+        assert_eq!(storer.count::<Data>(".data.1.").await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix() {
+        let storer = MemoryStorer::new();
+        for path in [
+            ".tenant123.a.",
+            ".tenant123.b.",
+            ".tenant12.a.",
+            ".tenant1234.a.",
+        ]
+        .iter()
+        {
+            storer
+                .create(Data::U64(1).to_unsealed_entry(path.to_string()).unwrap())
+                .await
+                .unwrap();
+        }
+        storer
+            .create(
+                SodiumOxideSymmetricKey::new()
+                    .to_unsealed_entry(".tenant123.key.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(storer.delete_prefix(".tenant123").await.unwrap(), 3);
+        assert_eq!(storer.count::<Data>(".tenant123.").await.unwrap(), 0);
+        assert_eq!(storer.count::<Key>(".tenant123.").await.unwrap(), 0);
+        assert!(storer.get::<Data>(".tenant12.a.").await.is_ok());
+        assert!(storer.get::<Data>(".tenant1234.a.").await.is_ok());
+
+        assert_eq!(storer.delete_prefix(".tenant12.").await.unwrap(), 1);
+        assert!(storer.get::<Data>(".tenant1234.a.").await.is_ok());
+        assert_eq!(storer.delete_prefix(".tenant12.").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix_rejects_matching_everything() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        for prefix in ["", ".", ".."].iter() {
+            match storer.delete_prefix(prefix).await {
+                Err(CryptoError::InvalidPathPrefix { prefix: p }) => assert_eq!(p, *prefix),
+                _ => panic!("Prefix \"{}\" should have been rejected", prefix),
+            }
+        }
+        assert!(storer.get::<Data>(".data.").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_list_modified_since() {
+        let storer = MemoryStorer::new();
+        let since = Utc.with_ymd_and_hms(2021, 6, 1, 0, 0, 0).unwrap();
+        for (i, days) in [-2i64, -1, 1, 2].iter().enumerate() {
+            let bytes = VectorByteSource::new(Some(&b"hello"[..]), since + Duration::days(*days));
+            storer
+                .create(Entry::<Data>::new(
+                    format!(".data.{}.", i),
+                    StringDataBuilder {}.into(),
+                    State::Unsealed {
+                        bytes: bytes.into(),
+                    },
+                ))
+                .await
+                .unwrap();
+        }
+
+        let entries = storer
+            .list_modified_since::<Data>(".data.", since, 0, 10)
+            .await
+            .unwrap();
+        let paths: Vec<&str> = entries.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(paths, vec![".data.2.", ".data.3."]);
+        assert!(entries.iter().all(|e| e.last_modified().unwrap() > since));
+        assert_eq!(
+            storer
+                .list_modified_since::<Data>(".data.", since, 1, 10)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(storer
+            .list_modified_since::<Key>(".data.", since, 0, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_rejects_invalid_page_size() {
+        let storer = MemoryStorer::new();
+        storer
+            .create(
+                Data::String("hello, world!".to_owned())
+                    .to_unsealed_entry(".data.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        for page_size in [-1, 0, DEFAULT_MAX_PAGE_SIZE + 1].iter() {
+            match storer.list::<Data>(".data.", 0, *page_size).await {
+                Err(CryptoError::InvalidPageSize { requested, max }) => {
+                    assert_eq!(requested, *page_size);
+                    assert_eq!(max, DEFAULT_MAX_PAGE_SIZE);
+                }
+                _ => panic!("Page size {} should have been rejected", page_size),
+            }
+        }
+        assert_eq!(
+            storer
+                .list::<Data>(".data.", 0, DEFAULT_MAX_PAGE_SIZE)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Every listing method checks the page size
+        match storer
+            .list_by_algorithm::<Data>(".data.", "1.2.3", 0, 0)
+            .await
+        {
+            Err(CryptoError::InvalidPageSize { requested: 0, .. }) => (),
+            _ => panic!("Page size should have been rejected when listing by algorithm"),
+        }
+        match storer
+            .list_modified_since::<Data>(".data.", Utc::now(), 0, DEFAULT_MAX_PAGE_SIZE + 1)
+            .await
+        {
+            Err(CryptoError::InvalidPageSize { .. }) => (),
+            _ => panic!("Page size should have been rejected when listing by modification"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_max_page_size() {
+        let storer = MemoryStorer::new().with_max_page_size(1).unwrap();
+        assert_eq!(storer.max_page_size(), 1);
+        match storer.list::<Data>(".data.", 0, 2).await {
+            Err(CryptoError::InvalidPageSize {
+                requested: 2,
+                max: 1,
+            }) => (),
+            _ => panic!("Page size above the storer's maximum should have been rejected"),
+        }
+
+        // Other storers keep the default
+        assert_eq!(MemoryStorer::new().max_page_size(), DEFAULT_MAX_PAGE_SIZE);
+
+        for max in [0, -1].iter() {
+            match MemoryStorer::new().with_max_page_size(*max) {
+                Err(CryptoError::InvalidMaxPageSize { max: m }) => assert_eq!(m, *max),
+                _ => panic!("Maximum page size {} should have been rejected", max),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check() {
+        assert!(MemoryStorer::new().health_check().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rename() {
+        let storer = MemoryStorer::new();
+        let mut entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(".data.old.".to_owned())
+            .unwrap();
+        entry.set_metadata("owner", "alice");
+        storer.create(entry).await.unwrap();
+
+        let renamed = storer
+            .rename::<Data>(".data.old.", ".data.new.")
+            .await
+            .unwrap();
+        assert_eq!(renamed.path, ".data.new.");
+        match storer.get::<Data>(".data.old.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Renamed entry should no longer be found at its old path"),
+        }
+        let entry = storer.get::<Data>(".data.new.").await.unwrap();
+        assert_eq!(entry.path, ".data.new.");
+        assert_eq!(entry.get_metadata("owner"), Some("alice"));
+        assert_eq!(
+            entry.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+
+        match storer.rename::<Data>(".data.old.", ".data.other.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Renaming a missing entry should have been not found"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_append_and_read_range() {
+        let storer = MemoryStorer::new();
+        for (i, event) in ["created", "updated", "deleted"].iter().enumerate() {
+            let entry = Data::String((*event).to_owned())
+                .to_unsealed_entry(".events.".to_owned())
+                .unwrap();
+            assert_eq!(storer.append(".log.", entry).await.unwrap(), i as u64);
+        }
+
+        let entries = storer.read_range::<Data>(".log.", 1, 3).await.unwrap();
+        let mut events = vec![];
+        for entry in entries.iter() {
+            events.push(entry.resolve().await.unwrap().clone());
+        }
+        assert_eq!(
+            events,
+            vec![
+                Data::String("updated".to_owned()),
+                Data::String("deleted".to_owned())
+            ]
+        );
+        assert_eq!(
+            storer
+                .read_range::<Data>(".log.", 1, 2)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(storer
+            .read_range::<Data>(".other-log.", 0, 3)
+            .await
+            .unwrap()
+            .is_empty());
+        match storer.get::<Data>(".events.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Appended entries should not be stored as regular entries"),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{CryptoError, Entry, EntryPatch, StorableType, Storer, TypeStorer};
+use async_trait::async_trait;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Storer operation measured by a `MetricsStorer`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StorerOperation {
+    Get,
+    Create,
+    Delete,
+}
+
+impl StorerOperation {
+    /// Returns a stable identifier of the operation, suitable for use as a metric label
+    pub fn name(&self) -> &'static str {
+        match self {
+            StorerOperation::Get => "get",
+            StorerOperation::Create => "create",
+            StorerOperation::Delete => "delete",
+        }
+    }
+}
+
+/// Receives the measurements taken by a `MetricsStorer`, typically to forward them to
+/// the metrics system of the embedding application
+pub trait MetricsSink: Send + Sync {
+    /// Counts a completed operation along with whether it succeeded
+    fn increment(&self, operation: StorerOperation, success: bool);
+
+    /// Records how long a completed operation took, whether it succeeded or not
+    fn record_latency(&self, operation: StorerOperation, latency: Duration);
+}
+
+/// Wraps another storer and reports a count and latency for each `get`, `create` and
+/// `delete` to a `MetricsSink`. Other operations are passed straight to the inner storer
+/// so that it can still use its own implementations of them.
+///
+/// The sink cannot be serialized, so converting this storer into a `TypeStorer`, as is
+/// done when building referenced entries, yields the inner storer without metrics.
+#[derive(Clone)]
+pub struct MetricsStorer<S> {
+    inner: S,
+    sink: Arc<dyn MetricsSink>,
+}
+
+impl<S: Storer> From<MetricsStorer<S>> for TypeStorer {
+    fn from(ms: MetricsStorer<S>) -> Self {
+        ms.inner.into()
+    }
+}
+
+impl<S: Storer> MetricsStorer<S> {
+    /// Instantiates a storer reporting the operations on the inner storer to `sink`
+    pub fn new(inner: S, sink: Arc<dyn MetricsSink>) -> Self {
+        MetricsStorer { inner, sink }
+    }
+
+    /// Returns a reference to the wrapped storer
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+
+    fn record<R>(
+        &self,
+        operation: StorerOperation,
+        start: Instant,
+        result: &Result<R, CryptoError>,
+    ) {
+        self.sink.record_latency(operation, start.elapsed());
+        self.sink.increment(operation, result.is_ok());
+    }
+}
+
+#[async_trait]
+impl<S: Storer> Storer for MetricsStorer<S> {
+    async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
+        let start = Instant::now();
+        let result = self.inner.get::<T>(path).await;
+        self.record(StorerOperation::Get, start, &result);
+        result
+    }
+
+    async fn create<T: StorableType>(&self, value: Entry<T>) -> Result<Entry<T>, CryptoError> {
+        let start = Instant::now();
+        let result = self.inner.create(value).await;
+        self.record(StorerOperation::Create, start, &result);
+        result
+    }
+
+    async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
+        let start = Instant::now();
+        let result = self.inner.delete::<T>(path).await;
+        self.record(StorerOperation::Delete, start, &result);
+        result
+    }
+
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        self.inner.apply_patch::<T>(path, patch).await
+    }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        self.inner.rename::<T>(from, to).await
+    }
+
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        self.inner.health_check().await
+    }
+
+    async fn flush(&self) -> Result<(), CryptoError> {
+        self.inner.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{MetricsSink, MetricsStorer, StorerOperation};
+    use crate::{Data, MemoryStorer, Storer, ToEntry};
+    use std::{
+        collections::HashMap,
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    #[derive(Default)]
+    struct CountingSink {
+        counts: Mutex<HashMap<(StorerOperation, bool), u64>>,
+        latencies: Mutex<Vec<(StorerOperation, Duration)>>,
+    }
+
+    impl MetricsSink for CountingSink {
+        fn increment(&self, operation: StorerOperation, success: bool) {
+            *self
+                .counts
+                .lock()
+                .unwrap()
+                .entry((operation, success))
+                .or_insert(0) += 1;
+        }
+
+        fn record_latency(&self, operation: StorerOperation, latency: Duration) {
+            self.latencies.lock().unwrap().push((operation, latency));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metricsstorer_counts_operations() {
+        let sink = Arc::new(CountingSink::default());
+        let storer = MetricsStorer::new(MemoryStorer::new(), sink.clone());
+
+        storer
+            .create(Data::U64(1).to_unsealed_entry(".data.".to_owned()).unwrap())
+            .await
+            .unwrap();
+        storer.get::<Data>(".data.").await.unwrap();
+        assert!(storer.get::<Data>(".missing.").await.is_err());
+
+        let counts = sink.counts.lock().unwrap();
+        assert_eq!(counts.get(&(StorerOperation::Create, true)), Some(&1));
+        assert_eq!(counts.get(&(StorerOperation::Get, true)), Some(&1));
+        assert_eq!(counts.get(&(StorerOperation::Get, false)), Some(&1));
+        assert_eq!(counts.get(&(StorerOperation::Delete, true)), None);
+
+        let latencies = sink.latencies.lock().unwrap();
+        assert_eq!(
+            latencies.iter().map(|(op, _)| *op).collect::<Vec<_>>(),
+            vec![
+                StorerOperation::Create,
+                StorerOperation::Get,
+                StorerOperation::Get
+            ]
+        );
+    }
+}
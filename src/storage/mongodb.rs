@@ -1,19 +1,27 @@
 use crate::{
-    CryptoError, Entry, IndexedStorer, IndexedTypeStorer, StorableType, Storer, TypeStorer,
+    entry::public_key_fingerprint,
+    storage::{check_page_size, default_max_page_size, deletion_prefix, validate_max_page_size},
+    AsymmetricKeyBuilder, CryptoError, Entry, EntryPatch, IndexedStorer, IndexedTypeStorer,
+    KeyBuilder, StorableType, Storer, TypeBuilder, TypeStorer,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
 use futures::StreamExt;
 use mongodb::{
     bson::{self, Bson, Document},
+    error::{TRANSIENT_TRANSACTION_ERROR, UNKNOWN_TRANSACTION_COMMIT_RESULT},
     options::ClientOptions,
-    options::{FindOneOptions, FindOptions},
-    Client,
+    options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, IndexOptions, ReturnDocument},
+    Client, ClientSession, Collection, Database, IndexModel,
 };
 use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
 use std::{
+    convert::TryFrom,
     error::Error,
     fmt::{self, Display, Formatter},
+    sync::Arc,
+    time::Duration,
 };
 
 #[derive(Debug)]
@@ -62,13 +70,37 @@ impl From<MongoStorerError> for CryptoError {
     }
 }
 
-/// Stores an instance of a mongodb-backed key storer
+const DEFAULT_COLLECTION_NAME: &str = "entries";
+
+/// Suffix of the collection appended log entries are stored in
+const LOG_COLLECTION_SUFFIX: &str = "_log";
+
+/// Suffix of the collection holding the sequence counter of each log
+const LOG_COUNTERS_COLLECTION_SUFFIX: &str = "_log_counters";
+
+/// Number of times an append's transaction, and separately its commit, is attempted
+/// before the last error is returned
+const MAX_TRANSACTION_ATTEMPTS: u32 = 5;
+
+/// Delay before the first retry of a transaction or commit, doubled on every retry
+const TRANSACTION_RETRY_BACKOFF: Duration = Duration::from_millis(50);
+
+fn default_collection_name() -> String {
+    DEFAULT_COLLECTION_NAME.to_owned()
+}
+
+/// Stores an instance of a mongodb-backed key storer. Clones and handles returned by
+/// `with_collection` share the same client, and so the same connection pool.
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MongoStorer {
     url: String,
     db_name: String,
+    #[serde(default = "default_collection_name")]
+    collection_name: String,
+    #[serde(default = "default_max_page_size")]
+    max_page_size: i64,
     #[serde(skip)]
-    client: OnceCell<Client>,
+    client: Arc<OnceCell<Client>>,
 }
 
 impl From<MongoStorer> for IndexedTypeStorer {
@@ -85,14 +117,78 @@ impl From<MongoStorer> for TypeStorer {
 
 impl MongoStorer {
     /// Instantiates a mongo-backed key storer using a URL to the mongo cluster and the
-    /// name of the DB to connect to.
-    pub fn new(url: &str, db_name: &str) -> Self {
+    /// names of the DB and collection to store entries in.
+    pub fn new(url: &str, db_name: &str, collection_name: &str) -> Self {
         MongoStorer {
             url: url.to_owned(),
             db_name: db_name.to_owned(),
-            client: OnceCell::new(),
+            collection_name: collection_name.to_owned(),
+            max_page_size: default_max_page_size(),
+            client: Arc::new(OnceCell::new()),
+        }
+    }
+
+    /// Sets the largest page size accepted when listing entries, which must be positive
+    pub fn with_max_page_size(mut self, max: i64) -> Result<Self, CryptoError> {
+        self.max_page_size = validate_max_page_size(max)?;
+        Ok(self)
+    }
+
+    /// Returns the largest page size accepted when listing entries
+    pub fn max_page_size(&self) -> i64 {
+        self.max_page_size
+    }
+
+    /// Returns a storer for a different collection in the same DB. The new storer shares
+    /// this storer's client, so no additional connection pool is created.
+    pub fn with_collection(&self, collection_name: &str) -> Self {
+        MongoStorer {
+            collection_name: collection_name.to_owned(),
+            ..self.clone()
         }
     }
+
+    /// Returns the name of the collection entries are stored in
+    pub fn collection_name(&self) -> &str {
+        &self.collection_name
+    }
+
+    /// Creates the indexes that speed up the storer's queries: one on the algorithm OID
+    /// of stored keys, and a unique one on the sequence numbers of each log so that two
+    /// appended entries can never share one. Intended to be run once at setup with
+    /// credentials allowed to create indexes; the storer works without them. Creating an
+    /// index that already exists is a no-op, but the unique index fails to build if the
+    /// log collection already holds duplicate sequence numbers.
+    pub async fn ensure_indexes(&self) -> Result<(), CryptoError> {
+        let internal_error = |e: mongodb::error::Error| -> CryptoError {
+            MongoStorerError::InternalError {
+                source: Box::new(e),
+            }
+            .into()
+        };
+        self.collection()
+            .await?
+            .create_index(
+                IndexModel::builder()
+                    .keys(bson::doc! { "algorithm_oid": 1 })
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(internal_error)?;
+        self.suffixed_collection(LOG_COLLECTION_SUFFIX)
+            .await?
+            .create_index(
+                IndexModel::builder()
+                    .keys(bson::doc! { "collection": 1, "seq": 1 })
+                    .options(IndexOptions::builder().unique(true).build())
+                    .build(),
+                None,
+            )
+            .await
+            .map_err(internal_error)?;
+        Ok(())
+    }
 }
 
 impl MongoStorer {
@@ -115,6 +211,240 @@ impl MongoStorer {
             }
         }
     }
+
+    async fn database(&self) -> Result<Database, MongoStorerError> {
+        Ok(self.get_client().await?.database(&self.db_name))
+    }
+
+    async fn collection(&self) -> Result<Collection<Document>, MongoStorerError> {
+        Ok(self.database().await?.collection(&self.collection_name))
+    }
+
+    /// Returns the collection next to the entries collection with the given suffix
+    async fn suffixed_collection(
+        &self,
+        suffix: &str,
+    ) -> Result<Collection<Document>, MongoStorerError> {
+        Ok(self
+            .database()
+            .await?
+            .collection(&format!("{}{}", self.collection_name, suffix)))
+    }
+
+    /// Returns the builder of the entry matching the filter
+    async fn stored_builder(&self, filter: &Document) -> Result<TypeBuilder, MongoStorerError> {
+        let doc = self
+            .collection()
+            .await?
+            .find_one(filter.clone(), None)
+            .await
+            .map_err(|e| MongoStorerError::InternalError {
+                source: Box::new(e),
+            })?
+            .ok_or(MongoStorerError::NotFound)?;
+        let builder = doc
+            .get("builder")
+            .cloned()
+            .ok_or(MongoStorerError::NotFound)?;
+        bson::from_bson(builder).map_err(|e| MongoStorerError::InternalError {
+            source: Box::new(e),
+        })
+    }
+
+    /// Lists entries of type T under the given path prefix that also match the given
+    /// filter. The filter is ANDed with the type index, so it can narrow the results by
+    /// any stored field but can never widen them to entries of another type. Page sizes
+    /// are validated like in `list`.
+    pub async fn list_where<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        filter: Document,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let filter = where_filter(path_prefix, T::get_index(), filter);
+        let filter_options = FindOptions::builder().skip(skip).limit(page_size).build();
+        self.find_entries(filter, filter_options).await
+    }
+
+    /// Lists entries of type T under the given path prefix whose metadata holds the given
+    /// value for the given key. Metadata is stored as a plain subdocument of the entry,
+    /// so it can also be matched in `list_where` filters as `metadata.<key>`.
+    pub async fn list_by_metadata<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        key: &str,
+        value: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        self.list_where(path_prefix, metadata_filter(key, value)?, skip, page_size)
+            .await
+    }
+
+    async fn find_entries<T: StorableType>(
+        &self,
+        filter: Document,
+        filter_options: FindOptions,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let cursor = self
+            .collection()
+            .await?
+            .find(filter, filter_options)
+            .await
+            .map_err(|e| -> CryptoError {
+                MongoStorerError::InternalError {
+                    source: Box::new(e),
+                }
+                .into()
+            })?;
+
+        Ok(cursor
+            .filter_map(|doc| async move {
+                match doc {
+                    Ok(doc) => Some(doc),
+                    Err(_) => None,
+                }
+            })
+            .collect::<Vec<Document>>()
+            .await
+            .into_iter()
+            .filter_map(|doc| -> Option<Entry<T>> {
+                match bson::from_bson(Bson::Document(doc)) {
+                    Ok(entry) => Some(entry),
+                    Err(_) => None,
+                }
+            })
+            .collect::<Vec<Entry<T>>>())
+    }
+}
+
+/// Increments the log's counter and inserts the entry under the sequence number it was
+/// at, both within the session's transaction. Returns `None` if the counter could not
+/// be read back.
+async fn append_in_transaction(
+    counters: &Collection<Document>,
+    log: &Collection<Document>,
+    collection_path: &str,
+    entry: Document,
+    session: &mut ClientSession,
+) -> Result<Option<u64>, mongodb::error::Error> {
+    let options = FindOneAndUpdateOptions::builder()
+        .upsert(true)
+        .return_document(ReturnDocument::After)
+        .build();
+    let counter = counters
+        .find_one_and_update_with_session(
+            bson::doc! { "_id": collection_path },
+            bson::doc! { "$inc": { "next_seq": 1_i64 } },
+            options,
+            session,
+        )
+        .await?;
+    let seq = match counter.and_then(|c| c.get_i64("next_seq").ok()) {
+        Some(next_seq) => next_seq - 1,
+        None => return Ok(None),
+    };
+    log.insert_one_with_session(
+        bson::doc! { "collection": collection_path, "seq": seq, "entry": entry },
+        None,
+        session,
+    )
+    .await?;
+    Ok(Some(seq as u64))
+}
+
+/// Returns the delay to wait after the given failed attempt, counting from 1
+fn transaction_retry_delay(attempt: u32) -> Duration {
+    TRANSACTION_RETRY_BACKOFF * 2u32.pow(attempt - 1)
+}
+
+/// Commits the session's transaction, retrying the commit alone while the server cannot
+/// tell whether it was applied, up to `MAX_TRANSACTION_ATTEMPTS` times. Retrying a
+/// commit never applies the transaction twice.
+async fn commit_with_retry(session: &mut ClientSession) -> Result<(), mongodb::error::Error> {
+    let mut attempt = 1;
+    loop {
+        match session.commit_transaction().await {
+            Err(e)
+                if e.contains_label(UNKNOWN_TRANSACTION_COMMIT_RESULT)
+                    && attempt < MAX_TRANSACTION_ATTEMPTS =>
+            {
+                tokio::time::sleep(transaction_retry_delay(attempt)).await;
+                attempt += 1;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Builds the filter matching the log entries of the given type with sequence numbers in
+/// `from_seq..to_seq`. Sequence numbers are stored as i64, so bounds beyond `i64::MAX`
+/// are clamped to it rather than wrapping around to negative values.
+fn log_range_filter(
+    collection_path: &str,
+    from_seq: u64,
+    to_seq: u64,
+    index: Option<Document>,
+) -> Document {
+    let mut filter = bson::doc! {
+        "collection": collection_path,
+        "seq": {
+            "$gte": i64::try_from(from_seq).unwrap_or(i64::MAX),
+            "$lt": i64::try_from(to_seq).unwrap_or(i64::MAX),
+        },
+    };
+    if let Some(i) = index {
+        filter.insert("entry.value", i);
+    }
+    filter
+}
+
+/// Combines a path prefix and type index with a caller-supplied filter. The two are
+/// joined with `$and` rather than merged key by key so that the caller's filter cannot
+/// replace the `path` or `value` constraints.
+fn where_filter(path_prefix: &str, index: Option<Document>, filter: Document) -> Document {
+    let mut base = bson::doc! {
+        "path": { "$regex": format!("^{}", escape_regex(path_prefix)) }
+    };
+    if let Some(i) = index {
+        base.insert("value", i);
+    }
+    bson::doc! { "$and": [base, filter] }
+}
+
+/// Builds the filter matching entries whose metadata holds `value` under `key`. Keys
+/// that would address a nested field or an operator instead of a single metadata field
+/// are rejected.
+fn metadata_filter(key: &str, value: &str) -> Result<Document, CryptoError> {
+    if key.is_empty() || key.contains('.') || key.starts_with('$') {
+        return Err(CryptoError::InvalidMetadataKey {
+            key: key.to_owned(),
+        });
+    }
+    let mut filter = Document::new();
+    filter.insert(format!("metadata.{}", key), value);
+    Ok(filter)
+}
+
+/// Converts a timestamp to a BSON datetime so range queries compare dates rather
+/// than strings. BSON datetimes only have millisecond precision.
+fn to_bson_datetime(dt: DateTime<Utc>) -> bson::DateTime {
+    bson::DateTime::from_millis(dt.timestamp_millis())
+}
+
+/// Escapes the regex metacharacters in the given string so it can be used as a
+/// literal prefix in a `$regex` query
+fn escape_regex(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
 }
 
 #[async_trait]
@@ -130,11 +460,8 @@ impl IndexedStorer for MongoStorer {
         }
 
         let filter_options = FindOneOptions::builder().build();
-        let client = self.get_client().await?;
-
-        client
-            .database(&self.db_name)
-            .collection("entries")
+        self.collection()
+            .await?
             .find_one(filter, filter_options)
             .await
             .map_err(|e| -> CryptoError {
@@ -161,18 +488,147 @@ impl IndexedStorer for MongoStorer {
         page_size: i64,
         index: &Option<Document>,
     ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
         let mut filter = bson::doc! { "path": path };
         if let Some(i) = index {
             filter.insert("value", i);
         }
         let filter_options = FindOptions::builder().skip(skip).limit(page_size).build();
+        self.find_entries(filter, filter_options).await
+    }
 
-        let cursor = self
+    async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError> {
+        let mut filter = bson::doc! {
+            "path": { "$regex": format!("^{}", escape_regex(path_prefix)) }
+        };
+        if let Some(i) = T::get_index() {
+            filter.insert("value", i);
+        }
+
+        self.collection()
+            .await?
+            .count_documents(filter, None)
+            .await
+            .map_err(|e| {
+                MongoStorerError::InternalError {
+                    source: Box::new(e),
+                }
+                .into()
+            })
+    }
+
+    async fn list_modified_since<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let filter = bson::doc! { "last_modified": { "$gt": to_bson_datetime(since) } };
+        self.list_where(path_prefix, filter, skip, page_size).await
+    }
+
+    async fn list_by_algorithm<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        oid: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let filter = bson::doc! { "algorithm_oid": oid };
+        self.list_where(path_prefix, filter, skip, page_size).await
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError> {
+        let filter = bson::doc! {
+            "path": { "$regex": format!("^{}", escape_regex(&deletion_prefix(prefix)?)) }
+        };
+
+        self.collection()
+            .await?
+            .delete_many(filter, None)
+            .await
+            .map(|result| result.deleted_count)
+            .map_err(|e| {
+                MongoStorerError::InternalError {
+                    source: Box::new(e),
+                }
+                .into()
+            })
+    }
+
+    async fn append<T: StorableType>(
+        &self,
+        collection_path: &str,
+        entry: Entry<T>,
+    ) -> Result<u64, CryptoError> {
+        // The counter increment and the insert run in a single transaction, retried with
+        // backoff when it conflicts with a concurrent append, so a failed append never
+        // leaves a gap.
+        // Transactions require the server to be a replica set or sharded cluster.
+        let internal_error = |e: mongodb::error::Error| -> CryptoError {
+            MongoStorerError::InternalError {
+                source: Box::new(e),
+            }
+            .into()
+        };
+        let doc = bson::to_document(&entry).map_err(|e| MongoStorerError::InternalError {
+            source: Box::new(e),
+        })?;
+        let counters = self
+            .suffixed_collection(LOG_COUNTERS_COLLECTION_SUFFIX)
+            .await?;
+        let log = self.suffixed_collection(LOG_COLLECTION_SUFFIX).await?;
+        let mut session = self
             .get_client()
             .await?
-            .database(&self.db_name)
-            .collection("entries")
-            .find(filter, filter_options)
+            .start_session(None)
+            .await
+            .map_err(internal_error)?;
+
+        let mut attempt = 1;
+        loop {
+            session
+                .start_transaction(None)
+                .await
+                .map_err(internal_error)?;
+            let result =
+                append_in_transaction(&counters, &log, collection_path, doc.clone(), &mut session)
+                    .await;
+            let result = match result {
+                Ok(Some(seq)) => commit_with_retry(&mut session).await.map(|_| Some(seq)),
+                other => {
+                    let _ = session.abort_transaction().await;
+                    other
+                }
+            };
+            match result {
+                Ok(Some(seq)) => return Ok(seq),
+                Ok(None) => return Err(MongoStorerError::NotFound.into()),
+                Err(e)
+                    if e.contains_label(TRANSIENT_TRANSACTION_ERROR)
+                        && attempt < MAX_TRANSACTION_ATTEMPTS =>
+                {
+                    tokio::time::sleep(transaction_retry_delay(attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(internal_error(e)),
+            }
+        }
+    }
+
+    async fn read_range<T: StorableType>(
+        &self,
+        collection_path: &str,
+        from_seq: u64,
+        to_seq: u64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let filter = log_range_filter(collection_path, from_seq, to_seq, T::get_index());
+        let options = FindOptions::builder().sort(bson::doc! { "seq": 1 }).build();
+        let cursor = self
+            .suffixed_collection(LOG_COLLECTION_SUFFIX)
+            .await?
+            .find(filter, options)
             .await
             .map_err(|e| -> CryptoError {
                 MongoStorerError::InternalError {
@@ -184,7 +640,7 @@ impl IndexedStorer for MongoStorer {
         Ok(cursor
             .filter_map(|doc| async move {
                 match doc {
-                    Ok(doc) => Some(doc),
+                    Ok(doc) => doc.get_document("entry").ok().cloned(),
                     Err(_) => None,
                 }
             })
@@ -199,6 +655,37 @@ impl IndexedStorer for MongoStorer {
             })
             .collect::<Vec<Entry<T>>>())
     }
+
+    async fn get_by_fingerprint<T: StorableType>(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Entry<T>, CryptoError> {
+        let mut filter = bson::doc! { "fingerprint": fingerprint };
+        if let Some(i) = T::get_index() {
+            filter.insert("value", i);
+        }
+
+        let filter_options = FindOneOptions::builder().build();
+        self.collection()
+            .await?
+            .find_one(filter, filter_options)
+            .await
+            .map_err(|e| -> CryptoError {
+                MongoStorerError::InternalError {
+                    source: Box::new(e),
+                }
+                .into()
+            })
+            .and_then(|doc| match doc {
+                Some(doc) => bson::from_bson(Bson::Document(doc)).map_err(|e| {
+                    MongoStorerError::InternalError {
+                        source: Box::new(e),
+                    }
+                    .into()
+                }),
+                None => Err(MongoStorerError::NotFound.into()),
+            })
+    }
 }
 
 #[async_trait]
@@ -211,10 +698,8 @@ impl Storer for MongoStorer {
         }
         let delete_options = mongodb::options::DeleteOptions::builder().build();
         match self
-            .get_client()
+            .collection()
             .await?
-            .database(&self.db_name)
-            .collection::<Document>("entries")
             .delete_one(filter, Some(delete_options))
             .await
         {
@@ -230,20 +715,42 @@ impl Storer for MongoStorer {
         self.get_indexed::<T>(path, &T::get_index()).await
     }
 
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        self.get_client()
+            .await
+            .map_err(|e| CryptoError::StorerUnavailable {
+                source: Box::new(e),
+            })?
+            .database(&self.db_name)
+            .run_command(bson::doc! { "ping": 1 }, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| CryptoError::StorerUnavailable {
+                source: Box::new(e),
+            })
+    }
+
     async fn create<T: StorableType>(&self, entry: Entry<T>) -> Result<Entry<T>, CryptoError> {
         let filter = bson::doc! { "path": &entry.path };
         let filter_options = mongodb::options::ReplaceOptions::builder()
             .upsert(true)
             .build();
-        let doc = bson::to_document(&entry).map_err(|e| MongoStorerError::InternalError {
+        let mut doc = bson::to_document(&entry).map_err(|e| MongoStorerError::InternalError {
             source: Box::new(e),
         })?;
+        if let Some(fingerprint) = entry.fingerprint() {
+            doc.insert("fingerprint", fingerprint);
+        }
+        if let Some(algorithm_oid) = entry.algorithm_oid() {
+            doc.insert("algorithm_oid", algorithm_oid);
+        }
+        if let Some(last_modified) = entry.last_modified() {
+            doc.insert("last_modified", to_bson_datetime(last_modified));
+        }
 
         match self
-            .get_client()
+            .collection()
             .await?
-            .database(&self.db_name)
-            .collection("entries")
             .replace_one(filter, doc, filter_options)
             .await
         {
@@ -254,4 +761,334 @@ impl Storer for MongoStorer {
             .into()),
         }
     }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        let mut filter = bson::doc! { "path": from };
+        if let Some(i) = T::get_index() {
+            filter.insert("value", i);
+        }
+        let update = bson::doc! { "$set": { "path": to } };
+        let options = FindOneAndUpdateOptions::builder()
+            .return_document(ReturnDocument::After)
+            .build();
+        let collection = self.collection().await?;
+
+        let doc = collection
+            .find_one_and_update(filter, update, options)
+            .await
+            .map_err(|e| MongoStorerError::InternalError {
+                source: Box::new(e),
+            })?
+            .ok_or(MongoStorerError::NotFound)?;
+
+        // The move itself is atomic, any entry previously stored at the new path is
+        // removed afterwards
+        if from != to {
+            let id = doc.get("_id").cloned().ok_or(MongoStorerError::NotFound)?;
+            collection
+                .delete_many(bson::doc! { "path": to, "_id": { "$ne": id } }, None)
+                .await
+                .map_err(|e| MongoStorerError::InternalError {
+                    source: Box::new(e),
+                })?;
+        }
+
+        bson::from_bson(Bson::Document(doc)).map_err(|e| {
+            MongoStorerError::InternalError {
+                source: Box::new(e),
+            }
+            .into()
+        })
+    }
+
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        let index = &T::get_index();
+        let mut filter = bson::doc! { "path": path };
+        if let Some(i) = index {
+            filter.insert("value", i);
+        }
+        let mut set = Document::new();
+        let mut unset = Document::new();
+        if let Some(ref builder) = patch.builder {
+            set.insert(
+                "builder",
+                bson::to_bson(builder).map_err(|e| MongoStorerError::InternalError {
+                    source: Box::new(e),
+                })?,
+            );
+            let algorithm_oid = match builder {
+                TypeBuilder::Key(kb) => kb.algorithm_oid(),
+                TypeBuilder::Data(_) => None,
+            };
+            match algorithm_oid {
+                Some(oid) => {
+                    set.insert("algorithm_oid", oid.to_string());
+                }
+                None => {
+                    unset.insert("algorithm_oid", "");
+                }
+            }
+            // A fingerprint stored for a public key must not outlive a change of type
+            if patch.value.is_none()
+                && !matches!(
+                    builder,
+                    TypeBuilder::Key(KeyBuilder::Asymmetric(AsymmetricKeyBuilder::Public(_)))
+                )
+            {
+                unset.insert("fingerprint", "");
+            }
+        }
+        if let Some(ref value) = patch.value {
+            set.insert(
+                "value",
+                bson::to_bson(value).map_err(|e| MongoStorerError::InternalError {
+                    source: Box::new(e),
+                })?,
+            );
+            // Only public keys are fingerprinted, so the builder the value will be stored
+            // with is needed, whether patched or already stored
+            let builder = match patch.builder {
+                Some(ref builder) => builder.clone(),
+                None => self.stored_builder(&filter).await?,
+            };
+            match public_key_fingerprint(&builder, value) {
+                Some(fingerprint) => {
+                    set.insert("fingerprint", fingerprint);
+                }
+                None => {
+                    unset.insert("fingerprint", "");
+                }
+            }
+            match value.last_modified() {
+                Some(last_modified) => {
+                    set.insert("last_modified", to_bson_datetime(last_modified));
+                }
+                None => {
+                    unset.insert("last_modified", "");
+                }
+            }
+        }
+        if let Some(ref metadata) = patch.metadata {
+            // Empty metadata is not stored, matching how entries are serialized
+            if metadata.is_empty() {
+                unset.insert("metadata", "");
+            } else {
+                set.insert(
+                    "metadata",
+                    bson::to_bson(metadata).map_err(|e| MongoStorerError::InternalError {
+                        source: Box::new(e),
+                    })?,
+                );
+            }
+        }
+        if set.is_empty() && unset.is_empty() {
+            return Ok(());
+        }
+        let mut update = Document::new();
+        if !set.is_empty() {
+            update.insert("$set", set);
+        }
+        if !unset.is_empty() {
+            update.insert("$unset", unset);
+        }
+
+        match self
+            .collection()
+            .await?
+            .update_one(filter, update, None)
+            .await
+        {
+            Ok(result) if result.matched_count == 0 => Err(MongoStorerError::NotFound.into()),
+            Ok(_) => Ok(()),
+            Err(e) => Err(MongoStorerError::InternalError {
+                source: Box::new(e),
+            }
+            .into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        log_range_filter, metadata_filter, transaction_retry_delay, where_filter, MongoStorer,
+        TRANSACTION_RETRY_BACKOFF,
+    };
+    use crate::{CryptoError, Data, HasIndex, IndexedStorer, Storer};
+    use mongodb::bson::{self, Bson};
+
+    #[tokio::test]
+    async fn test_delete_prefix_rejects_matching_everything() {
+        // Rejected before connecting, so no server is needed
+        let storer = MongoStorer::new("mongodb://localhost:1", "test", "entries");
+        for prefix in ["", "."].iter() {
+            match storer.delete_prefix(prefix).await {
+                Err(CryptoError::InvalidPathPrefix { .. }) => (),
+                _ => panic!("Prefix \"{}\" should have been rejected", prefix),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_health_check_unparseable_url() {
+        // The client cannot be built, so no server is needed
+        let storer = MongoStorer::new("not a mongodb url", "test", "entries");
+        match storer.health_check().await {
+            Err(CryptoError::StorerUnavailable { .. }) => (),
+            _ => panic!("Health check with an unusable client should have failed"),
+        }
+    }
+
+    #[test]
+    fn test_transaction_retry_delay_doubles() {
+        assert_eq!(transaction_retry_delay(1), TRANSACTION_RETRY_BACKOFF);
+        assert_eq!(transaction_retry_delay(3), TRANSACTION_RETRY_BACKOFF * 4);
+    }
+
+    #[test]
+    fn test_log_range_filter_clamps_open_ended_range() {
+        let filter = log_range_filter(".log.", 0, u64::MAX, Data::get_index());
+        let seq = filter.get_document("seq").unwrap();
+        assert_eq!(seq.get_i64("$gte").unwrap(), 0);
+        assert_eq!(seq.get_i64("$lt").unwrap(), i64::MAX);
+        assert_eq!(
+            filter.get_document("entry.value").unwrap(),
+            &Data::get_index().unwrap()
+        );
+
+        let filter = log_range_filter(".log.", u64::MAX - 1, u64::MAX, None);
+        let seq = filter.get_document("seq").unwrap();
+        assert_eq!(seq.get_i64("$gte").unwrap(), i64::MAX);
+        assert!(filter.get("entry.value").is_none());
+    }
+
+    #[test]
+    fn test_where_filter_ands_type_index() {
+        let filter = where_filter(
+            ".data.",
+            Data::get_index(),
+            bson::doc! { "value.c.bytes.b": "hello", "value": "overridden" },
+        );
+        let clauses = filter.get_array("$and").unwrap();
+        assert_eq!(filter.len(), 1);
+        assert_eq!(clauses.len(), 2);
+
+        let base = match &clauses[0] {
+            Bson::Document(d) => d,
+            _ => panic!("First clause should have been a document"),
+        };
+        assert_eq!(
+            base.get_document("value").unwrap(),
+            &Data::get_index().unwrap()
+        );
+        assert_eq!(
+            base.get_document("path")
+                .unwrap()
+                .get_str("$regex")
+                .unwrap(),
+            "^\\.data\\."
+        );
+        match &clauses[1] {
+            Bson::Document(d) => assert_eq!(d.get_str("value.c.bytes.b").unwrap(), "hello"),
+            _ => panic!("Second clause should have been the caller's filter"),
+        }
+    }
+
+    #[test]
+    fn test_metadata_filter_matches_subdocument_field() {
+        let filter = metadata_filter("owner", "alice").unwrap();
+        assert_eq!(filter.len(), 1);
+        assert_eq!(filter.get_str("metadata.owner").unwrap(), "alice");
+    }
+
+    #[test]
+    fn test_metadata_filter_rejects_nested_and_operator_keys() {
+        for key in ["", "owner.name", "$where", "$ne"].iter() {
+            match metadata_filter(key, "alice") {
+                Err(CryptoError::InvalidMetadataKey { .. }) => (),
+                _ => panic!("Metadata key \"{}\" should have been rejected", key),
+            }
+        }
+    }
+
+    #[cfg(feature = "mongodb-tests")]
+    #[tokio::test]
+    async fn test_with_collection_isolates_writes() {
+        use super::MongoStorer;
+        use crate::{CryptoError, ToEntry};
+        use std::sync::Arc;
+
+        let url = std::env::var("MONGODB_TEST_URL")
+            .unwrap_or_else(|_| "mongodb://localhost:27017".to_owned());
+        let tenant_a = MongoStorer::new(&url, "redact-crypto-test", "tenant-a");
+        let tenant_b = tenant_a.with_collection("tenant-b");
+        assert!(Arc::ptr_eq(&tenant_a.client, &tenant_b.client));
+
+        tenant_a
+            .create(
+                Data::U64(5)
+                    .to_unsealed_entry(".isolated.".to_owned())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert!(tenant_a.get::<Data>(".isolated.").await.is_ok());
+        match tenant_b.get::<Data>(".isolated.").await {
+            Err(CryptoError::NotFound { .. }) => (),
+            _ => panic!("Entry written to one collection should not be visible in another"),
+        }
+        assert!(tenant_b.client.get().is_some());
+        tenant_a.delete::<Data>(".isolated.").await.unwrap();
+    }
+
+    #[cfg(feature = "mongodb-tests")]
+    #[tokio::test]
+    async fn test_ensure_indexes_is_idempotent() {
+        let url = std::env::var("MONGODB_TEST_URL")
+            .unwrap_or_else(|_| "mongodb://localhost:27017".to_owned());
+        let storer = MongoStorer::new(&url, "redact-crypto-test", "indexes");
+        storer.ensure_indexes().await.unwrap();
+        storer.ensure_indexes().await.unwrap();
+    }
+
+    #[cfg(feature = "mongodb-tests")]
+    #[tokio::test]
+    async fn test_list_where_runs_filter_against_server() {
+        use crate::ToEntry;
+
+        let url = std::env::var("MONGODB_TEST_URL")
+            .unwrap_or_else(|_| "mongodb://localhost:27017".to_owned());
+        let storer = MongoStorer::new(&url, "redact-crypto-test", "list-where");
+        for (path, value) in [(".where.a.", 1), (".where.b.", 2), (".other.b.", 3)].iter() {
+            storer
+                .create(
+                    Data::U64(*value)
+                        .to_unsealed_entry((*path).to_owned())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+        }
+
+        let entries = storer
+            .list_where::<Data>(
+                ".where.",
+                bson::doc! { "path": { "$in": [".where.b.", ".other.b."] } },
+                0,
+                10,
+            )
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, ".where.b.");
+        assert_eq!(entries[0].resolve().await.unwrap(), &Data::U64(2));
+
+        for path in [".where.a.", ".where.b.", ".other.b."].iter() {
+            storer.delete::<Data>(path).await.unwrap();
+        }
+    }
 }
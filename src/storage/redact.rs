@@ -1,7 +1,11 @@
 use crate::{
-    CryptoError, Entry, IndexedStorer, IndexedTypeStorer, StorableType, Storer, TypeStorer,
+    storage::{check_page_size, default_max_page_size, deletion_prefix, validate_max_page_size},
+    CryptoError, Entry, EntryPatch, IndexedStorer, IndexedTypeStorer, StorableType, Storer,
+    TypeStorer,
 };
 use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
 use mongodb::bson::Document;
 use once_cell::sync::Lazy;
 use reqwest::StatusCode;
@@ -112,6 +116,8 @@ impl ClientTlsConfig {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct RedactStorer {
     url: String,
+    #[serde(default = "default_max_page_size")]
+    max_page_size: i64,
 }
 
 /// Stores an instance of a redact-backed key storer.
@@ -120,8 +126,20 @@ impl RedactStorer {
     pub fn new(url: &str) -> Self {
         Self {
             url: url.to_owned(),
+            max_page_size: default_max_page_size(),
         }
     }
+
+    /// Sets the largest page size accepted when listing entries, which must be positive
+    pub fn with_max_page_size(mut self, max: i64) -> Result<Self, CryptoError> {
+        self.max_page_size = validate_max_page_size(max)?;
+        Ok(self)
+    }
+
+    /// Returns the largest page size accepted when listing entries
+    pub fn max_page_size(&self) -> i64 {
+        self.max_page_size
+    }
 }
 
 impl From<RedactStorer> for IndexedTypeStorer {
@@ -202,6 +220,217 @@ impl RedactStorer {
     }
 }
 
+impl RedactStorer {
+    /// Lists entries like `list`, but parses them one at a time as the response body
+    /// arrives instead of buffering the whole body first. Entries that were already
+    /// parsed are yielded even if the body later turns out to be truncated or malformed,
+    /// in which case the stream ends with an error.
+    pub async fn list_stream<T: StorableType>(
+        &self,
+        path: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<BoxStream<'static, Result<Entry<T>, CryptoError>>, CryptoError> {
+        self.list_indexed_stream::<T>(path, skip, page_size, &T::get_index())
+            .await
+    }
+
+    async fn list_indexed_stream<T: StorableType>(
+        &self,
+        path: &str,
+        skip: u64,
+        page_size: i64,
+        index: &Option<Document>,
+    ) -> Result<BoxStream<'static, Result<Entry<T>, CryptoError>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let mut req_url = format!(
+            "{}/{}?skip={}&page_size={}",
+            &self.url,
+            encode_path(path),
+            skip,
+            page_size
+        );
+        if let Some(i) = index {
+            req_url.push_str(format!("&index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+
+        let response = match http_client.get(&req_url).send().await {
+            Ok(r) => r.error_for_status().map_err(|source| -> CryptoError {
+                if source.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                    RedactStorerError::NotFound.into()
+                } else {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                }
+            })?,
+            Err(source) => {
+                return Err(RedactStorerError::InternalError {
+                    source: Box::new(source),
+                }
+                .into())
+            }
+        };
+
+        let body = response.bytes_stream().map_err(|source| -> CryptoError {
+            RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()
+        });
+        Ok(stream::try_unfold(
+            (Box::pin(body), JsonArrayReader::default()),
+            |(mut body, mut reader)| async move {
+                loop {
+                    if let Some(element) = reader.next_element()? {
+                        let entry =
+                            serde_json::from_slice::<Entry<T>>(&element).map_err(json_error)?;
+                        return Ok(Some((entry, (body, reader))));
+                    }
+                    match body.next().await {
+                        Some(chunk) => reader.push(&chunk?),
+                        None => return reader.finish().map(|_| None),
+                    }
+                }
+            },
+        )
+        .boxed())
+    }
+}
+
+/// Splits a JSON array arriving in chunks into the bytes of its elements, so that each
+/// element can be deserialized as soon as it is complete. Only the nesting of brackets
+/// and strings is tracked; the elements themselves are validated when deserialized.
+#[derive(Default)]
+struct JsonArrayReader {
+    buf: Vec<u8>,
+    /// Offset in `buf` up to which bytes have been scanned
+    scanned: usize,
+    /// Offset in `buf` at which the element being scanned starts
+    element_start: Option<usize>,
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    opened: bool,
+    closed: bool,
+}
+
+impl JsonArrayReader {
+    fn push(&mut self, chunk: &[u8]) {
+        self.buf.extend_from_slice(chunk);
+    }
+
+    /// Returns the next complete element, or `None` if more bytes are needed or the
+    /// array was closed
+    fn next_element(&mut self) -> Result<Option<Vec<u8>>, CryptoError> {
+        while self.scanned < self.buf.len() && !self.closed {
+            let i = self.scanned;
+            let byte = self.buf[i];
+            self.scanned += 1;
+
+            if !self.opened {
+                match byte {
+                    b'[' => self.opened = true,
+                    b if b.is_ascii_whitespace() => (),
+                    _ => return Err(json_error(malformed_array())),
+                }
+                continue;
+            }
+
+            let start = match self.element_start {
+                Some(start) => start,
+                None => {
+                    match byte {
+                        b']' => self.closed = true,
+                        b',' => (),
+                        b if b.is_ascii_whitespace() => (),
+                        _ => {
+                            self.element_start = Some(i);
+                            self.scanned = i;
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            if self.in_string {
+                match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => (),
+                }
+                continue;
+            }
+            match byte {
+                b'"' => self.in_string = true,
+                b'{' | b'[' => self.depth += 1,
+                b'}' | b']' if self.depth > 0 => self.depth -= 1,
+                b',' | b']' if self.depth == 0 => {
+                    self.closed = byte == b']';
+                    let element = self.buf[start..i].to_vec();
+                    self.buf.drain(..self.scanned);
+                    self.scanned = 0;
+                    self.element_start = None;
+                    return Ok(Some(element));
+                }
+                _ => (),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Checks that the array was closed once the body has ended
+    fn finish(&self) -> Result<(), CryptoError> {
+        if self.closed {
+            Ok(())
+        } else {
+            Err(json_error(malformed_array()))
+        }
+    }
+}
+
+fn malformed_array() -> serde_json::Error {
+    serde::de::Error::custom("response body is not a complete JSON array")
+}
+
+/// Percent-encodes an entry path for use as a single segment of a request URL. Every
+/// byte outside of the unreserved characters of RFC 3986 is encoded, including `/`, `?`
+/// and `#`, so a path can neither add URL segments nor start the query or fragment.
+/// The server is expected to decode the segment back into the entry path.
+fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for b in path.bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                encoded.push(b as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Returns the URL of the given entry path under the storer's URL, for requests that
+/// append an endpoint after the path. An empty path is the storer's URL itself rather
+/// than an empty segment, so the endpoint is never preceded by `//`.
+fn path_url(url: &str, path: &str) -> String {
+    if path.is_empty() {
+        url.to_owned()
+    } else {
+        format!("{}/{}", url, encode_path(path))
+    }
+}
+
+fn json_error(source: serde_json::Error) -> CryptoError {
+    RedactStorerError::InternalError {
+        source: Box::new(source),
+    }
+    .into()
+}
+
 #[async_trait]
 impl IndexedStorer for RedactStorer {
     async fn get_indexed<T: StorableType>(
@@ -209,7 +438,7 @@ impl IndexedStorer for RedactStorer {
         path: &str,
         index: &Option<Document>,
     ) -> Result<Entry<T>, CryptoError> {
-        let mut req_url = format!("{}/{}?", &self.url, path);
+        let mut req_url = format!("{}/{}?", &self.url, encode_path(path));
         if let Some(i) = index {
             req_url.push_str(format!("index={}", i).as_ref());
         }
@@ -250,15 +479,248 @@ impl IndexedStorer for RedactStorer {
         page_size: i64,
         index: &Option<Document>,
     ) -> Result<Vec<Entry<T>>, CryptoError> {
+        self.list_indexed_stream::<T>(path, skip, page_size, index)
+            .await?
+            .try_collect()
+            .await
+    }
+
+    async fn count<T: StorableType>(&self, path_prefix: &str) -> Result<u64, CryptoError> {
+        let mut req_url = format!("{}/count?", path_url(&self.url, path_prefix));
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+
+        match http_client.get(&req_url).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<u64>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn list_modified_since<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        since: DateTime<Utc>,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
         let mut req_url = format!(
-            "{}/{}?skip={}&page_size={}",
-            &self.url, path, skip, page_size
+            "{}/{}?skip={}&page_size={}&modified_since={}",
+            &self.url,
+            encode_path(path_prefix),
+            skip,
+            page_size,
+            since.timestamp_millis()
         );
-        if let Some(i) = index {
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("&index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+
+        match http_client.get(&req_url).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<Vec<Entry<T>>>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn list_by_algorithm<T: StorableType>(
+        &self,
+        path_prefix: &str,
+        oid: &str,
+        skip: u64,
+        page_size: i64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        check_page_size(page_size, self.max_page_size)?;
+        let mut req_url = format!(
+            "{}/{}?skip={}&page_size={}&algorithm_oid={}",
+            &self.url,
+            encode_path(path_prefix),
+            skip,
+            page_size,
+            oid
+        );
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("&index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+
+        match http_client.get(&req_url).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<Vec<Entry<T>>>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn delete_prefix(&self, prefix: &str) -> Result<u64, CryptoError> {
+        let req_url = format!(
+            "{}/{}?recursive=true",
+            &self.url,
+            encode_path(&deletion_prefix(prefix)?)
+        );
+        let http_client = RedactStorer::get_http_client()?;
+
+        match http_client.delete(&req_url).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<u64>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn append<T: StorableType>(
+        &self,
+        collection_path: &str,
+        entry: Entry<T>,
+    ) -> Result<u64, CryptoError> {
+        let req_url = format!("{}/log", path_url(&self.url, collection_path));
+        let http_client = RedactStorer::get_http_client()?;
+
+        match http_client.post(&req_url).json(&entry).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<u64>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn read_range<T: StorableType>(
+        &self,
+        collection_path: &str,
+        from_seq: u64,
+        to_seq: u64,
+    ) -> Result<Vec<Entry<T>>, CryptoError> {
+        let mut req_url = format!(
+            "{}/log?from_seq={}&to_seq={}",
+            path_url(&self.url, collection_path),
+            from_seq,
+            to_seq
+        );
+        if let Some(i) = T::get_index() {
             req_url.push_str(format!("&index={}", i).as_ref());
         }
         let http_client = RedactStorer::get_http_client()?;
 
+        match http_client.get(&req_url).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?
+                .json::<Vec<Entry<T>>>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn get_by_fingerprint<T: StorableType>(
+        &self,
+        fingerprint: &str,
+    ) -> Result<Entry<T>, CryptoError> {
+        let mut req_url = format!("{}/by-fingerprint/{}?", &self.url, fingerprint);
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+
         match http_client.get(&req_url).send().await {
             Ok(r) => Ok(r
                 .error_for_status()
@@ -272,7 +734,7 @@ impl IndexedStorer for RedactStorer {
                         .into()
                     }
                 })?
-                .json::<Vec<Entry<T>>>()
+                .json::<Entry<T>>()
                 .await
                 .map_err(|source| -> CryptoError {
                     RedactStorerError::InternalError {
@@ -290,8 +752,22 @@ impl IndexedStorer for RedactStorer {
 
 #[async_trait]
 impl Storer for RedactStorer {
+    async fn health_check(&self) -> Result<(), CryptoError> {
+        let req_url = format!("{}/health", &self.url);
+        let http_client = RedactStorer::get_http_client()?;
+        http_client
+            .get(&req_url)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|source| CryptoError::StorerUnavailable {
+                source: Box::new(source),
+            })
+    }
+
     async fn delete<T: StorableType>(&self, path: &str) -> Result<(), CryptoError> {
-        let req_url = format!("{}/{}?", &self.url, path);
+        let req_url = format!("{}/{}?", &self.url, encode_path(path));
         let http_client = RedactStorer::get_http_client()?;
         match http_client.delete(&req_url).send().await {
             Ok(r) => r
@@ -314,14 +790,90 @@ impl Storer for RedactStorer {
         }
     }
 
+    async fn apply_patch<T: StorableType>(
+        &self,
+        path: &str,
+        patch: EntryPatch,
+    ) -> Result<(), CryptoError> {
+        let mut req_url = format!("{}/{}?", &self.url, encode_path(path));
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+        match http_client.patch(&req_url).json(&patch).send().await {
+            Ok(r) => r
+                .error_for_status()
+                .map(|_| ())
+                .map_err(|source| -> CryptoError {
+                    if source.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                        RedactStorerError::NotFound.into()
+                    } else {
+                        RedactStorerError::InternalError {
+                            source: Box::new(source),
+                        }
+                        .into()
+                    }
+                }),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
+    async fn rename<T: StorableType>(&self, from: &str, to: &str) -> Result<Entry<T>, CryptoError> {
+        let mut req_url = format!("{}/move?", &self.url);
+        if let Some(i) = T::get_index() {
+            req_url.push_str(format!("index={}", i).as_ref());
+        }
+        let http_client = RedactStorer::get_http_client()?;
+        let body = serde_json::json!({ "from": from, "to": to });
+
+        match http_client.post(&req_url).json(&body).send().await {
+            Ok(r) => Ok(r
+                .error_for_status()
+                .map_err(|source| -> CryptoError {
+                    if source.status() == Some(reqwest::StatusCode::NOT_FOUND) {
+                        RedactStorerError::NotFound.into()
+                    } else {
+                        RedactStorerError::InternalError {
+                            source: Box::new(source),
+                        }
+                        .into()
+                    }
+                })?
+                .json::<Entry<T>>()
+                .await
+                .map_err(|source| -> CryptoError {
+                    RedactStorerError::InternalError {
+                        source: Box::new(source),
+                    }
+                    .into()
+                })?),
+            Err(source) => Err(RedactStorerError::InternalError {
+                source: Box::new(source),
+            }
+            .into()),
+        }
+    }
+
     async fn get<T: StorableType>(&self, path: &str) -> Result<Entry<T>, CryptoError> {
         self.get_indexed::<T>(path, &T::get_index()).await
     }
 
     async fn create<T: StorableType>(&self, entry: Entry<T>) -> Result<Entry<T>, CryptoError> {
-        let value = serde_json::to_value(&entry).map_err(|e| RedactStorerError::InternalError {
-            source: Box::new(e),
-        })?;
+        let mut value =
+            serde_json::to_value(&entry).map_err(|e| RedactStorerError::InternalError {
+                source: Box::new(e),
+            })?;
+        if let Some(map) = value.as_object_mut() {
+            if let Some(fingerprint) = entry.fingerprint() {
+                map.insert("fingerprint".to_owned(), fingerprint.into());
+            }
+            if let Some(algorithm_oid) = entry.algorithm_oid() {
+                map.insert("algorithm_oid".to_owned(), algorithm_oid.into());
+            }
+        }
         let http_client = RedactStorer::get_http_client()?;
 
         http_client
@@ -349,3 +901,228 @@ impl Storer for RedactStorer {
             })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_path, path_url, JsonArrayReader, RedactStorer};
+    use crate::{CryptoError, Data, Entry, IndexedStorer, Storer, ToEntry};
+    use futures::StreamExt;
+    use std::time::Duration;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        sync::{mpsc, oneshot},
+    };
+
+    #[test]
+    fn test_jsonarrayreader_splits_elements_across_chunks() {
+        let json = br#" [ {"a": "x,]}\"", "b": [1, {"c": 2}]}, 3 ,"s"] "#;
+        let mut reader = JsonArrayReader::default();
+        let mut elements = vec![];
+        for chunk in json.chunks(3) {
+            reader.push(chunk);
+            while let Some(element) = reader.next_element().unwrap() {
+                elements.push(serde_json::from_slice::<serde_json::Value>(&element).unwrap());
+            }
+        }
+        reader.finish().unwrap();
+        assert_eq!(
+            elements,
+            vec![
+                serde_json::json!({"a": "x,]}\"", "b": [1, {"c": 2}]}),
+                serde_json::json!(3),
+                serde_json::json!("s"),
+            ]
+        );
+
+        let mut truncated = JsonArrayReader::default();
+        truncated.push(b"[1, 2");
+        assert_eq!(truncated.next_element().unwrap(), Some(b"1".to_vec()));
+        assert_eq!(truncated.next_element().unwrap(), None);
+        assert!(truncated.finish().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_prefix_rejects_matching_everything() {
+        // Rejected before any request is sent, so no server is needed
+        let storer = RedactStorer::new("http://127.0.0.1:1");
+        for prefix in ["", "."].iter() {
+            match storer.delete_prefix(prefix).await {
+                Err(CryptoError::InvalidPathPrefix { .. }) => (),
+                _ => panic!("Prefix \"{}\" should have been rejected", prefix),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_list_stream_parses_entries_incrementally() {
+        let entries = (0..1000u64)
+            .map(|i| {
+                Data::U64(i)
+                    .to_unsealed_entry(format!(".data.{}.", i))
+                    .unwrap()
+            })
+            .collect::<Vec<Entry<Data>>>();
+        let body = serde_json::to_vec(&entries).unwrap();
+
+        // Sends the first half of the body, and the rest only once the client has
+        // received an entry, which it cannot do if it buffers the whole body first
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let (first_received, wait_first) = oneshot::channel::<()>();
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+            while !String::from_utf8_lossy(&request).contains("\r\n\r\n") {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(headers.as_bytes()).await.unwrap();
+            let (first_half, second_half) = body.split_at(body.len() / 2);
+            socket.write_all(first_half).await.unwrap();
+            socket.flush().await.unwrap();
+            wait_first.await.unwrap();
+            socket.write_all(second_half).await.unwrap();
+        });
+
+        let mut stream = RedactStorer::new(&url)
+            .list_stream::<Data>(".data.", 0, 1000)
+            .await
+            .unwrap();
+        let (tx, mut rx) = mpsc::channel(8);
+        let forwarder = tokio::spawn(async move {
+            while let Some(entry) = stream.next().await {
+                tx.send(entry.unwrap()).await.unwrap();
+            }
+        });
+
+        let received = tokio::time::timeout(Duration::from_secs(10), async move {
+            let mut first_received = Some(first_received);
+            let mut received = vec![];
+            while let Some(entry) = rx.recv().await {
+                if let Some(signal) = first_received.take() {
+                    signal.send(()).unwrap();
+                }
+                received.push(entry.take_resolve().await.unwrap());
+            }
+            received
+        })
+        .await
+        .expect("Entries should have been yielded before the whole body arrived");
+
+        server.await.unwrap();
+        forwarder.await.unwrap();
+        assert_eq!(received, (0..1000).map(Data::U64).collect::<Vec<_>>());
+    }
+
+    /// Serves a single request with the given JSON body and returns the server's URL
+    /// along with a handle to the full request it received
+    async fn serve_once(body: Vec<u8>) -> (String, tokio::task::JoinHandle<String>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = vec![];
+            let mut buf = [0u8; 1024];
+            let header_end = loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break i + 4;
+                }
+            };
+            let content_length = String::from_utf8_lossy(&request[..header_end])
+                .lines()
+                .find_map(|l| {
+                    let (name, value) = l.split_once(':')?;
+                    if name.eq_ignore_ascii_case("content-length") {
+                        value.trim().parse::<usize>().ok()
+                    } else {
+                        None
+                    }
+                })
+                .unwrap_or(0);
+            while request.len() < header_end + content_length {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+            }
+            let headers = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            socket.write_all(headers.as_bytes()).await.unwrap();
+            socket.write_all(&body).await.unwrap();
+            String::from_utf8(request).unwrap()
+        });
+        (url, server)
+    }
+
+    #[test]
+    fn test_encode_path() {
+        assert_eq!(encode_path(".a b.c?d."), ".a%20b.c%3Fd.");
+        assert_eq!(encode_path(".a/b#c%d."), ".a%2Fb%23c%25d.");
+        assert_eq!(encode_path(".keys.default-1_x~."), ".keys.default-1_x~.");
+    }
+
+    #[test]
+    fn test_path_url_skips_empty_path() {
+        assert_eq!(path_url("http://localhost", ""), "http://localhost");
+        assert_eq!(
+            path_url("http://localhost", ".a b."),
+            "http://localhost/.a%20b."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_count_url() {
+        for (prefix, expected) in [("", "GET /count?"), (".a b.", "GET /.a%20b./count?")].iter() {
+            let (url, server) = serve_once(b"3".to_vec()).await;
+            let count = RedactStorer::new(&url).count::<Data>(prefix).await.unwrap();
+            let request = server.await.unwrap();
+            assert!(request.starts_with(expected), "{}", request);
+            assert_eq!(count, 3);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_and_create_encode_path() {
+        let path = ".a b.c?d.";
+        let entry = Data::String("hello, world!".to_owned())
+            .to_unsealed_entry(path.to_owned())
+            .unwrap();
+
+        let (url, server) = serve_once(serde_json::to_vec(&entry).unwrap()).await;
+        let fetched = RedactStorer::new(&url).get::<Data>(path).await.unwrap();
+        let request = server.await.unwrap();
+        assert!(request.starts_with("GET /.a%20b.c%3Fd.?"));
+        assert_eq!(fetched.path, path);
+        assert_eq!(
+            fetched.resolve().await.unwrap(),
+            &Data::String("hello, world!".to_owned())
+        );
+
+        let (url, server) = serve_once(b"{}".to_vec()).await;
+        let created = RedactStorer::new(&url).create(entry).await.unwrap();
+        let request = server.await.unwrap();
+        assert!(request.starts_with("POST / "));
+        assert!(request.contains("\"path\":\".a b.c?d.\""));
+        assert_eq!(created.path, path);
+    }
+
+    #[tokio::test]
+    async fn test_health_check_unreachable() {
+        // Bind and immediately release a port so nothing is listening on it
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        match RedactStorer::new(&url).health_check().await {
+            Err(CryptoError::StorerUnavailable { .. }) => (),
+            _ => panic!("Health check against an unreachable storer should have failed"),
+        }
+    }
+}
@@ -0,0 +1,157 @@
+//! Instrumentation of entry resolution and storer access with `tracing`, enabled by the
+//! `tracing` feature. Each operation runs in a span carrying its name and the entry path,
+//! and ends with an event recording its outcome and duration. Entry values and key
+//! material are never recorded. Without the feature, `traced!` expands to the bare
+//! future and nothing is evaluated for instrumentation.
+
+#[cfg(feature = "tracing")]
+use crate::CryptoError;
+#[cfg(feature = "tracing")]
+use std::{future::Future, time::Instant};
+#[cfg(feature = "tracing")]
+use tracing::{Instrument, Span};
+
+/// Runs the future in a span for the given operation and entry path when the `tracing`
+/// feature is enabled
+#[cfg(feature = "tracing")]
+macro_rules! traced {
+    ($operation:expr, $path:expr, $future:expr) => {{
+        let span = $crate::trace::span($operation, $path);
+        $crate::trace::instrument(span, $future)
+    }};
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! traced {
+    ($operation:expr, $path:expr, $future:expr) => {{
+        $future
+    }};
+}
+
+pub(crate) use traced;
+
+#[cfg(feature = "tracing")]
+pub(crate) fn span(operation: &'static str, path: &str) -> Span {
+    tracing::debug_span!("redact_crypto", operation, path)
+}
+
+#[cfg(feature = "tracing")]
+pub(crate) async fn instrument<F, R>(span: Span, future: F) -> Result<R, CryptoError>
+where
+    F: Future<Output = Result<R, CryptoError>>,
+{
+    let start = Instant::now();
+    let result = future.instrument(span.clone()).await;
+    let duration_us = start.elapsed().as_micros() as u64;
+    match result {
+        Ok(_) => tracing::debug!(parent: &span, outcome = "ok", duration_us = duration_us),
+        Err(ref e) => tracing::debug!(
+            parent: &span,
+            outcome = "error",
+            error = %e,
+            duration_us = duration_us
+        ),
+    }
+    result
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{
+        key::sodiumoxide::SodiumOxideSymmetricKey, Entry, HasByteSource, MemoryStorer, Storer,
+        ToEntry,
+    };
+    use std::{
+        fmt::{self, Write},
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            Arc, Mutex,
+        },
+    };
+    use tracing::{
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+        Event, Metadata, Subscriber,
+    };
+
+    /// Records every span and event as a line of `name field=value` pairs
+    struct RecordingSubscriber {
+        lines: Arc<Mutex<Vec<String>>>,
+        next_id: AtomicU64,
+    }
+
+    struct FieldRecorder(String);
+
+    impl Visit for FieldRecorder {
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+
+    impl Subscriber for RecordingSubscriber {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut recorder = FieldRecorder(format!("span {}", span.metadata().name()));
+            span.record(&mut recorder);
+            self.lines.lock().unwrap().push(recorder.0);
+            Id::from_u64(self.next_id.fetch_add(1, Ordering::SeqCst) + 1)
+        }
+
+        fn record(&self, _: &Id, values: &Record<'_>) {
+            let mut recorder = FieldRecorder("record".to_owned());
+            values.record(&mut recorder);
+            self.lines.lock().unwrap().push(recorder.0);
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut recorder = FieldRecorder("event".to_owned());
+            event.record(&mut recorder);
+            self.lines.lock().unwrap().push(recorder.0);
+        }
+
+        fn enter(&self, _: &Id) {}
+
+        fn exit(&self, _: &Id) {}
+    }
+
+    #[tokio::test]
+    async fn test_resolve_referenced_entry_is_traced() {
+        let lines = Arc::new(Mutex::new(vec![]));
+        let _guard = tracing::subscriber::set_default(RecordingSubscriber {
+            lines: lines.clone(),
+            next_id: AtomicU64::new(0),
+        });
+
+        let storer = MemoryStorer::new();
+        let key = SodiumOxideSymmetricKey::new();
+        let key_bytes = key.byte_source().get().unwrap().to_vec();
+        storer
+            .create(key.to_unsealed_entry(".keys.kek.".to_owned()).unwrap())
+            .await
+            .unwrap();
+        let entry = Entry::<SodiumOxideSymmetricKey>::reference(".keys.kek.".to_owned(), storer);
+        entry.resolve().await.unwrap();
+
+        let lines = lines.lock().unwrap();
+        assert!(lines
+            .iter()
+            .any(|l| l == "span redact_crypto operation=\"resolve\" path=\".keys.kek.\""));
+        assert!(lines
+            .iter()
+            .any(|l| l == "span redact_crypto operation=\"get\" path=\".keys.kek.\""));
+        assert!(lines.iter().any(|l| l.starts_with("event outcome=\"ok\"")));
+        let encoded = [
+            base64::encode(&key_bytes),
+            hex::encode(&key_bytes),
+            format!("{:?}", key_bytes),
+        ];
+        assert!(lines
+            .iter()
+            .all(|l| encoded.iter().all(|e| !l.contains(e.as_str()))));
+    }
+}